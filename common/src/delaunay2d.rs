@@ -16,6 +16,14 @@ impl PartialEq for Edge {
 
 impl Eq for Edge {}
 
+impl Edge {
+    /// Checks whether two edges connect the same vertices regardless of their direction.
+    pub fn is_same_undirected(&self, other: &Edge) -> bool {
+        *self == *other
+            || (almost_equal_vec2(self.u, other.v) && almost_equal_vec2(self.v, other.u))
+    }
+}
+
 #[derive(Default)]
 pub struct Triangle {
     pub a: Vec2,
@@ -40,6 +48,15 @@ impl Triangle {
             || Vec2::distance(v, self.c) < 0.01
     }
 
+    /// Checks whether the point lies inside the triangle or on its border.
+    fn encloses(&self, point: Vec2) -> bool {
+        let ab = (self.b - self.a).perp_dot(point - self.a);
+        let bc = (self.c - self.b).perp_dot(point - self.b);
+        let ca = (self.a - self.c).perp_dot(point - self.c);
+
+        (ab >= 0. && bc >= 0. && ca >= 0.) || (ab <= 0. && bc <= 0. && ca <= 0.)
+    }
+
     pub fn circum_circle_contains(&self, v: &Vec2) -> bool {
         let ab = self.a.length_squared();
         let cd = self.b.length_squared();
@@ -67,7 +84,13 @@ impl Triangle {
 pub struct Delaunay2D {
     vertices: Vec<Vec2>,
     pub edges: Vec<Edge>,
+    /// All the triangles including the ones sharing vertices with the super triangle.
+    /// The latter are kept for the incremental insertion but aren't part of the result.
     triangles: Vec<Triangle>,
+    /// A triangle enclosing all the points of the initial triangulation.
+    super_triangle: Option<[Vec2; 3]>,
+    /// Bounds of the points the super triangle was built for.
+    bounds: FRect,
 }
 
 impl Delaunay2D {
@@ -76,8 +99,10 @@ impl Delaunay2D {
             edges: vec![],
             triangles: vec![],
             vertices,
+            super_triangle: None,
+            bounds: FRect::default(),
         };
-        res.triangulate_internal();
+        res.retriangulate();
         res
     }
 
@@ -86,6 +111,8 @@ impl Delaunay2D {
             edges: vec![],
             triangles: vec![],
             vertices: vec![],
+            super_triangle: None,
+            bounds: FRect::default(),
         };
 
         for i in 0..rooms.len() {
@@ -103,6 +130,81 @@ impl Delaunay2D {
         res
     }
 
+    /// Gets all the points of the triangulation.
+    pub fn vertices(&self) -> &[Vec2] {
+        &self.vertices
+    }
+
+    /// Inserts a point re-triangulating only the triangles whose circumcircles contain it.
+    /// If the point lies outside of the bounds of the points the triangulation was
+    /// initially built for, the whole triangulation is rebuilt.
+    pub fn insert_point(&mut self, point: Vec2) {
+        self.vertices.push(point);
+
+        if self.super_triangle.is_some() && self.bounds.contains(point) {
+            self.insert_internal(point);
+            self.rebuild_edges();
+        } else {
+            self.retriangulate();
+        }
+    }
+
+    /// Removes a point re-triangulating only the hole left by the triangles sharing it.
+    /// Returns `false` if the triangulation has no such point.
+    pub fn remove_point(&mut self, point: Vec2) -> bool {
+        let len = self.vertices.len();
+        self.vertices.retain(|v| !almost_equal_vec2(*v, point));
+
+        if self.vertices.len() == len {
+            return false;
+        }
+
+        if self.super_triangle.is_none() || !self.remove_internal(point) {
+            self.retriangulate();
+        } else {
+            self.rebuild_edges();
+        }
+        true
+    }
+
+    /// Gets the points connected by an edge to the point of the triangulation
+    /// which is the closest to `point`.
+    pub fn nearest_neighbors(&self, point: Vec2) -> Vec<Vec2> {
+        let Some(nearest) = self.vertices.iter().min_by(|a, b| {
+            a.distance_squared(point)
+                .total_cmp(&b.distance_squared(point))
+        }) else {
+            return vec![];
+        };
+
+        let mut res: Vec<Vec2> = vec![];
+        for edge in &self.edges {
+            let other = if almost_equal_vec2(edge.u, *nearest) {
+                edge.v
+            } else if almost_equal_vec2(edge.v, *nearest) {
+                edge.u
+            } else {
+                continue;
+            };
+
+            if !res.iter().any(|v| almost_equal_vec2(*v, other)) {
+                res.push(other);
+            }
+        }
+
+        res
+    }
+
+    fn retriangulate(&mut self) {
+        self.triangles.clear();
+        self.edges.clear();
+        self.super_triangle = None;
+
+        if !self.vertices.is_empty() {
+            self.triangulate_internal();
+        }
+    }
+
     fn triangulate_internal(&mut self) {
         let mut min_x = self.vertices[0].x;
         let mut min_y = self.vertices[0].y;
@@ -132,37 +234,117 @@ impl Delaunay2D {
         let p2 = Vec2::new(min_x - 1., max_y + dt_max);
         let p3 = Vec2::new(max_x + dt_max, min_y - 1.);
 
+        self.bounds = FRect::from_min_max(min_x, min_y, max_x, max_y);
+        self.super_triangle = Some([p1, p2, p3]);
         self.triangles.push(Triangle::new(p1, p2, p3));
 
-        for vertex in &self.vertices {
-            let mut polygon = Vec::new();
-
-            for t in self.triangles.iter_mut() {
-                if t.circum_circle_contains(vertex) {
-                    t.is_bad = true;
-                    polygon.push(Edge { u: t.a, v: t.b });
-                    polygon.push(Edge { u: t.b, v: t.c });
-                    polygon.push(Edge { u: t.c, v: t.a });
-                }
+        for i in 0..self.vertices.len() {
+            let vertex = self.vertices[i];
+            self.insert_internal(vertex);
+        }
+
+        self.rebuild_edges();
+    }
+
+    /// A single step of the Bowyer-Watson algorithm.
+    fn insert_internal(&mut self, vertex: Vec2) {
+        let mut polygon = Vec::new();
+
+        for t in self.triangles.iter_mut() {
+            if t.circum_circle_contains(&vertex) {
+                t.is_bad = true;
+                polygon.push(Edge { u: t.a, v: t.b });
+                polygon.push(Edge { u: t.b, v: t.c });
+                polygon.push(Edge { u: t.c, v: t.a });
             }
+        }
+
+        // remove bad rectangles
+        self.triangles.retain(|x| !x.is_bad);
+
+        // edges shared by two bad triangles are inside the hole, only its boundary is kept
+        let boundary: Vec<Edge> = polygon
+            .iter()
+            .filter(|edge| {
+                polygon
+                    .iter()
+                    .filter(|other| edge.is_same_undirected(other))
+                    .count()
+                    == 1
+            })
+            .copied()
+            .collect();
+
+        for edge in boundary {
+            self.triangles.push(Triangle::new(edge.u, edge.v, vertex));
+        }
+    }
 
-            // remove bad rectangles
-            self.triangles.retain(|x| !x.is_bad);
+    /// Removes the triangles sharing the vertex and fills the hole with the triangles
+    /// of the triangulation of the hole's vertices which lie inside of the hole.
+    /// Returns `false` if the hole couldn't be filled, so the triangles must be rebuilt.
+    fn remove_internal(&mut self, vertex: Vec2) -> bool {
+        let (hole, kept): (Vec<Triangle>, Vec<Triangle>) = std::mem::take(&mut self.triangles)
+            .into_iter()
+            .partition(|t| t.contains(vertex));
+        self.triangles = kept;
 
-            for edge in polygon {
-                self.triangles.push(Triangle::new(edge.u, edge.v, *vertex));
+        let mut hole_vertices: Vec<Vec2> = vec![];
+        for t in &hole {
+            for v in [t.a, t.b, t.c] {
+                if !almost_equal_vec2(v, vertex)
+                    && !hole_vertices.iter().any(|h| almost_equal_vec2(*h, v))
+                {
+                    hole_vertices.push(v);
+                }
             }
         }
 
-        self.triangles
-            .retain(|x| !x.contains(p1) && !x.contains(p2) && !x.contains(p3));
+        if hole_vertices.len() < 3 {
+            return false;
+        }
+
+        // Every triangle of the filled hole is a Delaunay triangle of the hole's vertices
+        let local = Delaunay2D::triangulate(hole_vertices.clone());
+        let Some([p1, p2, p3]) = local.super_triangle else {
+            return false;
+        };
+        let filling: Vec<Triangle> = local
+            .triangles
+            .into_iter()
+            .filter(|t| !t.contains(p1) && !t.contains(p2) && !t.contains(p3))
+            .filter(|t| {
+                let centroid = (t.a + t.b + t.c) / 3.;
+                hole.iter().any(|h| h.encloses(centroid))
+            })
+            .collect();
+
+        // A polygon with n vertices is always split into n - 2 triangles
+        if filling.len() != hole_vertices.len() - 2 {
+            return false;
+        }
+
+        self.triangles.extend(filling);
+        true
+    }
+
+    fn rebuild_edges(&mut self) {
+        self.edges.clear();
+
+        let Some([p1, p2, p3]) = self.super_triangle else {
+            return;
+        };
 
         let mut added_edges = vec![];
 
-        for t in &self.triangles {
+        for t in self
+            .triangles
+            .iter()
+            .filter(|x| !x.contains(p1) && !x.contains(p2) && !x.contains(p3))
+        {
             let ab = Edge { u: t.a, v: t.b };
             let bc = Edge { u: t.b, v: t.c };
-            let ca = Edge { u: t.c, v: t.c };
+            let ca = Edge { u: t.c, v: t.a };
 
             if !added_edges.contains(&ab) {
                 added_edges.push(ab);
@@ -179,3 +361,100 @@ impl Delaunay2D {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Gets the edges as sorted pairs of points, so they can be compared regardless of their order.
+    fn sorted_edges(delaunay: &Delaunay2D) -> Vec<[(i32, i32); 2]> {
+        let key = |v: Vec2| ((v.x * 100.0).round() as i32, (v.y * 100.0).round() as i32);
+        let mut edges: Vec<[(i32, i32); 2]> = delaunay
+            .edges
+            .iter()
+            .map(|edge| {
+                let mut pair = [key(edge.u), key(edge.v)];
+                pair.sort();
+                pair
+            })
+            .collect();
+        edges.sort();
+        edges.dedup();
+        edges
+    }
+
+    fn points() -> Vec<Vec2> {
+        vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(10.0, 1.0),
+            Vec2::new(9.0, 11.0),
+            Vec2::new(-1.0, 9.0),
+            Vec2::new(3.0, 4.0),
+            Vec2::new(6.5, 2.0),
+            Vec2::new(5.0, 7.5),
+        ]
+    }
+
+    #[test]
+    fn incremental_insertion_matches_batch() {
+        let points = points();
+        let batch = Delaunay2D::triangulate(points.clone());
+
+        let mut incremental = Delaunay2D::triangulate(points[..4].to_vec());
+        for point in &points[4..] {
+            incremental.insert_point(*point);
+        }
+
+        assert_eq!(incremental.vertices(), batch.vertices());
+        assert_eq!(sorted_edges(&incremental), sorted_edges(&batch));
+        // 7 points with 4 of them on the hull
+        assert_eq!(sorted_edges(&batch).len(), 3 * 7 - 3 - 4);
+    }
+
+    #[test]
+    fn insertion_outside_of_bounds_and_removal() {
+        let points = points();
+        let mut incremental = Delaunay2D::triangulate(vec![points[4]]);
+        for point in points.iter().filter(|p| **p != points[4]) {
+            incremental.insert_point(*point);
+        }
+        let mut reordered = points.clone();
+        reordered.remove(4);
+        reordered.insert(0, points[4]);
+        assert_eq!(
+            sorted_edges(&incremental),
+            sorted_edges(&Delaunay2D::triangulate(reordered))
+        );
+
+        assert!(incremental.remove_point(points[4]));
+        assert!(!incremental.remove_point(Vec2::new(100.0, 100.0)));
+        let mut remaining = points.clone();
+        remaining.remove(4);
+        assert_eq!(
+            sorted_edges(&incremental),
+            sorted_edges(&Delaunay2D::triangulate(remaining))
+        );
+    }
+
+    #[test]
+    fn removal_matches_batch_without_the_point() {
+        let points = points();
+        // only the inner points, the edges near the hull depend on the super triangle
+        for (i, point) in points.iter().enumerate().skip(4) {
+            let mut delaunay = Delaunay2D::triangulate(points.clone());
+            let super_triangle = delaunay.super_triangle;
+            assert!(delaunay.remove_point(*point));
+
+            let mut remaining = points.clone();
+            remaining.remove(i);
+            assert_eq!(delaunay.vertices(), remaining.as_slice());
+            assert_eq!(
+                sorted_edges(&delaunay),
+                sorted_edges(&Delaunay2D::triangulate(remaining)),
+                "removing {point}"
+            );
+            // the hole is filled locally instead of rebuilding with a new super triangle
+            assert_eq!(delaunay.super_triangle, super_triangle);
+        }
+    }
+}
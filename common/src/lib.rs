@@ -5,10 +5,13 @@ pub mod delaunay2d;
 pub mod math;
 pub mod prim;
 
+/// Describes a 2D-ray segment going from `start` to `end`.
 #[derive(Debug, Default, Clone, Copy)]
 pub struct Ray2D {
     pub start: Vec2,
     pub end: Vec2,
+    /// Direction of the ray which is NOT normalized: `direction = end - start`.
+    /// Use [`Ray2D::direction_normalized`] for the unit vector.
     pub direction: Vec2,
 }
 
@@ -20,6 +23,28 @@ impl Ray2D {
             direction: end - position,
         }
     }
+
+    /// Creates a ray going from `start` in the `direction` (doesn't need to be normalized)
+    /// with the specified `length`.
+    pub fn from_direction(start: Vec2, direction: Vec2, length: f32) -> Self {
+        Self::new(start, start + direction.normalize_or_zero() * length)
+    }
+
+    /// Gets the length of the ray.
+    pub fn length(&self) -> f32 {
+        self.direction.length()
+    }
+
+    /// Gets the normalized direction of the ray or `Vec2::ZERO` if the ray has zero length.
+    pub fn direction_normalized(&self) -> Vec2 {
+        self.direction.normalize_or_zero()
+    }
+
+    /// Gets the point on the ray where `t` is a fraction of the ray's length:
+    /// `point_at(0.0) == start`, `point_at(1.0) == end`.
+    pub fn point_at(&self, t: f32) -> Vec2 {
+        self.start + self.direction * t
+    }
 }
 
 /// Describes a 2D-rectangle with {x,y} being the top-left corner of the rectangle.
@@ -86,6 +111,33 @@ impl FRect {
         Vec2::new(self.x + self.width / 2.0, self.y + self.height / 2.0)
     }
 
+    /// Returns corners of the rectangle starting from the top-left one, going clockwise:
+    /// top-left, top-right, bottom-right, bottom-left.
+    pub fn corners(&self) -> [Vec2; 4] {
+        [
+            Vec2::new(self.left(), self.top()),
+            Vec2::new(self.right(), self.top()),
+            Vec2::new(self.right(), self.bottom()),
+            Vec2::new(self.left(), self.bottom()),
+        ]
+    }
+
+    /// Returns corners of the rectangle rotated around its center by `angle` (in radians).
+    /// Corners are in the same order as in [`FRect::corners`].
+    pub fn rotated(&self, angle: f32) -> [Vec2; 4] {
+        let center = self.center();
+        let rotation = Vec2::from_angle(angle);
+
+        self.corners()
+            .map(|corner| center + rotation.rotate(corner - center))
+    }
+
+    /// Returns corners of the rectangle as a polygon.
+    /// Vertices are in the same order as in [`FRect::corners`].
+    pub fn to_polygon(&self) -> Vec<Vec2> {
+        self.corners().to_vec()
+    }
+
     pub fn contains(&self, point: Vec2) -> bool {
         self.x <= point.x
             && point.x < (self.x + self.width)
@@ -93,6 +145,23 @@ impl FRect {
             && point.y < (self.y + self.height)
     }
 
+    /// Checks whether `other` lies fully inside this rectangle. Touching edges count as inside,
+    /// so a rectangle always contains itself.
+    pub fn contains_rect(&self, other: &FRect) -> bool {
+        self.left() <= other.left()
+            && other.right() <= self.right()
+            && self.top() <= other.top()
+            && other.bottom() <= self.bottom()
+    }
+
+    /// Gets the point inside the rectangle (or on its border) closest to `point`.
+    pub fn clamp_point(&self, point: Vec2) -> Vec2 {
+        Vec2::new(
+            point.x.clamp(self.left(), self.right()),
+            point.y.clamp(self.top(), self.bottom()),
+        )
+    }
+
     pub fn inflate(&mut self, horizontal: f32, vertical: f32) {
         self.x -= horizontal;
         self.y -= vertical;
@@ -107,6 +176,27 @@ impl FRect {
             && self.top() < other.bottom()
     }
 
+    /// Returns the overlapping region of two rectangles or `None` if they don't intersect.
+    /// Rectangles which only touch each other's edges don't intersect.
+    pub fn intersection(&self, other: &FRect) -> Option<FRect> {
+        if !self.intersects(*other) {
+            return None;
+        }
+
+        Some(FRect::from_min_max(
+            self.left().max(other.left()),
+            self.top().max(other.top()),
+            self.right().min(other.right()),
+            self.bottom().min(other.bottom()),
+        ))
+    }
+
+    /// Returns the area of the overlapping region of two rectangles.
+    pub fn overlap_area(&self, other: &FRect) -> f32 {
+        self.intersection(other)
+            .map_or(0.0, |rect| rect.width * rect.height)
+    }
+
     pub fn union(&self, other: &FRect) -> FRect {
         let x = self.x.min(other.x);
         let y = self.y.min(other.y);
@@ -119,119 +209,287 @@ impl FRect {
         )
     }
 
+    /// Gets the rectangle covering this one both at its current position and moved by `motion`,
+    /// i.e. the swept bounds of a moving rectangle. Used for the broadphase of moving colliders.
+    pub fn swept(&self, motion: Vec2) -> FRect {
+        let mut moved = *self;
+        moved.x += motion.x;
+        moved.y += motion.y;
+
+        self.union(&moved)
+    }
+
     pub fn union_vec2(&self, vec: &Vec2) -> FRect {
         let rect = FRect::new(vec.x, vec.y, 0.0, 0.0);
         self.union(&rect)
     }
 
+    /// Gets the closest point to the origin on the rectangle's border, assuming the origin is
+    /// inside the rectangle. The result is always axis-aligned: the x-penetration (distance to
+    /// the closest vertical edge) is compared to the y-penetration (distance to the closest
+    /// horizontal edge) and the smaller one is picked. Used as the minimum translation vector
+    /// of a Minkowski difference, so boxes are pushed straight out of each other.
+    /// On a tie the x axis is preferred.
     pub fn closest_point_to_origin(&self) -> Vec2 {
         let max = self.max();
-        let mut min_dist = self.x.abs();
-        let mut bounds_point = Vec2::new(self.x, 0.0);
-
-        if max.x.abs() < min_dist {
-            min_dist = max.x.abs();
-            bounds_point.x = max.x;
-            bounds_point.y = 0.0;
-        }
-
-        if max.y.abs() < min_dist {
-            min_dist = max.y.abs();
-            bounds_point.x = 0.0;
-            bounds_point.y = max.y;
-        }
 
-        if self.y.abs() < min_dist {
-            bounds_point.x = 0.0;
-            bounds_point.y = self.y;
+        let x = if max.x.abs() < self.x.abs() {
+            max.x
+        } else {
+            self.x
+        };
+        let y = if max.y.abs() < self.y.abs() {
+            max.y
+        } else {
+            self.y
+        };
+
+        if y.abs() < x.abs() {
+            Vec2::new(0.0, y)
+        } else {
+            Vec2::new(x, 0.0)
         }
-
-        bounds_point
     }
 
     /// Returns (Closest, EdgeNormal)
     pub fn closest_point_on_border(&self, point: Vec2) -> (Vec2, Vec2) {
+        let [top_left, _, bottom_right, _] = self.corners();
         let mut edge_normal = Vec2::ZERO;
 
-        let mut res = Vec2::new(0.0, 0.0);
-        res.x = point.x.clamp(self.left(), self.right());
-        res.y = point.y.clamp(self.top(), self.bottom());
+        let mut res = point.clamp(top_left, bottom_right);
 
         if self.contains(res) {
-            let dl = res.x - self.left();
-            let dr = self.right() - res.x;
-            let dt = res.y - self.top();
-            let db = self.bottom() - res.y;
+            let to_top_left = res - top_left;
+            let to_bottom_right = bottom_right - res;
 
-            let min = dl.min(dr.min(dt.min(db)));
+            let min = to_top_left.min_element().min(to_bottom_right.min_element());
 
-            if min == dt {
-                res.y = self.top();
+            if min == to_top_left.y {
+                res.y = top_left.y;
                 edge_normal.y = -1.0;
-            } else if min == db {
-                res.y = self.bottom();
+            } else if min == to_bottom_right.y {
+                res.y = bottom_right.y;
                 edge_normal.y = 1.0;
-            } else if min == dl {
-                res.x = self.left();
+            } else if min == to_top_left.x {
+                res.x = top_left.x;
                 edge_normal.x = -1.0;
             } else {
-                res.x = self.right();
+                res.x = bottom_right.x;
                 edge_normal.x = 1.0;
             }
 
             return (res, edge_normal);
         }
 
-        if res.x == self.left() {
+        if res.x == top_left.x {
             edge_normal.x = -1.0;
         }
-        if res.x == self.right() {
+        if res.x == bottom_right.x {
             edge_normal.x = 1.0;
         }
-        if res.y == self.top() {
+        if res.y == top_left.y {
             edge_normal.y = -1.0;
         }
-        if res.y == self.bottom() {
+        if res.y == bottom_right.y {
             edge_normal.y = 1.0;
         }
 
         (res, edge_normal)
     }
 
+    /// Checks if the ray intersects the rectangle. Returns the fraction `t` of the ray's length
+    /// where the ray enters the rectangle, so the entry point is `ray.point_at(t)`.
+    /// The fraction is `0.0` if the ray starts inside the rectangle and can be greater than `1.0`
+    /// if the rectangle lies beyond the ray's end.
     pub fn ray_intersects(&self, ray: &Ray2D) -> Option<f32> {
-        let mut distance = 0.0;
-        let mut max = f32::MAX;
-
-        let mut check_axis =
-            |dir_axis: f32, start_axis: f32, self_axis: f32, self_size: f32| -> bool {
-                if dir_axis.abs() < 1e-06 {
-                    if start_axis < self_axis || start_axis > self_axis + self_size {
-                        return false;
-                    }
-                } else {
-                    let inv_x = 1.0 / dir_axis;
-                    let mut left = (self_axis - start_axis) * inv_x;
-                    let mut right = (self_axis + self_size - start_axis) * inv_x;
-                    if left > right {
-                        std::mem::swap(&mut left, &mut right);
-                    }
-
-                    distance = left.max(distance);
-                    max = right.min(max);
-                    if distance > max {
-                        return false;
-                    }
+        let direction = ray.direction_normalized();
+        let min = Vec2::new(self.x, self.y);
+        let max = self.max();
+
+        // Distances along the ray at which it enters and exits the rectangle
+        let mut enter = 0.0_f32;
+        let mut exit = f32::MAX;
+
+        for axis in 0..2 {
+            let start = ray.start[axis];
+            if direction[axis].abs() < 1e-06 {
+                if start < min[axis] || start > max[axis] {
+                    return None;
+                }
+            } else {
+                let inv_dir = 1.0 / direction[axis];
+                let mut near = (min[axis] - start) * inv_dir;
+                let mut far = (max[axis] - start) * inv_dir;
+                if near > far {
+                    std::mem::swap(&mut near, &mut far);
                 }
 
-                true
-            };
+                enter = enter.max(near);
+                exit = exit.min(far);
+                if enter > exit {
+                    return None;
+                }
+            }
+        }
 
-        if !check_axis(ray.direction.x, ray.start.x, self.x, self.width)
-            || !check_axis(ray.direction.y, ray.start.y, self.y, self.height)
-        {
-            return None;
+        let length = ray.length();
+        Some(if length > 0.0 { enter / length } else { 0.0 })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: &[Vec2], expected: &[Vec2]) {
+        assert_eq!(actual.len(), expected.len());
+        for (actual, expected) in actual.iter().zip(expected) {
+            assert!(
+                actual.abs_diff_eq(*expected, 1e-5),
+                "{actual} != {expected}"
+            );
         }
+    }
+
+    #[test]
+    fn corners_start_top_left_going_clockwise() {
+        let rect = FRect::new(1.0, 2.0, 4.0, 2.0);
+        let expected = [
+            Vec2::new(1.0, 2.0),
+            Vec2::new(5.0, 2.0),
+            Vec2::new(5.0, 4.0),
+            Vec2::new(1.0, 4.0),
+        ];
+        assert_eq!(rect.corners(), expected);
+        assert_eq!(rect.to_polygon(), expected.to_vec());
+    }
+
+    #[test]
+    fn rotated_by_quarter_turn() {
+        let rect = FRect::new(0.0, 0.0, 4.0, 2.0);
+        let expected = [
+            Vec2::new(3.0, -1.0),
+            Vec2::new(3.0, 3.0),
+            Vec2::new(1.0, 3.0),
+            Vec2::new(1.0, -1.0),
+        ];
+        assert_close(&rect.rotated(std::f32::consts::FRAC_PI_2), &expected);
+        assert_close(&rect.rotated(0.0), &rect.corners());
+    }
+
+    #[test]
+    fn closest_point_on_border_inside_and_outside() {
+        let rect = FRect::new(0.0, 0.0, 10.0, 4.0);
+        // inside, closest to the left edge
+        assert_eq!(
+            rect.closest_point_on_border(Vec2::new(1.0, 2.0)),
+            (Vec2::new(0.0, 2.0), Vec2::new(-1.0, 0.0))
+        );
+        // inside, closest to the bottom edge
+        assert_eq!(
+            rect.closest_point_on_border(Vec2::new(5.0, 3.5)),
+            (Vec2::new(5.0, 4.0), Vec2::new(0.0, 1.0))
+        );
+        // outside, past the top-right corner
+        assert_eq!(
+            rect.closest_point_on_border(Vec2::new(12.0, -3.0)),
+            (Vec2::new(10.0, 0.0), Vec2::new(1.0, -1.0))
+        );
+    }
+
+    #[test]
+    fn intersection_of_partially_overlapping_rects() {
+        let a = FRect::new(0.0, 0.0, 4.0, 4.0);
+        let b = FRect::new(2.0, 1.0, 4.0, 2.0);
+        assert_eq!(a.intersection(&b), Some(FRect::new(2.0, 1.0, 2.0, 2.0)));
+        assert_eq!(b.intersection(&a), a.intersection(&b));
+        assert_eq!(a.overlap_area(&b), 4.0);
+    }
+
+    #[test]
+    fn intersection_with_contained_rect_is_the_inner_rect() {
+        let outer = FRect::new(0.0, 0.0, 10.0, 10.0);
+        let inner = FRect::new(2.0, 3.0, 4.0, 5.0);
+        assert_eq!(outer.intersection(&inner), Some(inner));
+        assert_eq!(outer.overlap_area(&inner), 20.0);
+    }
+
+    #[test]
+    fn touching_and_disjoint_rects_dont_intersect() {
+        let a = FRect::new(0.0, 0.0, 4.0, 4.0);
+        let touching = FRect::new(4.0, 0.0, 4.0, 4.0);
+        let disjoint = FRect::new(10.0, 10.0, 1.0, 1.0);
+        assert_eq!(a.intersection(&touching), None);
+        assert_eq!(a.overlap_area(&touching), 0.0);
+        assert_eq!(a.intersection(&disjoint), None);
+        assert_eq!(a.overlap_area(&disjoint), 0.0);
+    }
+
+    #[test]
+    fn ray_point_at_ends() {
+        let ray = Ray2D::new(Vec2::new(1.0, 2.0), Vec2::new(4.0, 6.0));
+        assert_eq!(ray.point_at(0.0), ray.start);
+        assert_eq!(ray.point_at(1.0), ray.end);
+        assert_eq!(ray.point_at(0.5), Vec2::new(2.5, 4.0));
+        assert_eq!(ray.length(), 5.0);
+        assert_eq!(ray.direction_normalized(), Vec2::new(0.6, 0.8));
+    }
+
+    #[test]
+    fn ray_intersects_returns_fraction_of_ray() {
+        let rect = FRect::new(5.0, -1.0, 2.0, 2.0);
+        let ray = Ray2D::new(Vec2::ZERO, Vec2::new(10.0, 0.0));
+        let fraction = rect.ray_intersects(&ray).unwrap();
+        assert_eq!(ray.point_at(fraction), Vec2::new(5.0, 0.0));
+
+        let diagonal = Ray2D::from_direction(Vec2::new(0.0, 6.0), Vec2::new(1.0, -1.0), 10.0);
+        let fraction = rect.ray_intersects(&diagonal).unwrap();
+        assert!(diagonal
+            .point_at(fraction)
+            .abs_diff_eq(Vec2::new(5.0, 1.0), 1e-5));
+        let short = Ray2D::from_direction(Vec2::ZERO, Vec2::X, 2.0);
+        assert_eq!(rect.ray_intersects(&short), Some(2.5));
+        assert_eq!(rect.ray_intersects(&Ray2D::new(Vec2::ZERO, Vec2::Y)), None);
+
+        let zero = Ray2D::new(Vec2::ONE, Vec2::ONE);
+        assert_eq!(zero.direction_normalized(), Vec2::ZERO);
+        assert_eq!(rect.ray_intersects(&zero), None);
+        let inside = Ray2D::new(Vec2::new(6.0, 0.0), Vec2::new(6.0, 0.0));
+        assert_eq!(rect.ray_intersects(&inside), Some(0.0));
+    }
+
+    #[test]
+    fn swept_with_positive_motion() {
+        let rect = FRect::new(0.0, 0.0, 4.0, 2.0);
+        assert_eq!(
+            rect.swept(Vec2::new(10.0, 3.0)),
+            FRect::new(0.0, 0.0, 14.0, 5.0)
+        );
+        assert_eq!(rect.swept(Vec2::ZERO), rect);
+    }
+
+    #[test]
+    fn swept_with_negative_motion() {
+        let rect = FRect::new(0.0, 0.0, 4.0, 2.0);
+        assert_eq!(
+            rect.swept(Vec2::new(-10.0, -3.0)),
+            FRect::new(-10.0, -3.0, 14.0, 5.0)
+        );
+        assert_eq!(
+            rect.swept(Vec2::new(-10.0, 3.0)),
+            FRect::new(-10.0, 0.0, 14.0, 5.0)
+        );
+    }
+
+    #[test]
+    fn contains_rect() {
+        let outer = FRect::new(0.0, 0.0, 10.0, 10.0);
+        assert!(outer.contains_rect(&FRect::new(2.0, 3.0, 4.0, 4.0)));
+        assert!(outer.contains_rect(&outer));
 
-        Some(distance)
+        let overlapping = FRect::new(5.0, 5.0, 10.0, 2.0);
+        assert!(outer.intersects(overlapping));
+        assert!(!outer.contains_rect(&overlapping));
+        assert!(!overlapping.contains_rect(&outer));
     }
 }
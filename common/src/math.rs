@@ -45,12 +45,16 @@ pub fn almost_equal_f32(x: f32, y: f32) -> bool {
 }
 
 pub fn almost_equal_vec2(left: Vec2, right: Vec2) -> bool {
-    almost_equal_f32(left.x, left.y) && almost_equal_f32(right.x, right.y)
+    almost_equal_f32(left.x, right.x) && almost_equal_f32(left.y, right.y)
 }
 
 pub fn choose_random<T>(arr: &[T]) -> (&T, usize) {
-    let mut rand = thread_rng();
-    let index = rand.gen_range(0..arr.len());
+    choose_random_with(&mut thread_rng(), arr)
+}
+
+/// Same as `choose_random` but uses the provided random number generator.
+pub fn choose_random_with<'a, T, R: Rng + ?Sized>(rng: &mut R, arr: &'a [T]) -> (&'a T, usize) {
+    let index = rng.gen_range(0..arr.len());
     (&arr[index], index)
 }
 
@@ -0,0 +1,79 @@
+//! Layer-masked broadphase queries with `SpatialHash::new` vs `SpatialHash::with_layer_sharding`
+//! when most of the colliders are on a layer the queries ignore.
+//!
+//! Run with `cargo bench -p physics --bench layer_sharding`.
+
+use std::time::{Duration, Instant};
+
+use bevy::ecs::system::SystemState;
+use common::FRect;
+use physics::prelude::*;
+
+const COLLIDERS: usize = 10_000;
+const QUERIES: usize = 1000;
+const RUNS: u32 = 20;
+
+const WALL_LAYER: i32 = 0b01;
+const DECOR_LAYER: i32 = 0b10;
+
+fn physics_app(layer_sharding: bool) -> App {
+    let mut app = App::new();
+    app.init_resource::<Time>().add_plugins(PhysicsPlugin {
+        layer_sharding,
+        ..default()
+    });
+    app
+}
+
+/// Spawns a grid of colliders with every tenth of them on `WALL_LAYER`
+/// and the rest on the ignored `DECOR_LAYER`.
+fn spawn_colliders(app: &mut App) {
+    for i in 0..COLLIDERS {
+        let position = Vec3::new((i % 100) as f32 * 20.0, (i / 100) as f32 * 20.0, 0.0);
+        let mut collider = colliders::Collider::new(shapes::ShapeType::Circle { radius: 8.0 });
+        collider.physics_layer = if i % 10 == 0 { WALL_LAYER } else { DECOR_LAYER };
+
+        app.world_mut().spawn((
+            SpatialBundle::from_transform(Transform::from_translation(position)),
+            collider,
+        ));
+    }
+}
+
+/// Measures the average time of `QUERIES` broadphase queries masked by `WALL_LAYER`.
+fn bench(name: &str, layer_sharding: bool) {
+    let mut app = physics_app(layer_sharding);
+    spawn_colliders(&mut app);
+
+    let mut state =
+        SystemState::<(Res<spatial_hash::SpatialHash>, Query<&colliders::Collider>)>::new(
+            app.world_mut(),
+        );
+    let (hash, colliders) = state.get(app.world());
+    assert_eq!(hash.registered_count(), COLLIDERS);
+
+    let mut total = Duration::ZERO;
+    let mut found = 0;
+    for _ in 0..RUNS {
+        let start = Instant::now();
+        for i in 0..QUERIES {
+            let position = Vec2::new((i % 40) as f32 * 50.0, (i / 40) as f32 * 80.0);
+            let bounds = FRect::new(position.x, position.y, 100.0, 100.0);
+            found += hash
+                .aabb_broadphase(&colliders, bounds, None, Some(WALL_LAYER))
+                .len();
+        }
+        total += start.elapsed();
+    }
+
+    println!(
+        "{name:<12} {:>10.3?} per {QUERIES} queries ({} found)",
+        total / RUNS,
+        found / RUNS as usize
+    );
+}
+
+fn main() {
+    bench("single hash", false);
+    bench("sharded", true);
+}
@@ -0,0 +1,105 @@
+//! Gathering the separation neighbors of a crowd with `overlap_circle` per agent
+//! vs a single `SpatialHash::for_each_pair_within` pass.
+//!
+//! Run with `cargo bench -p physics --bench separation_pairs`.
+
+use std::time::{Duration, Instant};
+
+use bevy::{ecs::system::SystemState, utils::HashMap};
+use physics::prelude::*;
+
+const AGENTS: usize = 1000;
+const RADIUS: f32 = 32.0;
+const AGENT_RADIUS: f32 = 6.0;
+const RUNS: u32 = 20;
+
+/// Spawns the agents on a jittered grid, with 6 or so neighbors within `RADIUS` of each one.
+fn spawn_agents(app: &mut App) -> Vec<Entity> {
+    (0..AGENTS)
+        .map(|i| {
+            let jitter = Vec2::new((i * 7 % 11) as f32, (i * 13 % 17) as f32);
+            let position = Vec2::new((i % 40) as f32 * 24.0, (i / 40) as f32 * 24.0) + jitter;
+            app.world_mut()
+                .spawn((
+                    SpatialBundle::from_transform(Transform::from_xyz(position.x, position.y, 0.0)),
+                    colliders::Collider::new(shapes::ShapeType::Circle {
+                        radius: AGENT_RADIUS,
+                    }),
+                ))
+                .id()
+        })
+        .collect()
+}
+
+fn separation(a: Vec2, b: Vec2) -> Vec2 {
+    let offset = a - b;
+    offset.normalize_or_zero() / offset.length().max(1.0)
+}
+
+fn main() {
+    let mut app = App::new();
+    app.init_resource::<Time>()
+        .add_plugins(PhysicsPlugin::default());
+    let agents = spawn_agents(&mut app);
+
+    let mut state =
+        SystemState::<(Res<spatial_hash::SpatialHash>, Query<&colliders::Collider>)>::new(
+            app.world_mut(),
+        );
+    let (hash, colliders) = state.get(app.world());
+    assert_eq!(hash.registered_count(), AGENTS);
+    let center = |entity| colliders.get(entity).unwrap().absolute_position();
+
+    let mut total = Duration::ZERO;
+    let mut pairs = 0;
+    let mut neighbors = Vec::new();
+    for _ in 0..RUNS {
+        let start = Instant::now();
+        let mut steering: HashMap<Entity, Vec2> = HashMap::with_capacity(AGENTS);
+        for &agent in &agents {
+            neighbors.clear();
+            hash.overlap_circle(
+                &colliders,
+                center(agent),
+                RADIUS,
+                Some(agent),
+                Some(&mut neighbors),
+                None,
+            );
+            let force = neighbors
+                .iter()
+                .map(|&neighbor| separation(center(agent), center(neighbor)))
+                .sum();
+            steering.insert(agent, force);
+            pairs += neighbors.len();
+        }
+        total += start.elapsed();
+    }
+    println!(
+        "{:<16} {:>10.3?} per {AGENTS} agents ({} neighbors)",
+        "overlap_circle",
+        total / RUNS,
+        pairs / RUNS as usize
+    );
+
+    let mut total = Duration::ZERO;
+    let mut pairs = 0;
+    for _ in 0..RUNS {
+        let start = Instant::now();
+        let mut steering: HashMap<Entity, Vec2> = HashMap::with_capacity(AGENTS);
+        // `overlap_circle` checks the actual shapes, so the agents' radius is added
+        hash.for_each_pair_within(&colliders, RADIUS + AGENT_RADIUS, None, |a, b| {
+            let force = separation(center(a), center(b));
+            *steering.entry(a).or_default() += force;
+            *steering.entry(b).or_default() -= force;
+            pairs += 2;
+        });
+        total += start.elapsed();
+    }
+    println!(
+        "{:<16} {:>10.3?} per {AGENTS} agents ({} neighbors)",
+        "pairs_within",
+        total / RUNS,
+        pairs / RUNS as usize
+    );
+}
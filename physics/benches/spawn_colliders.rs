@@ -0,0 +1,65 @@
+//! Spawning an enemy wave of colliders one by one vs `SpawnCollidersExt::spawn_colliders`.
+//!
+//! Run with `cargo bench -p physics --bench spawn_colliders`.
+
+use std::time::{Duration, Instant};
+
+use bevy::ecs::world::CommandQueue;
+use physics::prelude::*;
+
+const COLLIDERS: usize = 1000;
+const RUNS: u32 = 50;
+
+fn physics_app() -> App {
+    let mut app = App::new();
+    app.init_resource::<Time>()
+        .add_plugins(PhysicsPlugin::default());
+    app
+}
+
+fn wave() -> Vec<(SpatialBundle, colliders::Collider)> {
+    (0..COLLIDERS)
+        .map(|i| {
+            let position = Vec3::new((i % 40) as f32 * 24.0, (i / 40) as f32 * 24.0, 0.0);
+            (
+                SpatialBundle::from_transform(Transform::from_translation(position)),
+                colliders::Collider::new(shapes::ShapeType::Circle { radius: 8.0 }),
+            )
+        })
+        .collect()
+}
+
+/// Measures the average time of `spawn` applying its commands to a fresh app.
+fn bench(name: &str, spawn: impl Fn(&mut Commands, Vec<(SpatialBundle, colliders::Collider)>)) {
+    let mut total = Duration::ZERO;
+    for _ in 0..RUNS {
+        let mut app = physics_app();
+        let bundles = wave();
+
+        let start = Instant::now();
+        let mut queue = CommandQueue::default();
+        let mut commands = Commands::new(&mut queue, app.world());
+        spawn(&mut commands, bundles);
+        queue.apply(app.world_mut());
+        total += start.elapsed();
+
+        let hash = app.world().resource::<spatial_hash::SpatialHash>();
+        assert_eq!(hash.registered_count(), COLLIDERS);
+    }
+
+    println!(
+        "{name:<12} {:>10.3?} per {COLLIDERS} colliders",
+        total / RUNS
+    );
+}
+
+fn main() {
+    bench("one-by-one", |commands, bundles| {
+        for bundle in bundles {
+            commands.spawn(bundle);
+        }
+    });
+    bench("batched", |commands, bundles| {
+        commands.spawn_colliders(bundles);
+    });
+}
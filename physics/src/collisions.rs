@@ -1,12 +1,19 @@
 use bevy::prelude::*;
 use colliders::Collider;
+use common::math;
+use smallvec::SmallVec;
 
 pub mod colliders;
+pub mod raycast_debug;
 pub mod shapes;
 pub mod spatial_hash;
 pub mod tests;
 
 /// Represents a collision result of a `Collider` with another `Collider`.
+///
+/// The shape tests work on `Collider`s directly, so the result only borrows the other collider.
+/// The `Entity` of the other collider is known by the caller that fetched it from the `SpatialHash`
+/// and is reported by `CollideEvent` and `PreResolveCollision`.
 #[derive(Debug, Default, Clone, Copy)]
 pub struct CollisionResultRef<'a> {
     /// Another `Collider` the main one collided with.
@@ -18,6 +25,19 @@ pub struct CollisionResultRef<'a> {
     pub point: Vec2,
 }
 
+impl<'a> std::fmt::Display for CollisionResultRef<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "collision at {} (normal: {}, min translation: {}, depth: {:.3})",
+            self.point,
+            self.normal,
+            self.min_translation,
+            self.min_translation.length()
+        )
+    }
+}
+
 impl<'a> CollisionResultRef<'a> {
     pub fn invert(&mut self) {
         self.normal.x = -self.normal.x;
@@ -28,14 +48,105 @@ impl<'a> CollisionResultRef<'a> {
     }
 }
 
-#[derive(Debug, Default, Clone, Copy)]
+/// Represents a hit of a ray with a `Collider`.
+///
+/// Colliders are identified by their `Entity` in all the query results, there are no separate
+/// collider ids. Use `Query<&Collider>::get(entity)` to get the hit `Collider` itself.
+#[derive(Debug, Default, Clone, Copy, Reflect)]
 pub struct RaycastHit {
+    /// The `Entity` of the hit collider.
     pub collider: Option<Entity>,
     pub fraction: f32,
     pub distance: f32,
     pub point: Vec2,
     pub normal: Vec2,
+    /// The center of the hit collider at the time of the hit.
     pub centroid: Vec2,
 }
 
+impl std::fmt::Display for RaycastHit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "hit {:?} at {} (fraction: {:.3}, distance: {:.3}, normal: {})",
+            self.collider, self.point, self.fraction, self.distance, self.normal
+        )
+    }
+}
+
 pub const ALL_LAYERS: i32 = -1;
+
+/// Filters the colliders returned by the `SpatialHash` queries (`*_filtered` methods).
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use physics::prelude::{colliders::Collider, spatial_hash::SpatialHash, QueryFilter};
+/// # fn shoot(spatial_hash: &SpatialHash, colliders: &Query<&Collider>, player: Entity) {
+/// # let (start, end) = (Vec2::ZERO, Vec2::X * 100.0);
+/// let filter = QueryFilter::new()
+///     .with_layer_mask(0b10)
+///     .exclude(player)
+///     .with_triggers(false);
+/// let hits = spatial_hash.linecast_filtered(colliders, start, end, &filter, usize::MAX);
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryFilter {
+    /// Bitmask of layers the colliders are required to be on. Defaults to `ALL_LAYERS`.
+    pub layer_mask: i32,
+    /// Entities which are never returned, e.g. the querying entity itself.
+    /// Usually there are only a few of them, so they're stored inline.
+    pub exclude: SmallVec<[Entity; 4]>,
+    /// Whether trigger colliders are returned. Defaults to `true`.
+    /// Triggers are the sensors of this crate, so there's no separate sensor filtering.
+    pub include_triggers: bool,
+}
+
+impl Default for QueryFilter {
+    fn default() -> Self {
+        Self {
+            layer_mask: ALL_LAYERS,
+            exclude: SmallVec::new(),
+            include_triggers: true,
+        }
+    }
+}
+
+impl QueryFilter {
+    /// Creates a filter accepting all the colliders.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_layer_mask(mut self, layer_mask: i32) -> Self {
+        self.layer_mask = layer_mask;
+        self
+    }
+
+    /// Adds the entity to the excluded ones.
+    pub fn exclude(mut self, entity: Entity) -> Self {
+        self.exclude.push(entity);
+        self
+    }
+
+    pub fn with_triggers(mut self, include_triggers: bool) -> Self {
+        self.include_triggers = include_triggers;
+        self
+    }
+
+    /// Creates a filter from the arguments of the non-filtered queries.
+    pub(crate) fn from_args(exclude: Option<Entity>, layer_mask: Option<i32>) -> Self {
+        Self {
+            layer_mask: layer_mask.unwrap_or(ALL_LAYERS),
+            exclude: exclude.into_iter().collect(),
+            include_triggers: true,
+        }
+    }
+
+    /// Checks whether the collider of the entity passes the filter.
+    pub fn accepts(&self, entity: Entity, collider: &Collider) -> bool {
+        (self.include_triggers || !collider.is_trigger)
+            && math::is_flag_set(self.layer_mask, collider.physics_layer)
+            && !self.exclude.contains(&entity)
+    }
+}
@@ -1,5 +1,5 @@
 use bevy::prelude::*;
-use common::FRect;
+use common::{math::is_flag_set, FRect};
 
 use super::{
     shapes::{Shape, ShapeType},
@@ -27,8 +27,133 @@ pub struct Collider {
     /// Bitmask of layers collider collides with. Ignores other layers for collision resolution,
     /// however still triggers `CollideEvent`. Defaults to `ALL_LAYERS`.
     pub collides_with_layers: i32,
+    /// Optional fine-grained filtering for the collision resolution. See `CollisionGroups`.
+    /// Defaults to `None`.
+    pub collision_groups: Option<CollisionGroups>,
+    /// Id of the body the collider is a part of. Colliders with the same `body_group`
+    /// (e.g. the body and the weapon of a single character spread over the child entities)
+    /// never collide with each other. Defaults to `None`, meaning the collider is a body
+    /// on its own.
+    pub body_group: Option<u32>,
 }
 
+/// Collision groups à la Rapier. A pair of colliders interacts if and only if
+/// `a.memberships & b.filter != 0 && b.memberships & a.filter != 0`.
+///
+/// Unlike `physics_layer` and `collides_with_layers`, which are only checked by the moving
+/// collider, groups are checked by both colliders of the pair, so either side can opt out
+/// of the interaction.
+///
+/// If either of the two colliders has groups set, groups are used to decide whether the
+/// collision gets resolved. A collider without groups falls back to
+/// `physics_layer` as `memberships` and `collides_with_layers` as `filter`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+pub struct CollisionGroups {
+    /// Bitmask of groups the collider is a member of.
+    pub memberships: u32,
+    /// Bitmask of groups the collider interacts with.
+    pub filter: u32,
+}
+
+impl Default for CollisionGroups {
+    fn default() -> Self {
+        Self {
+            memberships: 1 << 0,
+            filter: u32::MAX,
+        }
+    }
+}
+
+impl CollisionGroups {
+    pub fn new(memberships: u32, filter: u32) -> Self {
+        Self {
+            memberships,
+            filter,
+        }
+    }
+
+    /// Checks whether two groups interact with each other.
+    pub fn interacts_with(&self, other: &CollisionGroups) -> bool {
+        (self.memberships & other.filter) != 0 && (other.memberships & self.filter) != 0
+    }
+}
+
+macro_rules! collision_layers {
+    ($($field:ident = $bit:expr),* $(,)?) => {
+        /// A bitmask of physics layers represented as a set of named toggles, one per bit,
+        /// so the layers can be edited in the reflection-based inspectors
+        /// (e.g. `bevy-inspector-egui`) without typing bitmask integers.
+        /// `layer_N` is the bit `1 << N`. Converts from and into the `i32` bitmask
+        /// used by `physics_layer` and `collides_with_layers`.
+        ///
+        /// ```
+        /// # use physics::prelude::colliders::{Collider, CollisionLayers};
+        /// # let mut collider = Collider::default();
+        /// let layers = CollisionLayers::from(collider.collides_with_layers);
+        /// collider.collides_with_layers = CollisionLayers { layer_2: false, ..layers }.into();
+        /// ```
+        #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+        pub struct CollisionLayers {
+            $(pub $field: bool,)*
+        }
+
+        impl From<i32> for CollisionLayers {
+            fn from(mask: i32) -> Self {
+                Self {
+                    $($field: mask & (1 << $bit) != 0,)*
+                }
+            }
+        }
+
+        impl From<CollisionLayers> for i32 {
+            fn from(layers: CollisionLayers) -> Self {
+                let mut mask = 0;
+                $(
+                    if layers.$field {
+                        mask |= 1 << $bit;
+                    }
+                )*
+                mask
+            }
+        }
+    };
+}
+
+collision_layers!(
+    layer_0 = 0,
+    layer_1 = 1,
+    layer_2 = 2,
+    layer_3 = 3,
+    layer_4 = 4,
+    layer_5 = 5,
+    layer_6 = 6,
+    layer_7 = 7,
+    layer_8 = 8,
+    layer_9 = 9,
+    layer_10 = 10,
+    layer_11 = 11,
+    layer_12 = 12,
+    layer_13 = 13,
+    layer_14 = 14,
+    layer_15 = 15,
+    layer_16 = 16,
+    layer_17 = 17,
+    layer_18 = 18,
+    layer_19 = 19,
+    layer_20 = 20,
+    layer_21 = 21,
+    layer_22 = 22,
+    layer_23 = 23,
+    layer_24 = 24,
+    layer_25 = 25,
+    layer_26 = 26,
+    layer_27 = 27,
+    layer_28 = 28,
+    layer_29 = 29,
+    layer_30 = 30,
+    layer_31 = 31,
+);
+
 impl Default for Collider {
     fn default() -> Self {
         Self {
@@ -37,13 +162,15 @@ impl Default for Collider {
             local_offset: Vec2::ZERO,
             physics_layer: 1 << 0,
             collides_with_layers: ALL_LAYERS,
+            collision_groups: None,
+            body_group: None,
         }
     }
 }
 
 #[inline]
 fn calc_bounds(shape: &Shape, local_offset: Vec2) -> FRect {
-    match shape.shape_type {
+    match shape.scaled_type() {
         ShapeType::None => FRect::new(0.0, 0.0, 0.0, 0.0),
         ShapeType::Circle { radius } => FRect::new(
             shape.position.x + local_offset.x - radius,
@@ -62,16 +189,10 @@ fn calc_bounds(shape: &Shape, local_offset: Vec2) -> FRect {
 
 #[inline]
 fn get_center(shape: &Shape, local_offset: Vec2) -> Vec2 {
-    match shape.shape_type {
+    match shape.scaled_type() {
         ShapeType::None => shape.position,
-        ShapeType::Circle { radius } => Vec2::new(
-            shape.position.x + local_offset.x + radius,
-            shape.position.y + local_offset.y + radius,
-        ),
-        ShapeType::Box { width, height } => Vec2::new(
-            shape.position.x + local_offset.x + width / 2.0,
-            shape.position.y + local_offset.y + height / 2.0,
-        ),
+        // Both shapes are centered at their positions, see `calc_bounds`
+        ShapeType::Circle { .. } | ShapeType::Box { .. } => shape.position + local_offset,
     }
 }
 
@@ -87,12 +208,14 @@ impl Collider {
         Self { shape, ..default() }
     }
 
-    /// Gets current bounds rectangle.
+    /// Gets current bounds rectangle in world space, i.e. with both the entity's position
+    /// and `local_offset` applied.
     pub fn bounds(&self) -> FRect {
         self.shape.bounds
     }
 
-    /// Gets `Collider`'s center.
+    /// Gets `Collider`'s center in world space. Shapes are centered at their positions,
+    /// so it's the same as `absolute_position`.
     pub fn center(&self) -> Vec2 {
         self.shape.center
     }
@@ -107,48 +230,86 @@ impl Collider {
         self.shape.position + self.local_offset
     }
 
+    /// Gets `physics_layer` as a set of named layers.
+    pub fn physics_layers(&self) -> CollisionLayers {
+        self.physics_layer.into()
+    }
+
+    /// Gets `collides_with_layers` as a set of named layers.
+    pub fn collides_with_layer_set(&self) -> CollisionLayers {
+        self.collides_with_layers.into()
+    }
+
+    /// Gets `Collider`'s collision groups. If no groups are set, they're derived
+    /// from `physics_layer` and `collides_with_layers`.
+    pub fn effective_groups(&self) -> CollisionGroups {
+        self.collision_groups.unwrap_or(CollisionGroups {
+            memberships: self.physics_layer as u32,
+            filter: self.collides_with_layers as u32,
+        })
+    }
+
+    /// Checks whether a collision of this `Collider` with the `other` one should be resolved.
+    /// Uses `CollisionGroups` if either of the colliders has them set, otherwise checks
+    /// if `other`'s `physics_layer` is in this collider's `collides_with_layers`.
+    pub fn resolves_with(&self, other: &Collider) -> bool {
+        if self.collision_groups.is_none() && other.collision_groups.is_none() {
+            return is_flag_set(self.collides_with_layers, other.physics_layer);
+        }
+
+        self.effective_groups()
+            .interacts_with(&other.effective_groups())
+    }
+
+    /// Checks whether both colliders are parts of the same body, see `body_group`.
+    pub fn same_body(&self, other: &Collider) -> bool {
+        self.body_group.is_some() && self.body_group == other.body_group
+    }
+
     /// Checks if this shape overlaps any other `Collider`.
+    /// Ignores the `is_trigger` values of both colliders.
+    ///
+    /// All the shapes are tested at their world positions (`bounds()` for boxes and
+    /// `absolute_position()` for circles), so the result agrees with
+    /// [`Collider::overlap_motion`] with zero motion. Colliders which only touch don't overlap.
     pub fn overlaps(&self, other: &Collider) -> bool {
-        let position = self.position();
-        match self.shape.shape_type {
-            ShapeType::Circle { radius: r1 } => match other.shape.shape_type {
+        match self.shape.scaled_type() {
+            ShapeType::Circle { radius: r1 } => match other.shape.scaled_type() {
                 ShapeType::Circle { radius: r2 } => super::tests::circle_to_circle(
-                    position + self.local_offset,
+                    self.absolute_position(),
                     r1,
-                    other.position() + other.local_offset,
+                    other.absolute_position(),
                     r2,
                 ),
-                ShapeType::Box { width, height } => super::tests::rect_to_circle(
-                    other.position().x + other.local_offset.x,
-                    other.position().y + other.local_offset.y,
-                    width,
-                    height,
-                    self.position() + self.local_offset,
-                    r1,
-                ),
+                ShapeType::Box { .. } => {
+                    let bounds = other.bounds();
+                    super::tests::rect_to_circle(
+                        bounds.x,
+                        bounds.y,
+                        bounds.width,
+                        bounds.height,
+                        self.absolute_position(),
+                        r1,
+                    )
+                }
                 ShapeType::None => false,
             },
-            ShapeType::Box {
-                width: w1,
-                height: h1,
-            } => match other.shape.shape_type {
-                ShapeType::Circle { radius } => super::tests::rect_to_circle(
-                    position.x + self.local_offset.x,
-                    position.y + self.local_offset.y,
-                    w1,
-                    h1,
-                    other.position() + other.local_offset,
-                    radius,
-                ),
-                ShapeType::Box {
-                    width: w2,
-                    height: h2,
-                } => super::tests::rect_to_rect(
-                    position + self.local_offset,
-                    Vec2::new(w1, h1),
-                    other.position() + other.local_offset,
-                    Vec2::new(w2, h2),
-                ),
+            ShapeType::Box { .. } => match other.shape.scaled_type() {
+                ShapeType::Circle { radius } => {
+                    let bounds = self.bounds();
+                    super::tests::rect_to_circle(
+                        bounds.x,
+                        bounds.y,
+                        bounds.width,
+                        bounds.height,
+                        other.absolute_position(),
+                        radius,
+                    )
+                }
+                ShapeType::Box { .. } => {
+                    // Boxes which only touch don't collide in `box_to_box` either
+                    self.bounds().intersects(other.bounds())
+                }
                 ShapeType::None => false,
             },
             ShapeType::None => false,
@@ -157,56 +318,20 @@ impl Collider {
 
     /// Checks if this Collider collides with collider. If it does,
     /// true will be returned and result will be populated with collision data.
+    ///
+    /// Respects `is_trigger` the same way as [`Collider::collides_with_motion`].
     pub fn collides_with<'a>(&self, other: &'a Collider) -> Option<CollisionResultRef<'a>> {
-        if self.is_trigger || other.is_trigger {
-            return None;
-        }
-
-        let res = match self.shape.shape_type {
-            ShapeType::Circle { .. } => match other.shape.shape_type {
-                ShapeType::Circle { .. } => super::shapes::collisions::circle_to_circle(
-                    &self.shape,
-                    &other.shape,
-                    self.local_offset,
-                    other.local_offset,
-                ),
-                ShapeType::Box { .. } => super::shapes::collisions::circle_to_box(
-                    &self.shape,
-                    &other.shape,
-                    self.local_offset,
-                    other.local_offset,
-                ),
-                ShapeType::None => None,
-            },
-            ShapeType::Box { .. } => match other.shape.shape_type {
-                ShapeType::Circle { .. } => super::shapes::collisions::circle_to_box(
-                    &other.shape,
-                    &self.shape,
-                    other.local_offset,
-                    self.local_offset,
-                ),
-                ShapeType::Box { .. } => super::shapes::collisions::box_to_box(
-                    &self.shape,
-                    &other.shape,
-                    self.local_offset,
-                    other.local_offset,
-                ),
-                ShapeType::None => None,
-            },
-            ShapeType::None => None,
-        };
-
-        if let Some(mut res) = res {
-            res.collider = Some(other);
-            return Some(res);
-        }
-
-        None
+        self.collides_with_motion(other, Vec2::ZERO)
     }
 
     /// Checks if this Collider with motion applied (delta movement vector) collides
     /// with collider. If it does, true will be returned and result will be populated
     ///  with collision data.
+    ///
+    /// Respects `is_trigger`: returns `None` if this collider is a trigger since triggers
+    /// never collide with anything. If only `other` is a trigger, the collision is still reported
+    /// so the caller can invoke the trigger instead of resolving the collision.
+    /// Use [`Collider::overlap_motion`] to ignore the trigger status altogether.
     pub fn collides_with_motion<'a>(
         &self,
         other: &'a Collider,
@@ -216,6 +341,30 @@ impl Collider {
             return None;
         }
 
+        self.overlap_motion(other, motion)
+    }
+
+    /// Same as [`Collider::collides_with_motion`] but for the case when `other` moves as well
+    /// during the same tick. The check is done in the frame of `other` using the relative
+    /// motion `self_motion - other_motion`, so the fast colliders moving towards each other
+    /// don't get missed. The resulting translation is relative as well.
+    pub fn collides_with_relative_motion<'a>(
+        &self,
+        other: &'a Collider,
+        self_motion: Vec2,
+        other_motion: Vec2,
+    ) -> Option<CollisionResultRef<'a>> {
+        self.collides_with_motion(other, self_motion - other_motion)
+    }
+
+    /// Checks if this Collider with motion applied overlaps with collider, ignoring
+    /// the `is_trigger` values of both colliders. Useful for pure overlap queries,
+    /// e.g. detecting a moving trigger entering another trigger.
+    pub fn overlap_motion<'a>(
+        &self,
+        other: &'a Collider,
+        motion: Vec2,
+    ) -> Option<CollisionResultRef<'a>> {
         let res = match self.shape.shape_type {
             ShapeType::Circle { .. } => match other.shape.shape_type {
                 ShapeType::Circle { .. } => super::shapes::collisions::circle_to_circle(
@@ -258,6 +407,33 @@ impl Collider {
         None
     }
 
+    /// Same as [`Collider::overlap_motion`] but this collider is inflated by `margin`,
+    /// so the colliders which are apart by no more than `margin` are reported as well
+    /// (e.g. a body resting against a wall after being pushed out of it by the skin width).
+    pub fn overlap_motion_within<'a>(
+        &self,
+        other: &'a Collider,
+        motion: Vec2,
+        margin: f32,
+    ) -> Option<CollisionResultRef<'a>> {
+        // The margin is given in world units while the shape is scaled afterwards
+        let scale = self.shape.scale.abs().max(Vec2::splat(f32::EPSILON));
+        let inflated_type = match self.shape.shape_type {
+            ShapeType::Circle { radius } => ShapeType::Circle {
+                radius: radius + margin / scale.max_element(),
+            },
+            ShapeType::Box { width, height } => ShapeType::Box {
+                width: width + 2.0 * margin / scale.x,
+                height: height + 2.0 * margin / scale.y,
+            },
+            ShapeType::None => return None,
+        };
+
+        let mut inflated = self.clone();
+        inflated.set_shape(inflated_type);
+        inflated.overlap_motion(other, motion)
+    }
+
     /// Checks if `Collider` collides with a line from start and to end vectors.
     /// If it does, returns `Some(RaycastHit)`.
     pub fn collides_with_line(&self, start: Vec2, end: Vec2) -> Option<RaycastHit> {
@@ -265,22 +441,72 @@ impl Collider {
             ShapeType::Circle { .. } => {
                 super::shapes::collisions::line_to_circle(start, end, &self.shape)
             }
-            ShapeType::Box { .. } => todo!(),
+            ShapeType::Box { .. } => {
+                super::shapes::collisions::line_to_box(start, end, &self.shape)
+            }
             ShapeType::None => None,
         }
     }
 
+    /// Checks whether the center of this `Collider` crosses the `other` one while moving
+    /// by `motion`, i.e. tests the segment from the position before the movement to the one
+    /// after it. Unlike `overlap_motion`, which only tests the destination, catches the fast
+    /// colliders skipping thin colliders (e.g. tripwire triggers) within a single tick.
+    pub fn passes_through(&self, other: &Collider, motion: Vec2) -> bool {
+        if motion == Vec2::ZERO {
+            return false;
+        }
+
+        let start = self.absolute_position();
+        other.collides_with_line(start, start + motion).is_some()
+    }
+
     /// Checks whether `Collider` contains a point withit itself or not.
     pub fn contains_point(&self, point: Vec2) -> bool {
-        match self.shape.shape_type {
+        match self.shape.scaled_type() {
             ShapeType::Circle { radius } => {
-                (point - self.shape.position).length_squared() <= radius * radius
+                (point - self.absolute_position()).length_squared() <= radius * radius
             }
             ShapeType::Box { .. } => self.bounds().contains(point),
             ShapeType::None => false,
         }
     }
 
+    /// Gets the distance from the `point` to the closest point of the `Collider`'s shape.
+    /// Returns `0.0` if the point is inside the shape and `f32::MAX` for `ShapeType::None`.
+    pub fn distance_to_point(&self, point: Vec2) -> f32 {
+        match self.shape.scaled_type() {
+            ShapeType::Circle { radius } => {
+                (point.distance(self.absolute_position()) - radius).max(0.0)
+            }
+            ShapeType::Box { .. } => {
+                let bounds = self.bounds();
+                let closest = Vec2::new(
+                    point.x.clamp(bounds.left(), bounds.right()),
+                    point.y.clamp(bounds.top(), bounds.bottom()),
+                );
+                point.distance(closest)
+            }
+            ShapeType::None => f32::MAX,
+        }
+    }
+
+    /// Changes the shape of the `Collider` at runtime (e.g. a growing hitbox),
+    /// recalculating its bounds and center. The `SpatialHash` is updated automatically
+    /// on the next `FixedUpdate`.
+    pub fn set_shape(&mut self, shape_type: ShapeType) {
+        self.shape.shape_type = shape_type;
+        self.set_position(self.shape.position);
+    }
+
+    /// Checks whether the cached bounds and center match the current shape, position and
+    /// `local_offset`. They go out of sync if `shape` or `local_offset` are written directly
+    /// instead of via `set_shape`, in which case the `SpatialHash` keeps the stale bounds
+    /// and the collisions silently stop working.
+    pub fn validate(&self) -> bool {
+        self.shape.bounds == self.calc_bounds() && self.shape.center == self.get_center()
+    }
+
     pub(crate) fn set_position(&mut self, position: Vec2) {
         self.shape.position = position;
 
@@ -290,8 +516,13 @@ impl Collider {
         self.shape.center = center;
     }
 
-    pub(crate) fn update_from_transform(&mut self, transform: &Transform) {
-        self.set_position(transform.translation.xy());
+    /// Updates position and scale of the `Collider` from the entity's world transform,
+    /// so colliders of child entities are placed at their actual world positions.
+    /// See [`Shape::scaled_type`] for the details on how the scale is applied.
+    pub(crate) fn update_from_transform(&mut self, transform: &GlobalTransform) {
+        let (scale, _, translation) = transform.to_scale_rotation_translation();
+        self.shape.scale = scale.xy();
+        self.set_position(translation.xy());
     }
 
     fn calc_bounds(&self) -> FRect {
@@ -302,3 +533,238 @@ impl Collider {
         get_center(&self.shape, self.local_offset)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_utils::*, CollideEvent};
+
+    #[test]
+    fn entity_scale_is_applied_to_collider() {
+        let mut app = physics_app();
+        let entity = app
+            .world_mut()
+            .spawn((
+                SpatialBundle::from_transform(Transform::from_scale(Vec3::new(2.0, 2.0, 1.0))),
+                circle(10.0),
+            ))
+            .id();
+
+        let collider = app.world().get::<Collider>(entity).unwrap();
+        assert_eq!(
+            collider.shape.scaled_type(),
+            ShapeType::Circle { radius: 20.0 }
+        );
+        assert_eq!(collider.bounds().width, 40.0);
+    }
+
+    #[test]
+    fn non_uniformly_scaled_circle_uses_largest_axis() {
+        let mut collider = circle(10.0);
+        collider.update_from_transform(&GlobalTransform::from_scale(Vec3::new(1.0, -3.0, 1.0)));
+        assert_eq!(
+            collider.shape.scaled_type(),
+            ShapeType::Circle { radius: 30.0 }
+        );
+    }
+
+    #[test]
+    fn collision_groups_interact_only_both_ways() {
+        let player = CollisionGroups::new(0b01, 0b10);
+        let enemy = CollisionGroups::new(0b10, 0b01);
+        let ghost = CollisionGroups::new(0b10, 0b00);
+        assert!(player.interacts_with(&enemy));
+        assert!(enemy.interacts_with(&player));
+        assert!(!player.interacts_with(&ghost));
+        assert!(!ghost.interacts_with(&player));
+    }
+
+    #[test]
+    fn resolves_with_uses_groups_of_either_collider() {
+        let mut first = circle(8.0);
+        let mut second = circle(8.0);
+        first.collides_with_layers = 0;
+        second.collides_with_layers = 0;
+        assert!(!first.resolves_with(&second));
+
+        first.collision_groups = Some(CollisionGroups::new(1, 1));
+        second.physics_layer = 1;
+        second.collides_with_layers = 1;
+        assert!(first.resolves_with(&second));
+        assert!(second.resolves_with(&first));
+
+        first.collision_groups = Some(CollisionGroups::new(2, 1));
+        assert!(!first.resolves_with(&second));
+        assert!(!second.resolves_with(&first));
+    }
+
+    #[test]
+    fn shrunk_collider_leaves_cells() {
+        // lies within the cell (1, -1) only
+        let corner_cell = FRect::new(50.0, -30.0, 5.0, 5.0);
+        let mut app = physics_app();
+        let entity = spawn_collider(&mut app, circle(30.0), Vec2::new(40.0, 20.0));
+        with_hash(&mut app, |hash, _| {
+            assert_eq!(hash.cell_count(), 6);
+            assert!(hash.get_nearby_bounds(corner_cell).contains(&entity));
+        });
+
+        app.world_mut()
+            .get_mut::<Collider>(entity)
+            .unwrap()
+            .set_shape(ShapeType::Circle { radius: 5.0 });
+        tick(&mut app);
+
+        with_hash(&mut app, |hash, _| {
+            assert_eq!(hash.registered_bounds(entity).unwrap().width, 10.0);
+            assert_eq!(hash.cell_count(), 2);
+            assert!(hash.get_nearby_bounds(corner_cell).is_empty());
+        });
+    }
+
+    #[test]
+    fn circle_center_is_its_position() {
+        let mut collider = circle(10.0);
+        assert_eq!(collider.center(), Vec2::ZERO);
+        assert_eq!(collider.bounds(), FRect::new(-10.0, -10.0, 20.0, 20.0));
+
+        collider.local_offset = Vec2::new(5.0, 0.0);
+        collider.set_position(Vec2::new(20.0, 30.0));
+        assert_eq!(collider.center(), Vec2::new(25.0, 30.0));
+        assert_eq!(collider.center(), collider.absolute_position());
+        assert_eq!(collider.bounds(), FRect::new(15.0, 20.0, 20.0, 20.0));
+    }
+
+    #[test]
+    fn overlaps_agrees_with_collides_with() {
+        let shapes = [circle(8.0), circle(3.0), rect(16.0, 16.0), rect(30.0, 6.0)];
+
+        for first in &shapes {
+            for second in &shapes {
+                for offset in [Vec2::ZERO, Vec2::new(0.0, 4.0), Vec2::new(-2.5, 1.5)] {
+                    // stepping by a fraction, so the shapes never exactly touch
+                    for step in 0..30 {
+                        let mut a = first.clone();
+                        a.local_offset = offset;
+                        a.set_position(Vec2::new(10.0, -5.0));
+                        let mut b = second.clone();
+                        b.set_position(Vec2::new(10.0, -5.0) + Vec2::new(1.37, 0.91) * step as f32);
+
+                        assert_eq!(
+                            a.overlaps(&b),
+                            a.collides_with(&b).is_some(),
+                            "{:?} at {} vs {:?} at {}",
+                            a.shape.shape_type,
+                            a.absolute_position(),
+                            b.shape.shape_type,
+                            b.absolute_position(),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn named_layers_round_trip_to_bitmask() {
+        let layers = CollisionLayers {
+            layer_0: true,
+            layer_3: true,
+            layer_31: true,
+            ..default()
+        };
+        let mask: i32 = layers.into();
+        assert_eq!(mask, 0b1001 | i32::MIN);
+        assert_eq!(CollisionLayers::from(mask), layers);
+
+        let all = CollisionLayers::from(ALL_LAYERS);
+        assert!(all.layer_0 && all.layer_15 && all.layer_31);
+        assert_eq!(i32::from(all), ALL_LAYERS);
+        assert_eq!(i32::from(CollisionLayers::default()), 0);
+
+        let collider = Collider {
+            physics_layer: 0b100,
+            ..default()
+        };
+        assert!(collider.physics_layers().layer_2);
+        assert_eq!(i32::from(collider.physics_layers()), 0b100);
+        assert_eq!(i32::from(collider.collides_with_layer_set()), ALL_LAYERS);
+
+        // the raw bitmasks stay reflected, so the inspectors can still edit them
+        let reflected: &dyn bevy::reflect::Struct = &collider;
+        let physics_layer = reflected.field("physics_layer").unwrap();
+        assert_eq!(physics_layer.downcast_ref::<i32>(), Some(&0b100));
+        assert!(reflected.field("collides_with_layers").is_some());
+    }
+
+    #[test]
+    fn raw_field_mutation_is_detected() {
+        let mut collider = circle(8.0);
+        collider.set_position(Vec2::new(10.0, 10.0));
+        assert!(collider.validate());
+
+        collider.local_offset = Vec2::new(0.0, -16.0);
+        assert!(!collider.validate());
+        collider.set_position(collider.shape.position);
+        assert!(collider.validate());
+
+        collider.shape.shape_type = ShapeType::Circle { radius: 20.0 };
+        assert!(!collider.validate());
+        collider.set_shape(ShapeType::Circle { radius: 20.0 });
+        assert!(collider.validate());
+        assert_eq!(collider.bounds().width, 40.0);
+    }
+
+    #[test]
+    fn collision_groups_let_mover_pass_through() {
+        let mut app = physics_app();
+        let wall = spawn_wall(&mut app, Vec2::new(20.0, 0.0), Vec2::new(16.0, 64.0));
+        let mover = spawn_collider(&mut app, circle(8.0), Vec2::ZERO);
+
+        move_by(&mut app, mover, Vec2::new(10.0, 0.0));
+        tick(&mut app);
+        let stopped = position(&app, mover).x;
+        assert!(stopped > 3.9 && stopped <= 4.0);
+
+        let mut collider = app.world_mut().get_mut::<Collider>(wall).unwrap();
+        collider.collision_groups = Some(CollisionGroups::new(0b01, 0b01));
+        let mut collider = app.world_mut().get_mut::<Collider>(mover).unwrap();
+        collider.collision_groups = Some(CollisionGroups::new(0b10, u32::MAX));
+
+        move_by(&mut app, mover, Vec2::new(10.0, 0.0));
+        tick(&mut app);
+        assert_eq!(position(&app, mover).x, stopped + 10.0);
+    }
+
+    #[test]
+    fn same_body_group_doesnt_collide_with_itself() {
+        #[derive(Resource, Default)]
+        struct Collisions(Vec<(Entity, Entity)>);
+
+        let mut app = physics_app();
+        app.init_resource::<Collisions>();
+
+        let mut torso = circle(8.0);
+        torso.body_group = Some(7);
+        let mut arm = circle(8.0);
+        arm.body_group = Some(7);
+        let torso = spawn_collider(&mut app, torso, Vec2::ZERO);
+        let arm = spawn_collider(&mut app, arm, Vec2::new(12.0, 0.0));
+        app.world_mut().entity_mut(torso).observe(
+            |trigger: Trigger<CollideEvent>, mut collisions: ResMut<Collisions>| {
+                let event = trigger.event();
+                collisions.0.push((event.entity_main, event.collided_with));
+            },
+        );
+
+        move_by(&mut app, torso, Vec2::new(5.0, 0.0));
+        tick(&mut app);
+        assert_eq!(position(&app, torso), Vec2::new(5.0, 0.0));
+        assert!(app.world().resource::<Collisions>().0.is_empty());
+
+        app.world_mut().get_mut::<Collider>(arm).unwrap().body_group = Some(8);
+        move_by(&mut app, torso, Vec2::new(-1.0, 0.0));
+        tick(&mut app);
+        assert_eq!(app.world().resource::<Collisions>().0, vec![(torso, arm)]);
+    }
+}
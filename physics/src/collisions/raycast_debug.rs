@@ -0,0 +1,181 @@
+use std::collections::VecDeque;
+
+use bevy::{
+    color::palettes::css::{LIME, ORANGE, YELLOW},
+    prelude::*,
+};
+
+use super::{spatial_hash::SpatialHash, RaycastHit};
+
+/// A single recorded raycast.
+#[derive(Debug, Clone, Copy)]
+pub struct DebugRay {
+    pub start: Vec2,
+    pub end: Vec2,
+    /// The closest hit of the ray, if any.
+    pub hit: Option<RaycastHit>,
+}
+
+/// Stores the last `capacity` raycasts so they can be drawn with `draw_raycasts`.
+/// Not inserted by `PhysicsPlugin`, the debug tooling should insert it.
+///
+/// While `enabled`, every raycast of the `SpatialHash` (linecasts, `query_ray_first` and
+/// the behaviors built on them) is recorded automatically at the end of the frame.
+/// Custom rays can be recorded with `record`, which does nothing while `enabled` is `false`.
+#[derive(Resource, Debug, Clone)]
+pub struct RaycastDebugBuffer {
+    /// Toggles both the recording and the drawing.
+    pub enabled: bool,
+    capacity: usize,
+    rays: VecDeque<DebugRay>,
+}
+
+impl Default for RaycastDebugBuffer {
+    fn default() -> Self {
+        Self::new(64)
+    }
+}
+
+impl RaycastDebugBuffer {
+    /// Creates a disabled buffer keeping at most `capacity` last rays.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            enabled: false,
+            capacity,
+            rays: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// Records a ray from `start` to `end` with the hits returned by the query.
+    /// Only the closest hit is stored. The oldest ray is dropped if the buffer is full.
+    pub fn record(&mut self, start: Vec2, end: Vec2, hits: &[RaycastHit]) {
+        if !self.enabled || self.capacity == 0 {
+            return;
+        }
+
+        if self.rays.len() >= self.capacity {
+            self.rays.pop_front();
+        }
+
+        let hit = hits
+            .iter()
+            .min_by(|a, b| a.fraction.total_cmp(&b.fraction))
+            .copied();
+
+        self.rays.push_back(DebugRay { start, end, hit });
+    }
+
+    /// Iterates the recorded rays from the oldest to the newest.
+    pub fn iter(&self) -> impl Iterator<Item = &DebugRay> {
+        self.rays.iter()
+    }
+
+    pub fn clear(&mut self) {
+        self.rays.clear();
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+}
+
+/// Moves the rays recorded by the `SpatialHash` into the `RaycastDebugBuffer` and
+/// toggles the recording of the hash along with the buffer.
+/// Runs only while the buffer exists, see `stop_ray_recording`.
+pub(crate) fn collect_raycasts(
+    spatial_hash: Res<SpatialHash>,
+    mut buffer: ResMut<RaycastDebugBuffer>,
+) {
+    if spatial_hash.is_recording_rays() != buffer.enabled {
+        spatial_hash.set_ray_recording(buffer.enabled);
+    }
+
+    if !buffer.enabled {
+        return;
+    }
+    for ray in spatial_hash.take_recorded_rays() {
+        buffer.record(ray.start, ray.end, ray.hit.as_slice());
+    }
+}
+
+/// Stops the recording of the `SpatialHash` once the `RaycastDebugBuffer` is removed.
+pub(crate) fn stop_ray_recording(spatial_hash: Res<SpatialHash>) {
+    spatial_hash.set_ray_recording(false);
+}
+
+/// Draws the rays stored in `RaycastDebugBuffer`: the ray itself, the hit point and
+/// the surface normal at the hit point. Does nothing if the buffer is missing or disabled.
+pub fn draw_raycasts(buffer: Option<Res<RaycastDebugBuffer>>, mut gizmos: Gizmos) {
+    let Some(buffer) = buffer else {
+        return;
+    };
+
+    if !buffer.enabled {
+        return;
+    }
+
+    for ray in buffer.iter() {
+        match ray.hit {
+            Some(hit) => {
+                gizmos.line_2d(ray.start, hit.point, YELLOW);
+                gizmos.line_2d(hit.point, ray.end, ORANGE.with_alpha(0.3));
+                gizmos.circle_2d(hit.point, 2.0, ORANGE);
+                gizmos.arrow_2d(hit.point, hit.point + hit.normal * 12.0, LIME);
+            }
+            None => {
+                gizmos.line_2d(ray.start, ray.end, YELLOW.with_alpha(0.5));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{collisions::ALL_LAYERS, test_utils::*};
+
+    #[test]
+    fn recording_follows_the_buffer() {
+        let mut app = physics_app();
+        spawn_wall(&mut app, Vec2::new(50.0, 0.0), Vec2::new(10.0, 40.0));
+        let linecast = |app: &mut App| {
+            with_hash(app, |hash, colliders| {
+                hash.linecast(
+                    colliders,
+                    Vec2::ZERO,
+                    Vec2::new(100.0, 0.0),
+                    None,
+                    ALL_LAYERS,
+                    true,
+                );
+            });
+        };
+        let is_recording = |app: &App| app.world().resource::<SpatialHash>().is_recording_rays();
+
+        // nothing is recorded without the buffer
+        app.world_mut().run_schedule(Last);
+        assert!(!is_recording(&app));
+
+        let mut buffer = RaycastDebugBuffer::new(4);
+        buffer.enabled = true;
+        app.insert_resource(buffer);
+        app.world_mut().run_schedule(Last);
+        assert!(is_recording(&app));
+        linecast(&mut app);
+        app.world_mut().run_schedule(Last);
+        let buffer = app.world().resource::<RaycastDebugBuffer>();
+        assert_eq!(buffer.iter().count(), 1);
+        assert!(buffer.iter().next().unwrap().hit.is_some());
+
+        app.world_mut().resource_mut::<RaycastDebugBuffer>().enabled = false;
+        app.world_mut().run_schedule(Last);
+        assert!(!is_recording(&app));
+
+        app.world_mut().resource_mut::<RaycastDebugBuffer>().enabled = true;
+        app.world_mut().run_schedule(Last);
+        assert!(is_recording(&app));
+        app.world_mut().remove_resource::<RaycastDebugBuffer>();
+        app.world_mut().run_schedule(Last);
+        assert!(!is_recording(&app));
+    }
+}
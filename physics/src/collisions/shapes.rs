@@ -11,14 +11,35 @@ pub enum ShapeType {
     Box { width: f32, height: f32 },
 }
 
+impl ShapeType {
+    /// Gets the geometric area of the shape. `None` has zero area.
+    pub fn area(&self) -> f32 {
+        match *self {
+            ShapeType::None => 0.0,
+            ShapeType::Circle { radius } => std::f32::consts::PI * radius * radius,
+            ShapeType::Box { width, height } => width * height,
+        }
+    }
+
+    /// Gets the geometric perimeter of the shape. `None` has zero perimeter.
+    pub fn perimeter(&self) -> f32 {
+        match *self {
+            ShapeType::None => 0.0,
+            ShapeType::Circle { radius } => std::f32::consts::TAU * radius,
+            ShapeType::Box { width, height } => 2.0 * (width + height),
+        }
+    }
+}
+
 /// Represents a collider shape with specified `ShapeType`.
-/// Internally stores position and center vectors as well as bounds rectangle.
+/// Internally stores position, scale and center vectors as well as bounds rectangle.
 /// Those fields are private and update internally.
 #[derive(Debug, Clone, Copy, PartialEq, Reflect)]
 pub struct Shape {
-    /// Shape Type.
+    /// Shape Type. Stores unscaled dimensions of the shape.
     pub shape_type: ShapeType,
     pub(crate) position: Vec2,
+    pub(crate) scale: Vec2,
     pub(crate) center: Vec2,
     pub(crate) bounds: FRect,
 }
@@ -28,6 +49,7 @@ impl Default for Shape {
         Self {
             shape_type: ShapeType::None,
             position: Vec2::ZERO,
+            scale: Vec2::ONE,
             center: Vec2::ZERO,
             bounds: FRect::new(0.0, 0.0, 0.0, 0.0),
         }
@@ -45,11 +67,30 @@ impl Shape {
     pub fn bounds(&self) -> FRect {
         self.bounds
     }
+
+    /// Gets the `ShapeType` with the scale of the entity's `Transform` applied.
+    /// Box's width and height are multiplied by the respective scale axes.
+    /// Circle's radius is multiplied by the largest scale axis, so a non-uniformly
+    /// scaled circle stays a circle enclosing the scaled sprite.
+    /// Negative scale (e.g. a flipped sprite) is treated as positive.
+    pub fn scaled_type(&self) -> ShapeType {
+        let scale = self.scale.abs();
+        match self.shape_type {
+            ShapeType::None => ShapeType::None,
+            ShapeType::Circle { radius } => ShapeType::Circle {
+                radius: radius * scale.max_element(),
+            },
+            ShapeType::Box { width, height } => ShapeType::Box {
+                width: width * scale.x,
+                height: height * scale.y,
+            },
+        }
+    }
 }
 
 pub mod collisions {
     use bevy::math::Vec2;
-    use common::FRect;
+    use common::{FRect, Ray2D};
 
     use crate::prelude::{CollisionResultRef, RaycastHit};
 
@@ -90,8 +131,8 @@ pub mod collisions {
         first_offset: Vec2,
         second_offset: Vec2,
     ) -> Option<CollisionResultRef<'a>> {
-        match first.shape_type {
-            ShapeType::Circle { radius: r1 } => match second.shape_type {
+        match first.scaled_type() {
+            ShapeType::Circle { radius: r1 } => match second.scaled_type() {
                 ShapeType::Circle { radius: r2 } => {
                     let mut res = CollisionResultRef::default();
 
@@ -103,7 +144,9 @@ pub mod collisions {
                     let collided = dist_sqr < sum_of_radii * sum_of_radii;
                     if collided {
                         let normal = first_pos - second_pos;
-                        res.normal = normal.normalize_or_zero();
+                        // Concentric circles have no direction to separate along,
+                        // so an arbitrary but deterministic axis is picked
+                        res.normal = normal.try_normalize().unwrap_or(Vec2::X);
                         let depth = sum_of_radii - dist_sqr.sqrt();
                         res.min_translation = -depth * res.normal;
                         res.point = second_pos + res.normal * r2;
@@ -129,15 +172,13 @@ pub mod collisions {
         circle_offset: Vec2,
         box_offset: Vec2,
     ) -> Option<CollisionResultRef<'a>> {
-        match circle.shape_type {
+        match circle.scaled_type() {
             ShapeType::Circle { radius } => {
                 let mut res = CollisionResultRef::default();
 
                 let circle_pos = circle.position + circle_offset;
 
-                let mut bx_bounds = bx.bounds;
-                bx_bounds.x += box_offset.x;
-                bx_bounds.y += box_offset.y;
+                let bx_bounds = offset_bounds(bx, box_offset);
 
                 let (closest_point, normal) = bx_bounds.closest_point_on_border(circle_pos);
                 res.normal = normal;
@@ -152,8 +193,12 @@ pub mod collisions {
 
                 let sqr_dist = closest_point.distance_squared(circle_pos);
                 if sqr_dist == 0.0 {
-                    res.min_translation = res.normal * radius;
-                } else if sqr_dist <= radius * radius {
+                    // The center lies exactly on the right or bottom edge,
+                    // which `contains` doesn't count as inside
+                    res.point = closest_point;
+                    res.min_translation = -res.normal * radius;
+                    return Some(res);
+                } else if sqr_dist < radius * radius {
                     res.normal = circle_pos - closest_point;
                     let depth = res.normal.length() - radius;
 
@@ -173,13 +218,14 @@ pub mod collisions {
     /// Performs a line-to-circle collision check.
     /// Returns `Some(RaycastHit)` if collision occurs, `None` otherwise.
     pub fn line_to_circle(start: Vec2, end: Vec2, s: &Shape) -> Option<RaycastHit> {
-        match s.shape_type {
+        match s.scaled_type() {
             ShapeType::Circle { radius } => {
                 let mut hit = RaycastHit::default();
 
-                let length = start.distance(end);
-                let d = (end - start) / length;
-                let m = start - s.position;
+                let ray = Ray2D::new(start, end);
+                let length = ray.length();
+                let d = ray.direction_normalized();
+                let m = start - s.center;
                 let b = m.dot(d);
                 let c = m.dot(m) - radius * radius;
 
@@ -193,40 +239,248 @@ pub mod collisions {
                     return None;
                 }
 
-                hit.fraction = -b - discr.sqrt();
+                hit.distance = (-b - discr.sqrt()).max(0.0);
+                hit.fraction = if length > 0.0 {
+                    hit.distance / length
+                } else {
+                    0.0
+                };
+                hit.point = ray.point_at(hit.fraction);
+                hit.normal = (hit.point - s.center).normalize_or_zero();
 
-                if hit.fraction < 0.0 {
-                    hit.fraction = 0.0;
+                Some(hit)
+            }
+            ShapeType::Box { .. } => panic!("s: expected circle, got box"),
+            ShapeType::None => None,
+        }
+    }
+
+    /// Performs a line-to-box collision check.
+    /// Returns `Some(RaycastHit)` if collision occurs, `None` otherwise.
+    pub fn line_to_box(start: Vec2, end: Vec2, s: &Shape) -> Option<RaycastHit> {
+        match s.scaled_type() {
+            ShapeType::Box { .. } => {
+                let ray = Ray2D::new(start, end);
+                let fraction = s.bounds.ray_intersects(&ray)?;
+
+                if fraction > 1.0 {
+                    return None;
                 }
 
-                hit.point = start + hit.fraction * d;
-                let dist = start.distance(hit.point);
-                hit.distance = dist;
-                hit.normal = (hit.point - s.position).normalize_or_zero();
-                hit.fraction = hit.distance / length;
+                let mut hit = RaycastHit {
+                    fraction,
+                    point: ray.point_at(fraction),
+                    ..Default::default()
+                };
+                hit.distance = start.distance(hit.point);
+                hit.normal = s.bounds.closest_point_on_border(hit.point).1;
 
                 Some(hit)
             }
-            ShapeType::Box { .. } => panic!("s: expected circle, got box"),
+            ShapeType::Circle { .. } => panic!("s: expected box, got circle"),
             ShapeType::None => None,
         }
     }
 
+    /// Computes the time of impact of a circle moving by `velocity` (delta movement of the tick)
+    /// with a static box. Returns the normalized time in range [0, 1] at which the circle
+    /// touches the box first or `None` if it doesn't touch it during the movement.
+    /// Returns `Some(0.0)` if the circle already overlaps the box.
+    ///
+    /// The circle's center is ray-cast against the box inflated by the radius (a Minkowski sum
+    /// of the box and the circle). The corners of the sum are rounded, so the hits within
+    /// the corner regions are tested against the circles around the box's corners.
+    pub fn time_of_impact_circle_box(
+        circle: Vec2,
+        radius: f32,
+        velocity: Vec2,
+        box_rect: FRect,
+    ) -> Option<f32> {
+        let mut expanded = box_rect;
+        expanded.inflate(radius, radius);
+
+        let ray = Ray2D::new(circle, circle + velocity);
+        let t = expanded.ray_intersects(&ray)?;
+        if t > 1.0 {
+            return None;
+        }
+
+        let point = ray.point_at(t);
+        let corner_x = if point.x < box_rect.left() {
+            Some(box_rect.left())
+        } else if point.x > box_rect.right() {
+            Some(box_rect.right())
+        } else {
+            None
+        };
+        let corner_y = if point.y < box_rect.top() {
+            Some(box_rect.top())
+        } else if point.y > box_rect.bottom() {
+            Some(box_rect.bottom())
+        } else {
+            None
+        };
+
+        match (corner_x, corner_y) {
+            (Some(x), Some(y)) => time_of_impact_point(circle, radius, velocity, Vec2::new(x, y)),
+            _ => Some(t),
+        }
+    }
+
+    /// Computes the time of impact of a circle moving by `velocity` with a static point.
+    fn time_of_impact_point(circle: Vec2, radius: f32, velocity: Vec2, point: Vec2) -> Option<f32> {
+        let m = circle - point;
+        let c = m.length_squared() - radius * radius;
+        if c <= 0.0 {
+            return Some(0.0);
+        }
+
+        let a = velocity.length_squared();
+        let b = m.dot(velocity);
+        // moving away or not moving at all
+        if a == 0.0 || b >= 0.0 {
+            return None;
+        }
+
+        let discr = b * b - a * c;
+        if discr < 0.0 {
+            return None;
+        }
+
+        let t = (-b - discr.sqrt()) / a;
+        (t <= 1.0).then_some(t)
+    }
+
+    /// Gets the shape's bounds placed at `shape.position + offset`. The bounds already contain
+    /// the collider's local offset (as well as the center does), so only the rest
+    /// of the offset (e.g. the motion) is applied.
+    fn offset_bounds(shape: &Shape, offset: Vec2) -> FRect {
+        let shift = shape.position + offset - shape.center;
+        let mut bounds = shape.bounds;
+        bounds.x += shift.x;
+        bounds.y += shift.y;
+        bounds
+    }
+
     fn minkowski_diff(
         first: &Shape,
         second: &Shape,
         first_offset: Vec2,
         second_offset: Vec2,
     ) -> FRect {
-        let pos1 = first.position + first_offset;
-        let b1 = first.bounds.location() + first_offset;
-        let b2 = second.bounds.location() + second_offset;
+        let b1 = offset_bounds(first, first_offset);
+        let b2 = offset_bounds(second, second_offset);
 
-        let pos_offset = pos1 - (b1 + first.bounds.size() / 2.0);
-        let top_left =
-            b1 + pos_offset - Vec2::new(b2.x + second.bounds.width, b2.y + second.bounds.height);
-        let full_size = first.bounds.size() + second.bounds.size();
+        let top_left = b1.location() - b2.max();
+        let full_size = b1.size() + b2.size();
 
         FRect::new(top_left.x, top_left.y, full_size.x, full_size.y)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+
+    use super::*;
+    use collisions::time_of_impact_circle_box;
+
+    #[test]
+    fn circle_area_and_perimeter() {
+        let circle = ShapeType::Circle { radius: 2.0 };
+
+        assert!((circle.area() - 4.0 * PI).abs() < 1e-5);
+        assert!((circle.perimeter() - 4.0 * PI).abs() < 1e-5);
+    }
+
+    #[test]
+    fn box_area_and_perimeter() {
+        let rect = ShapeType::Box {
+            width: 3.0,
+            height: 4.0,
+        };
+
+        assert_eq!(rect.area(), 12.0);
+        assert_eq!(rect.perimeter(), 14.0);
+    }
+
+    #[test]
+    fn none_has_no_area() {
+        assert_eq!(ShapeType::None.area(), 0.0);
+        assert_eq!(ShapeType::None.perimeter(), 0.0);
+    }
+
+    fn assert_time(actual: Option<f32>, expected: f32) {
+        let actual = actual.expect("no impact");
+        assert!((actual - expected).abs() < 1e-4, "{actual} != {expected}");
+    }
+
+    #[test]
+    fn time_of_impact_head_on() {
+        let box_rect = FRect::new(0.0, 0.0, 10.0, 10.0);
+
+        let toi =
+            time_of_impact_circle_box(Vec2::new(-20.0, 5.0), 5.0, Vec2::new(40.0, 0.0), box_rect);
+        assert_time(toi, 0.375);
+
+        let toi =
+            time_of_impact_circle_box(Vec2::new(5.0, 30.0), 5.0, Vec2::new(0.0, -20.0), box_rect);
+        assert_time(toi, 0.75);
+
+        // already overlapping
+        let toi =
+            time_of_impact_circle_box(Vec2::new(12.0, 5.0), 5.0, Vec2::new(40.0, 0.0), box_rect);
+        assert_time(toi, 0.0);
+    }
+
+    #[test]
+    fn time_of_impact_grazing_corner() {
+        let box_rect = FRect::new(0.0, 0.0, 10.0, 10.0);
+
+        // passes the top-left corner 4.9 units above it, touching the rounded corner
+        // of the expanded box later than its flat edge would be reached
+        let toi =
+            time_of_impact_circle_box(Vec2::new(-20.0, -4.9), 5.0, Vec2::new(40.0, 0.0), box_rect);
+        let expected = (20.0 - (25.0f32 - 4.9 * 4.9).sqrt()) / 40.0;
+        assert_time(toi, expected);
+        assert!(toi.unwrap() > 15.0 / 40.0);
+    }
+
+    #[test]
+    fn time_of_impact_miss() {
+        let box_rect = FRect::new(0.0, 0.0, 10.0, 10.0);
+
+        // cuts the corner of the expanded box, but stays outside its rounded corner
+        let toi =
+            time_of_impact_circle_box(Vec2::new(-10.0, 2.5), 5.0, Vec2::new(10.0, -10.0), box_rect);
+        assert_eq!(toi, None);
+        // passing by
+        let toi =
+            time_of_impact_circle_box(Vec2::new(-20.0, -6.0), 5.0, Vec2::new(40.0, 0.0), box_rect);
+        assert_eq!(toi, None);
+        // stopping short of the box
+        let toi =
+            time_of_impact_circle_box(Vec2::new(-20.0, 5.0), 5.0, Vec2::new(10.0, 0.0), box_rect);
+        assert_eq!(toi, None);
+        // moving away
+        let toi =
+            time_of_impact_circle_box(Vec2::new(-20.0, 5.0), 5.0, Vec2::new(-40.0, 0.0), box_rect);
+        assert_eq!(toi, None);
+    }
+
+    #[test]
+    fn concentric_circles_have_full_depth() {
+        let first = Shape::new(ShapeType::Circle { radius: 8.0 });
+        let second = Shape::new(ShapeType::Circle { radius: 4.0 });
+
+        let res = collisions::circle_to_circle(&first, &second, Vec2::ZERO, Vec2::ZERO)
+            .expect("concentric circles collide");
+        assert_eq!(res.normal.length(), 1.0);
+        assert_eq!(res.min_translation.length(), 12.0);
+        assert_eq!(res.min_translation, -12.0 * res.normal);
+
+        // moving the first circle by the translation separates them
+        let offset = -res.min_translation;
+        assert!(collisions::circle_to_circle(&first, &second, offset, Vec2::ZERO).is_none());
+    }
+}
@@ -1,3 +1,8 @@
+use std::sync::{
+    atomic::{AtomicBool, AtomicUsize, Ordering},
+    Mutex,
+};
+
 use bevy::{
     prelude::*,
     utils::{HashMap, HashSet},
@@ -7,7 +12,9 @@ use common::{
     FRect, Ray2D,
 };
 
-use super::{colliders::Collider, tests::*, RaycastHit, ALL_LAYERS};
+use super::{
+    colliders::Collider, raycast_debug::DebugRay, tests::*, QueryFilter, RaycastHit, ALL_LAYERS,
+};
 
 pub type ColliderSet = HashSet<Entity>;
 
@@ -16,6 +23,19 @@ pub struct SpatialHash {
     cell_size: i32,
     inverse_cell_size: f32,
     cell_map: IntIntMap,
+    /// Bounds every entity was registered with, so it can be removed
+    /// even if its `Collider` is no longer accessible.
+    registered_bounds: HashMap<Entity, FRect>,
+    /// `physics_layer` every entity was registered with.
+    registered_layers: HashMap<Entity, i32>,
+    /// Cells split by `physics_layer` if the hash is sharded, see `with_layer_sharding`.
+    layer_maps: Option<HashMap<i32, IntIntMap>>,
+    /// Number of raycasts performed since the last `take_raycast_count`.
+    /// Atomic because the raycasts only borrow the hash.
+    raycast_count: AtomicUsize,
+    /// Whether the raycasts are stored in `recorded_rays`, see `set_ray_recording`.
+    record_rays: AtomicBool,
+    recorded_rays: Mutex<Vec<DebugRay>>,
     pub grid_bounds: FRect,
 }
 
@@ -26,13 +46,53 @@ impl SpatialHash {
             cell_size,
             inverse_cell_size: 1.0 / cell_size as f32,
             cell_map: IntIntMap::default(),
+            registered_bounds: HashMap::new(),
+            registered_layers: HashMap::new(),
+            layer_maps: None,
+            raycast_count: AtomicUsize::new(0),
+            record_rays: AtomicBool::new(false),
+            recorded_rays: Mutex::new(Vec::new()),
             grid_bounds: FRect::new(0.0, 0.0, 0.0, 0.0),
         }
     }
 
+    /// Creates an instance of the hash which additionally stores the colliders split by their
+    /// `physics_layer`. Layer-masked broadphase queries then scan only the cells of the matching
+    /// layers instead of filtering every collider of a cell, which pays off when most of
+    /// the colliders are on layers the queries ignore. Queries without a layer mask still use
+    /// the combined cells. Costs extra memory and registration time.
+    pub fn with_layer_sharding(cell_size: i32) -> Self {
+        Self {
+            layer_maps: Some(HashMap::new()),
+            ..Self::new(cell_size)
+        }
+    }
+
+    /// Checks whether the hash was created with `with_layer_sharding`.
+    pub fn is_layer_sharded(&self) -> bool {
+        self.layer_maps.is_some()
+    }
+
     /// Register a collider in the hash.
+    /// Colliders with `ShapeType::None` never collide, so they're not registered.
+    ///
+    /// Cells store unique entities, so registering an already registered entity never
+    /// duplicates it: if its bounds are unchanged, it's a no-op, otherwise the entity is
+    /// moved out of the cells it was previously registered in.
     pub fn register(&mut self, collider: &Collider, entity: Entity) {
+        if collider.shape.shape_type == super::shapes::ShapeType::None {
+            return;
+        }
+
         let bounds = collider.bounds();
+        let layer = collider.physics_layer;
+
+        if let Some(prev_bounds) = self.registered_bounds.get(&entity) {
+            if *prev_bounds == bounds && self.registered_layers.get(&entity) == Some(&layer) {
+                return;
+            }
+            self.remove_entity(entity);
+        }
 
         let p1 = self.cell_coords(bounds.x, bounds.y);
         let p2 = self.cell_coords(bounds.right(), bounds.bottom());
@@ -44,36 +104,75 @@ impl SpatialHash {
             self.grid_bounds = self.grid_bounds.union_vec2(&p2);
         }
 
+        let mut layer_map = self
+            .layer_maps
+            .as_mut()
+            .map(|layer_maps| layer_maps.entry(layer).or_default());
+
         for x in (p1.x as i32)..=(p2.x as i32) {
             for y in (p1.y as i32)..=(p2.y as i32) {
-                if let Some(c) = self.get_cell_mut(x, y) {
-                    c.insert(entity);
-                } else {
-                    let mut c = HashSet::new();
-                    c.insert(entity);
-                    self.cell_map.insert(x, y, c);
+                self.cell_map.insert_entity(x, y, entity);
+                if let Some(layer_map) = layer_map.as_mut() {
+                    layer_map.insert_entity(x, y, entity);
                 }
             }
         }
+
+        self.registered_bounds.insert(entity, bounds);
+        self.registered_layers.insert(entity, layer);
     }
 
     /// Removes a collider from the hash.
-    pub fn remove(&mut self, collider: &Collider, entity: Entity) {
-        let bounds = collider.bounds();
+    /// Uses the bounds the collider was registered with, so it's a no-op for the colliders
+    /// which weren't registered (e.g. the ones with `ShapeType::None`).
+    pub fn remove(&mut self, _collider: &Collider, entity: Entity) {
+        self.remove_entity(entity);
+    }
 
+    /// Gets the bounds the entity was registered with.
+    pub fn registered_bounds(&self, entity: Entity) -> Option<FRect> {
+        self.registered_bounds.get(&entity).copied()
+    }
+
+    /// Gets the physics layer the entity was registered with.
+    pub fn registered_layer(&self, entity: Entity) -> Option<i32> {
+        self.registered_layers.get(&entity).copied()
+    }
+
+    /// Removes an entity from the hash using the bounds it was registered with.
+    /// Unlike `remove`, doesn't require the entity's `Collider`, so it works for the entities
+    /// which are being despawned. Returns `false` if the entity wasn't registered.
+    pub fn remove_entity(&mut self, entity: Entity) -> bool {
+        if let Some(bounds) = self.registered_bounds.remove(&entity) {
+            let layer = self.registered_layers.remove(&entity);
+            self.remove_from_cells(bounds, layer, entity);
+            true
+        } else {
+            false
+        }
+    }
+
+    fn remove_from_cells(&mut self, bounds: FRect, layer: Option<i32>, entity: Entity) {
         let p1 = self.cell_coords(bounds.x, bounds.y);
         let p2 = self.cell_coords(bounds.right(), bounds.bottom());
 
+        let mut layer_map = self
+            .layer_maps
+            .as_mut()
+            .zip(layer)
+            .and_then(|(layer_maps, layer)| layer_maps.get_mut(&layer));
+
         for x in (p1.x as i32)..=(p2.x as i32) {
             for y in (p1.y as i32)..=(p2.y as i32) {
-                if let Some(c) = self.get_cell_mut(x, y) {
-                    c.retain(|&x| x != entity);
-                } else {
+                if !self.cell_map.remove_entity(x, y, entity) {
                     error!(
-                        "removing collider {:?} from a cell that is is not present in",
-                        collider
+                        "removing entity {:?} from a cell that is is not present in",
+                        entity
                     );
                 }
+                if let Some(layer_map) = layer_map.as_mut() {
+                    layer_map.remove_entity(x, y, entity);
+                }
             }
         }
     }
@@ -121,34 +220,122 @@ impl SpatialHash {
         exclude_collider: Option<Entity>,
         layer_mask: Option<i32>,
     ) -> HashSet<Entity> {
-        let mut tmp_hashset = HashSet::new();
-
         let layer_mask = layer_mask.unwrap_or(ALL_LAYERS);
 
+        self.broadphase_by(query, bounds, layer_mask, |entity, collider| {
+            exclude_collider != Some(entity) && is_flag_set(layer_mask, collider.physics_layer)
+        })
+    }
+
+    /// Gets the colliders whose bounds intersect the world-space `rect`, e.g. for culling
+    /// by the visible area. Unlike [`aabb_broadphase`], the result is sorted by `Entity`,
+    /// so it's stable from frame to frame.
+    pub fn query_region(
+        &self,
+        query: &Query<&Collider>,
+        rect: FRect,
+        layer_mask: Option<i32>,
+    ) -> Vec<Entity> {
+        let mut result: Vec<Entity> = self
+            .aabb_broadphase(query, rect, None, layer_mask)
+            .into_iter()
+            .collect();
+        result.sort_unstable();
+        result
+    }
+
+    /// Gets the colliders visible through the camera, see [`query_region`].
+    /// Returns an empty list if the camera's viewport can't be mapped to the world,
+    /// e.g. if the camera has no render target yet.
+    pub fn query_viewport(
+        &self,
+        query: &Query<&Collider>,
+        camera: &Camera,
+        camera_transform: &GlobalTransform,
+        layer_mask: Option<i32>,
+    ) -> Vec<Entity> {
+        let Some(viewport_size) = camera.logical_viewport_size() else {
+            return vec![];
+        };
+        // `viewport_to_world_2d` expects the coordinates relative to the viewport
+        let corners = (
+            camera.viewport_to_world_2d(camera_transform, Vec2::ZERO),
+            camera.viewport_to_world_2d(camera_transform, viewport_size),
+        );
+        let (Some(a), Some(b)) = corners else {
+            return vec![];
+        };
+
+        // The viewport's y goes down, so the corners are swapped in the world
+        let min = a.min(b);
+        let size = a.max(b) - min;
+        self.query_region(query, FRect::new(min.x, min.y, size.x, size.y), layer_mask)
+    }
+
+    /// Same as [`aabb_broadphase`] but the colliders are filtered by `filter`.
+    pub fn aabb_broadphase_filtered(
+        &self,
+        query: &Query<&Collider>,
+        bounds: FRect,
+        filter: &QueryFilter,
+    ) -> HashSet<Entity> {
+        self.broadphase_by(query, bounds, filter.layer_mask, |entity, collider| {
+            filter.accepts(entity, collider)
+        })
+    }
+
+    /// Gets the colliders whose bounds intersect `bounds` and which are accepted by `accept`.
+    /// `layer_mask` is used only to pick the layers to scan if the hash is sharded.
+    fn broadphase_by(
+        &self,
+        query: &Query<&Collider>,
+        bounds: FRect,
+        layer_mask: i32,
+        accept: impl Fn(Entity, &Collider) -> bool,
+    ) -> HashSet<Entity> {
+        let mut tmp_hashset = HashSet::new();
+
         let p1 = self.cell_coords(bounds.x, bounds.y);
         let p2 = self.cell_coords(bounds.right(), bounds.bottom());
 
-        for x in (p1.x as i32)..=(p2.x as i32) {
-            for y in (p1.y as i32)..=(p2.y as i32) {
-                let cell = self.get_cell(x, y);
+        let mut check_cell = |cell: &ColliderSet| {
+            for entity in cell {
+                let collder = query.get(*entity).unwrap();
 
-                match cell {
-                    Some(cell) => {
-                        for entity in cell {
-                            let collder = query.get(*entity).unwrap();
+                if !accept(*entity, collder) {
+                    continue;
+                }
 
-                            if exclude_collider.is_some_and(|excl| *entity == excl)
-                                || !is_flag_set(layer_mask, collder.physics_layer)
-                            {
-                                continue;
-                            }
+                if bounds.intersects(collder.bounds()) {
+                    tmp_hashset.insert(*entity);
+                }
+            }
+        };
+
+        match &self.layer_maps {
+            // Scanning only the layers matching the mask
+            Some(layer_maps) if layer_mask != ALL_LAYERS => {
+                for (layer, layer_map) in layer_maps {
+                    if !is_flag_set(layer_mask, *layer) {
+                        continue;
+                    }
 
-                            if bounds.intersects(collder.bounds()) {
-                                tmp_hashset.insert(*entity);
+                    for x in (p1.x as i32)..=(p2.x as i32) {
+                        for y in (p1.y as i32)..=(p2.y as i32) {
+                            if let Some(cell) = layer_map.get(x, y) {
+                                check_cell(cell);
                             }
                         }
                     }
-                    None => continue,
+                }
+            }
+            _ => {
+                for x in (p1.x as i32)..=(p2.x as i32) {
+                    for y in (p1.y as i32)..=(p2.y as i32) {
+                        if let Some(cell) = self.get_cell(x, y) {
+                            check_cell(cell);
+                        }
+                    }
                 }
             }
         }
@@ -163,40 +350,63 @@ impl SpatialHash {
         query: &Query<&Collider>,
         rect: FRect,
         exclude_collider: Option<Entity>,
-        mut results: Option<&mut Vec<Entity>>,
+        results: Option<&mut Vec<Entity>>,
         layer_mask: Option<i32>,
     ) -> i32 {
-        let mut total = 0;
-        let potentials = self.aabb_broadphase(query, rect, exclude_collider, layer_mask);
+        let found = self.overlap_rectangle_filtered(
+            query,
+            rect,
+            &QueryFilter::from_args(exclude_collider, layer_mask),
+        );
+        let total = found.len() as i32;
+        if let Some(results) = results {
+            results.extend(found);
+        }
+
+        total
+    }
+
+    /// Gets all colliders inside specified rectangle which pass the `filter`.
+    pub fn overlap_rectangle_filtered(
+        &self,
+        query: &Query<&Collider>,
+        rect: FRect,
+        filter: &QueryFilter,
+    ) -> Vec<Entity> {
+        let mut results = vec![];
+        let potentials = self.aabb_broadphase_filtered(query, rect, filter);
         for entity in potentials {
             let collider = query.get(entity).unwrap();
-            match collider.shape.shape_type {
+            match collider.shape.scaled_type() {
                 super::shapes::ShapeType::Circle { radius } => {
                     if rect_to_circle(
                         rect.x,
                         rect.y,
                         rect.width,
                         rect.height,
-                        collider.center(),
+                        collider.absolute_position(),
                         radius,
                     ) {
-                        if let Some(results) = results.as_mut() {
-                            results.push(entity);
-                        }
-                        total += 1;
+                        results.push(entity);
                     }
                 }
                 super::shapes::ShapeType::Box { .. } => {
-                    if let Some(results) = results.as_mut() {
+                    // The broadphase only guarantees the cells overlap, not the shapes
+                    let bounds = collider.bounds();
+                    if rect_to_rect(
+                        rect.location(),
+                        rect.size(),
+                        bounds.location(),
+                        bounds.size(),
+                    ) {
                         results.push(entity);
                     }
-                    total += 1;
                 }
                 super::shapes::ShapeType::None => {}
             }
         }
 
-        total
+        results
     }
 
     /// Returns number of all colliders within circle.
@@ -207,9 +417,31 @@ impl SpatialHash {
         circle_center: Vec2,
         radius: f32,
         exclude_collider: Option<Entity>,
-        mut results: Option<&mut Vec<Entity>>,
+        results: Option<&mut Vec<Entity>>,
         layer_mask: Option<i32>,
     ) -> i32 {
+        let found = self.overlap_circle_filtered(
+            query,
+            circle_center,
+            radius,
+            &QueryFilter::from_args(exclude_collider, layer_mask),
+        );
+        let total = found.len() as i32;
+        if let Some(results) = results {
+            results.extend(found);
+        }
+
+        total
+    }
+
+    /// Gets all colliders within circle which pass the `filter`.
+    pub fn overlap_circle_filtered(
+        &self,
+        query: &Query<&Collider>,
+        circle_center: Vec2,
+        radius: f32,
+        filter: &QueryFilter,
+    ) -> Vec<Entity> {
         let bounds = FRect::new(
             circle_center.x - radius,
             circle_center.y - radius,
@@ -217,53 +449,343 @@ impl SpatialHash {
             radius * 2.0,
         );
 
-        let mut total = 0;
+        let mut results = vec![];
 
-        let mut test_circle = Collider::new(super::shapes::ShapeType::Circle { radius });
-        test_circle.set_position(circle_center);
-
-        let potentials = self.aabb_broadphase(query, bounds, exclude_collider, layer_mask);
+        // The broadphase uses the circle's bounds, the precise tests use the same center
+        let potentials = self.aabb_broadphase_filtered(query, bounds, filter);
         for entity in potentials {
             let collider = query.get(entity).unwrap();
-            match collider.shape.shape_type {
-                super::shapes::ShapeType::Circle { .. } => {
-                    if collider.overlaps(&test_circle) {
-                        if let Some(results) = results.as_mut() {
-                            results.push(entity);
-                        }
-                        total += 1;
-                    }
-                }
+            let overlaps = match collider.shape.scaled_type() {
+                super::shapes::ShapeType::Circle {
+                    radius: collider_radius,
+                } => circle_to_circle(
+                    circle_center,
+                    radius,
+                    collider.absolute_position(),
+                    collider_radius,
+                ),
                 super::shapes::ShapeType::Box { .. } => {
-                    if collider.overlaps(&test_circle) {
-                        if let Some(results) = results.as_mut() {
-                            results.push(entity);
+                    let collider_bounds = collider.bounds();
+                    rect_to_circle(
+                        collider_bounds.x,
+                        collider_bounds.y,
+                        collider_bounds.width,
+                        collider_bounds.height,
+                        circle_center,
+                        radius,
+                    )
+                }
+                super::shapes::ShapeType::None => false,
+            };
+
+            if overlaps {
+                results.push(entity);
+            }
+        }
+
+        results
+    }
+
+    /// Gets all colliders within circle sorted nearest-first along with the distances
+    /// from `circle_center` to their shapes (see `Collider::distance_to_point`).
+    /// Returns at most `max_results` nearest colliders.
+    /// Useful for area-of-effect abilities like explosions or auras.
+    pub fn overlap_circle_sorted(
+        &self,
+        query: &Query<&Collider>,
+        circle_center: Vec2,
+        radius: f32,
+        exclude_collider: Option<Entity>,
+        layer_mask: Option<i32>,
+        max_results: usize,
+    ) -> Vec<(Entity, f32)> {
+        let mut overlaps = vec![];
+        self.overlap_circle(
+            query,
+            circle_center,
+            radius,
+            exclude_collider,
+            Some(&mut overlaps),
+            layer_mask,
+        );
+
+        let mut res: Vec<(Entity, f32)> = overlaps
+            .into_iter()
+            .map(|entity| {
+                let collider = query.get(entity).unwrap();
+                (entity, collider.distance_to_point(circle_center))
+            })
+            .collect();
+        res.sort_by(|a, b| a.1.total_cmp(&b.1));
+        res.truncate(max_results);
+
+        res
+    }
+
+    /// Gets all colliders whose centers lie within the circular sector, e.g. a vision cone
+    /// or a melee arc. The sector starts at `origin`, is facing the `direction` (doesn't need to be normalized)
+    /// and spans `half_angle` radians to each side of it.
+    ///
+    /// If `line_of_sight_mask` is set, the candidates blocked by a collider
+    /// on the specified layers between `origin` and the candidate's center are skipped.
+    #[allow(clippy::too_many_arguments)]
+    pub fn overlap_sector(
+        &self,
+        query: &Query<&Collider>,
+        origin: Vec2,
+        direction: Vec2,
+        half_angle: f32,
+        radius: f32,
+        exclude_collider: Option<Entity>,
+        layer_mask: Option<i32>,
+        line_of_sight_mask: Option<i32>,
+    ) -> Vec<Entity> {
+        let mut candidates = vec![];
+        self.overlap_circle(
+            query,
+            origin,
+            radius,
+            exclude_collider,
+            Some(&mut candidates),
+            layer_mask,
+        );
+
+        let direction = direction.normalize_or_zero();
+        let min_dot = half_angle.cos();
+
+        candidates.retain(|&entity| {
+            let center = query.get(entity).unwrap().absolute_position();
+            let to_center = center - origin;
+
+            if to_center.length_squared() > radius * radius {
+                return false;
+            }
+            if to_center != Vec2::ZERO && to_center.normalize().dot(direction) < min_dot {
+                return false;
+            }
+
+            if let Some(mask) = line_of_sight_mask {
+                let (_, hits) = self.linecast(query, origin, center, exclude_collider, mask, false);
+                let blocked = hits.iter().any(|hit| hit.collider != Some(entity));
+                if blocked {
+                    return false;
+                }
+            }
+
+            true
+        });
+
+        candidates
+    }
+
+    /// Finds the closest collider to the `point` within `max_radius`.
+    /// Returns the collider's `Entity` and the distance from the `point` to the collider's shape.
+    ///
+    /// The hash is searched ring by ring around the `point`'s cell, so the search
+    /// stops as soon as no collider in the next rings can be closer than the found one.
+    pub fn nearest(
+        &self,
+        query: &Query<&Collider>,
+        point: Vec2,
+        max_radius: f32,
+        exclude_collider: Option<Entity>,
+        layer_mask: Option<i32>,
+    ) -> Option<(Entity, f32)> {
+        let layer_mask = layer_mask.unwrap_or(ALL_LAYERS);
+        let origin = self.cell_coords(point.x, point.y);
+        let (ox, oy) = (origin.x as i32, origin.y as i32);
+
+        let mut checked = ColliderSet::new();
+        let mut closest: Option<(Entity, f32)> = None;
+
+        let mut ring = 0;
+        loop {
+            for y in (oy - ring)..=(oy + ring) {
+                for x in (ox - ring)..=(ox + ring) {
+                    // process only the border cells of the ring
+                    if (x - ox).abs() != ring && (y - oy).abs() != ring {
+                        continue;
+                    }
+
+                    let Some(cell) = self.get_cell(x, y) else {
+                        continue;
+                    };
+
+                    for entity in cell {
+                        if exclude_collider.is_some_and(|excl| *entity == excl)
+                            || !checked.insert(*entity)
+                        {
+                            continue;
+                        }
+
+                        let collider = query.get(*entity).unwrap();
+                        if !is_flag_set(layer_mask, collider.physics_layer) {
+                            continue;
+                        }
+
+                        let distance = collider.distance_to_point(point);
+                        if distance <= max_radius
+                            && !closest.is_some_and(|(_, closest)| closest <= distance)
+                        {
+                            closest = Some((*entity, distance));
                         }
-                        total += 1;
                     }
                 }
-                super::shapes::ShapeType::None => {}
             }
+
+            // Every collider which was not found yet lies fully outside of
+            // the checked rings, so it's at least `ring * cell_size` away.
+            let checked_distance = (ring * self.cell_size) as f32;
+            if closest.is_some_and(|(_, distance)| distance <= checked_distance)
+                || checked_distance > max_radius
+                || self.ring_covers_grid(ox, oy, ring)
+            {
+                break;
+            }
+
+            ring += 1;
         }
 
-        total
+        closest
     }
 
     /// Performs a linecast and returns number of collisions and collisions themselves.
+    /// Hits are sorted by distance from `start`, the closest one goes first.
+    /// Trigger colliders are skipped unless `include_triggers` is set to `true`.
+    /// `exclude_collider` is never hit, e.g. the caster's own collider the ray starts inside of.
     pub fn linecast(
         &self,
         query: &Query<&Collider>,
         start: Vec2,
         end: Vec2,
+        exclude_collider: Option<Entity>,
+        layer_mask: i32,
+        include_triggers: bool,
+    ) -> (i32, Vec<RaycastHit>) {
+        self.linecast_max(
+            query,
+            start,
+            end,
+            exclude_collider,
+            layer_mask,
+            include_triggers,
+            usize::MAX,
+        )
+    }
+
+    /// Same as [`linecast`] but returns at most `max_hits` closest hits.
+    #[allow(clippy::too_many_arguments)]
+    pub fn linecast_max(
+        &self,
+        query: &Query<&Collider>,
+        start: Vec2,
+        end: Vec2,
+        exclude_collider: Option<Entity>,
         layer_mask: i32,
+        include_triggers: bool,
+        max_hits: usize,
     ) -> (i32, Vec<RaycastHit>) {
-        let mut res = Vec::new();
+        let filter = QueryFilter::from_args(exclude_collider, Some(layer_mask))
+            .with_triggers(include_triggers);
+        let res = self.linecast_filtered(query, start, end, &filter, max_hits);
+        (res.len() as i32, res)
+    }
+
+    /// Performs a linecast and returns at most `max_hits` closest hits of the colliders
+    /// which pass the `filter`, sorted by distance from `start`.
+    pub fn linecast_filtered(
+        &self,
+        query: &Query<&Collider>,
+        start: Vec2,
+        end: Vec2,
+        filter: &QueryFilter,
+        max_hits: usize,
+    ) -> Vec<RaycastHit> {
+        self.raycast_count.fetch_add(1, Ordering::Relaxed);
         let ray = Ray2D::new(start, end);
         let mut parser = RaycastResultParser::default();
-        parser.start(ray, layer_mask);
+        parser.start(ray, filter.layer_mask, filter.include_triggers);
+        for entity in &filter.exclude {
+            parser.exclude(*entity);
+        }
 
-        let mut cur_cell = self.cell_coords(start.x, start.y);
-        let last_cell = self.cell_coords(end.x, end.y);
+        self.traverse_ray(&ray, |cell, _| {
+            parser.check_ray_intersection(query, cell);
+            false
+        });
+
+        let hits = parser.finish(max_hits);
+        self.record_ray(start, end, hits.first().copied());
+        hits
+    }
+
+    /// Fires a hitscan from `start` to `end` and returns the first `Entity` hit along with
+    /// the hit point. Unlike movement, a hitscan can hit triggers (e.g. damage volumes)
+    /// if `include_triggers` is set to `true`. The shooter can be skipped via `exclude_collider`.
+    pub fn hitscan(
+        &self,
+        query: &Query<&Collider>,
+        start: Vec2,
+        end: Vec2,
+        exclude_collider: Option<Entity>,
+        layer_mask: i32,
+        include_triggers: bool,
+    ) -> Option<(Entity, Vec2)> {
+        let (_, hits) = self.linecast_max(
+            query,
+            start,
+            end,
+            exclude_collider,
+            layer_mask,
+            include_triggers,
+            1,
+        );
+        hits.first()
+            .and_then(|hit| hit.collider.map(|entity| (entity, hit.point)))
+    }
+
+    /// Returns the closest hit of a non-trigger collider on the ray from `start` to `end` or `None`
+    /// if nothing blocks the ray. Unlike `linecast`, stops walking the cells as soon as the
+    /// closest hit is confirmed, which makes it cheaper for line-of-sight checks.
+    pub fn query_ray_first(
+        &self,
+        query: &Query<&Collider>,
+        start: Vec2,
+        end: Vec2,
+        exclude_collider: Option<Entity>,
+        layer_mask: i32,
+    ) -> Option<RaycastHit> {
+        self.raycast_count.fetch_add(1, Ordering::Relaxed);
+        let ray = Ray2D::new(start, end);
+        let mut parser = RaycastResultParser::default();
+        parser.start(ray, layer_mask, false);
+        if let Some(entity) = exclude_collider {
+            parser.exclude(entity);
+        }
+
+        self.traverse_ray(&ray, |cell, exit_fraction| {
+            parser.check_ray_intersection(query, cell);
+            // A collider may span multiple cells, so the closest hit is confirmed
+            // only if it's located within the cells visited so far
+            parser
+                .closest_hit()
+                .is_some_and(|hit| hit.fraction <= exit_fraction)
+        });
+
+        let hit = parser.finish(1).pop();
+        self.record_ray(start, end, hit);
+        hit
+    }
+
+    /// Walks through all the cells the ray passes in the order of the ray's direction
+    /// and calls `visit` for every non-empty one along with the fraction of the ray's length
+    /// where the ray leaves the cell. Stops as soon as `visit` returns `true`.
+    fn traverse_ray<F>(&self, ray: &Ray2D, mut visit: F)
+    where
+        F: FnMut(&ColliderSet, f32) -> bool,
+    {
+        let mut cur_cell = self.cell_coords(ray.start.x, ray.start.y);
+        let last_cell = self.cell_coords(ray.end.x, ray.end.y);
 
         let mut step_x = sign(ray.direction.x);
         let mut step_y = sign(ray.direction.y);
@@ -310,15 +832,19 @@ impl SpatialHash {
             f32::MAX
         };
 
+        // The ray never leaves the cell along the axis it doesn't step on
+        let exit_fraction = |max_x: f32, max_y: f32| {
+            let x = if step_x != 0 { max_x } else { f32::MAX };
+            let y = if step_y != 0 { max_y } else { f32::MAX };
+            x.min(y).min(1.0)
+        };
+
         if let Some(cell) = self.get_cell(cur_cell.x as i32, cur_cell.y as i32) {
-            if parser.check_ray_intersection(query, cell, &mut res) {
-                parser.reset();
-                return (parser.hit_counter, res);
+            if visit(cell, exit_fraction(max_x, max_y)) {
+                return;
             }
         }
 
-        let mut cell;
-
         while cur_cell.x != last_cell.x || cur_cell.y != last_cell.y {
             if max_x < max_y {
                 cur_cell.x = (approach(cur_cell.x, last_cell.x, step_x.abs() as f32) as i32) as f32;
@@ -328,22 +854,115 @@ impl SpatialHash {
                 max_y += dt_y;
             }
 
-            cell = self.get_cell(cur_cell.x as i32, cur_cell.y as i32);
-            if let Some(cell) = cell {
-                if parser.check_ray_intersection(query, cell, &mut res) {
-                    parser.reset();
-                    return (parser.hit_counter, res);
+            if let Some(cell) = self.get_cell(cur_cell.x as i32, cur_cell.y as i32) {
+                if visit(cell, exit_fraction(max_x, max_y)) {
+                    return;
                 }
             }
         }
-
-        parser.reset();
-        (parser.hit_counter, res)
     }
 
     /// Fully clears the hash.
     pub fn clear(&mut self) {
         self.cell_map.clear();
+        self.registered_bounds.clear();
+        self.registered_layers.clear();
+        if let Some(layer_maps) = self.layer_maps.as_mut() {
+            layer_maps.clear();
+        }
+    }
+
+    /// Changes the cell size and rebuilds the cells of all the registered colliders under it,
+    /// e.g. when moving from a small arena to a large open map. The colliders are re-registered
+    /// with their current bounds, the ones whose `Collider` is gone are dropped.
+    /// `grid_bounds` is recalculated in the new cell coordinates.
+    pub fn rehash(&mut self, new_cell_size: i32, query: &Query<&Collider>) {
+        let entities: Vec<Entity> = self.registered_bounds.keys().copied().collect();
+
+        self.clear();
+        self.cell_size = new_cell_size;
+        self.inverse_cell_size = 1.0 / new_cell_size as f32;
+        self.grid_bounds = FRect::new(0.0, 0.0, 0.0, 0.0);
+
+        for entity in entities {
+            if let Ok(collider) = query.get(entity) {
+                self.register(collider, entity);
+            }
+        }
+    }
+
+    /// Reserves capacity for at least `additional` more colliders to be registered.
+    /// Each collider is assumed to take a single cell.
+    pub fn reserve(&mut self, additional: usize) {
+        self.cell_map.store.reserve(additional);
+        self.registered_bounds.reserve(additional);
+        self.registered_layers.reserve(additional);
+    }
+
+    /// Drops all the empty cells and shrinks the capacity of the internal storage.
+    /// Can be called periodically to reclaim the memory after a lot of colliders were removed.
+    pub fn shrink_to_fit(&mut self) {
+        self.cell_map.store.retain(|_, cell| !cell.is_empty());
+        self.cell_map.store.shrink_to_fit();
+        if let Some(layer_maps) = self.layer_maps.as_mut() {
+            layer_maps.retain(|_, layer_map| !layer_map.store.is_empty());
+            for layer_map in layer_maps.values_mut() {
+                layer_map.store.shrink_to_fit();
+            }
+        }
+    }
+
+    /// Gets the number of cells currently stored in the hash.
+    pub fn cell_count(&self) -> usize {
+        self.cell_map.store.len()
+    }
+
+    /// Gets the number of registered colliders.
+    pub fn registered_count(&self) -> usize {
+        self.registered_bounds.len()
+    }
+
+    /// Gets the largest number of colliders stored in a single cell.
+    pub fn max_cell_occupancy(&self) -> usize {
+        self.cell_map
+            .store
+            .values()
+            .map(|cell| cell.len())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Gets the number of raycasts performed since the last call and resets the counter.
+    pub fn take_raycast_count(&self) -> usize {
+        self.raycast_count.swap(0, Ordering::Relaxed)
+    }
+
+    /// Toggles storing every raycast, so they can be drawn for debugging.
+    /// `PhysicsPlugin` keeps it in sync with `RaycastDebugBuffer::enabled`.
+    pub fn set_ray_recording(&self, enabled: bool) {
+        self.record_rays.store(enabled, Ordering::Relaxed);
+        if !enabled {
+            self.recorded_rays.lock().unwrap().clear();
+        }
+    }
+
+    /// Checks whether the raycasts are stored, see `set_ray_recording`.
+    pub fn is_recording_rays(&self) -> bool {
+        self.record_rays.load(Ordering::Relaxed)
+    }
+
+    /// Takes the raycasts stored since the last call.
+    pub fn take_recorded_rays(&self) -> Vec<DebugRay> {
+        std::mem::take(&mut *self.recorded_rays.lock().unwrap())
+    }
+
+    fn record_ray(&self, start: Vec2, end: Vec2, hit: Option<RaycastHit>) {
+        if self.record_rays.load(Ordering::Relaxed) {
+            self.recorded_rays
+                .lock()
+                .unwrap()
+                .push(DebugRay { start, end, hit });
+        }
     }
 
     /// Gets current cell size.
@@ -367,11 +986,110 @@ impl SpatialHash {
         result
     }
 
+    /// Gets all pairs of entities sharing at least one cell, i.e. the potentially colliding pairs.
+    /// Every pair is yielded exactly once, even if the entities share multiple cells,
+    /// with the smaller `Entity` going first. Layers and bounds are not checked.
+    pub fn candidate_pairs(&self) -> impl Iterator<Item = (Entity, Entity)> {
+        let mut pairs = HashSet::new();
+
+        for cell in self.cell_map.store.values() {
+            for (i, a) in cell.iter().enumerate() {
+                for b in cell.iter().skip(i + 1) {
+                    pairs.insert(if a < b { (*a, *b) } else { (*b, *a) });
+                }
+            }
+        }
+
+        pairs.into_iter()
+    }
+
+    /// Calls `f` for every pair of colliders whose centers (`absolute_position`) are within
+    /// `radius` of each other, every pair exactly once. Only the colliders on `layer_mask`
+    /// are paired.
+    ///
+    /// Unlike running `overlap_circle` for every collider, walks the hash once: the colliders
+    /// are bucketed by the cell of their center, and every bucket is paired only with
+    /// the buckets within `radius` of it, which makes it suitable for crowd behaviors
+    /// like separation.
+    pub fn for_each_pair_within<F>(
+        &self,
+        query: &Query<&Collider>,
+        radius: f32,
+        layer_mask: Option<i32>,
+        mut f: F,
+    ) where
+        F: FnMut(Entity, Entity),
+    {
+        let layer_mask = layer_mask.unwrap_or(ALL_LAYERS);
+
+        let mut buckets: HashMap<(i32, i32), Vec<(Entity, Vec2)>> = HashMap::new();
+        for &entity in self.registered_bounds.keys() {
+            let Ok(collider) = query.get(entity) else {
+                continue;
+            };
+            if !is_flag_set(layer_mask, collider.physics_layer) {
+                continue;
+            }
+            let center = collider.absolute_position();
+            let cell = self.cell_coords(center.x, center.y);
+            buckets
+                .entry((cell.x as i32, cell.y as i32))
+                .or_default()
+                .push((entity, center));
+        }
+
+        let reach = (radius * self.inverse_cell_size).ceil() as i32;
+        let radius_sqr = radius * radius;
+
+        for (&(x, y), bucket) in &buckets {
+            for (i, &(a, a_pos)) in bucket.iter().enumerate() {
+                for &(b, b_pos) in bucket.iter().skip(i + 1) {
+                    if a_pos.distance_squared(b_pos) <= radius_sqr {
+                        f(a, b);
+                    }
+                }
+            }
+
+            for dy in -reach..=reach {
+                for dx in -reach..=reach {
+                    // Only a half of the neighbors is visited, so every pair of
+                    // the buckets is processed once
+                    if dy < 0 || (dy == 0 && dx <= 0) {
+                        continue;
+                    }
+                    let Some(other) = buckets.get(&(x + dx, y + dy)) else {
+                        continue;
+                    };
+
+                    for &(a, a_pos) in bucket {
+                        for &(b, b_pos) in other {
+                            if a_pos.distance_squared(b_pos) <= radius_sqr {
+                                f(a, b);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn ring_covers_grid(&self, x: i32, y: i32, ring: i32) -> bool {
+        (x - ring) as f32 <= self.grid_bounds.left()
+            && (x + ring) as f32 >= self.grid_bounds.right()
+            && (y - ring) as f32 <= self.grid_bounds.top()
+            && (y + ring) as f32 >= self.grid_bounds.bottom()
+    }
+
     fn cell_coords(&self, x: f32, y: f32) -> Vec2 {
-        Vec2::new(
+        let cell = Vec2::new(
             floor_to_int(x * self.inverse_cell_size) as f32,
             floor_to_int(y * self.inverse_cell_size) as f32,
-        )
+        );
+        debug_assert!(
+            cell.x.abs() <= MAX_CELL_COORD && cell.y.abs() <= MAX_CELL_COORD,
+            "position ({x}, {y}) is out of the spatial hash range"
+        );
+        cell
     }
 
     fn get_cell(&self, x: i32, y: i32) -> Option<&ColliderSet> {
@@ -381,14 +1099,6 @@ impl SpatialHash {
 
         None
     }
-
-    fn get_cell_mut(&mut self, x: i32, y: i32) -> Option<&mut ColliderSet> {
-        if let Some(collider) = self.cell_map.get_mut(x, y) {
-            return Some(collider);
-        }
-
-        None
-    }
 }
 
 #[derive(Debug, Default)]
@@ -396,63 +1106,91 @@ struct IntIntMap {
     pub store: HashMap<i64, ColliderSet>,
 }
 
+/// Max absolute cell coordinate. Cell coordinates are passed around as `f32` which
+/// represents integers exactly only up to 2^24, further cells would alias with their neighbors.
+const MAX_CELL_COORD: f32 = (1 << 24) as f32;
+
+/// Packs cell coordinates into a single key: `x` goes to the high 32 bits and `y` to the low ones,
+/// so every pair of `i32`s maps to a distinct key.
 fn get_key(x: i32, y: i32) -> i64 {
-    let shl = (x as i64).overflowing_shl(32);
-    shl.0 | ((y as u32) as i64)
+    ((x as i64) << 32) | ((y as u32) as i64)
 }
 
 impl IntIntMap {
-    pub fn insert(&mut self, x: i32, y: i32, colliders: ColliderSet) {
-        self.store.insert(get_key(x, y), colliders);
-    }
-
     pub fn get(&self, x: i32, y: i32) -> Option<&ColliderSet> {
         self.store.get(&get_key(x, y))
     }
 
-    pub fn get_mut(&mut self, x: i32, y: i32) -> Option<&mut ColliderSet> {
-        self.store.get_mut(&get_key(x, y))
-    }
-
     pub fn clear(&mut self) {
         self.store.clear();
     }
+
+    /// Adds the entity to the cell, creating the cell if needed.
+    pub fn insert_entity(&mut self, x: i32, y: i32, entity: Entity) {
+        self.store.entry(get_key(x, y)).or_default().insert(entity);
+    }
+
+    /// Removes the entity from the cell. The cell is dropped once it's empty,
+    /// so the map doesn't grow as entities roam the world.
+    /// Returns `false` if there is no such cell.
+    pub fn remove_entity(&mut self, x: i32, y: i32, entity: Entity) -> bool {
+        let key = get_key(x, y);
+        let Some(cell) = self.store.get_mut(&key) else {
+            return false;
+        };
+
+        cell.remove(&entity);
+        if cell.is_empty() {
+            self.store.remove(&key);
+        }
+
+        true
+    }
 }
 
 #[derive(Default)]
 struct RaycastResultParser {
-    pub hit_counter: i32,
-    //hits: Option<&'a mut [RaycastHit<'a>]>,
-    tmp_hit: Option<RaycastHit>,
-    checked_colliders: Vec<Entity>,
-    cell_hits: Vec<RaycastHit>,
+    /// Colliders that were already tested during the current linecast.
+    /// A collider may span multiple cells, so it's tested only once.
+    checked_colliders: ColliderSet,
+    /// Hits collected across all the visited cells.
+    hits: Vec<RaycastHit>,
     ray: Option<Ray2D>,
     layer_mask: i32,
+    include_triggers: bool,
 }
 
 impl RaycastResultParser {
-    pub fn start(&mut self, ray: Ray2D, layer_mask: i32) {
+    pub fn start(&mut self, ray: Ray2D, layer_mask: i32, include_triggers: bool) {
         self.ray = Some(ray);
         self.layer_mask = layer_mask;
-        self.hit_counter = 0;
+        self.include_triggers = include_triggers;
     }
 
+    /// Skips the entity during the linecast. It's marked as already checked,
+    /// so it's never tested against the ray.
+    pub fn exclude(&mut self, entity: Entity) {
+        self.checked_colliders.insert(entity);
+    }
+
+    /// Tests all the colliders of the cell against the ray and stores the hits.
+    /// Returns `true` if at least one collider of the cell was hit.
     pub fn check_ray_intersection(
         &mut self,
         query: &Query<&Collider>,
         cell: &HashSet<Entity>,
-        raycasts: &mut Vec<RaycastHit>,
     ) -> bool {
         let ray = self.ray.unwrap();
+        let mut any_hit = false;
 
         for potential in cell {
-            if self.checked_colliders.contains(potential) {
+            if !self.checked_colliders.insert(*potential) {
                 continue;
             }
 
             let potential_collider = query.get(*potential).unwrap();
 
-            if potential_collider.is_trigger {
+            if potential_collider.is_trigger && !self.include_triggers {
                 continue;
             }
 
@@ -464,48 +1202,737 @@ impl RaycastResultParser {
 
             if let Some(fraction) = collider_bounds.ray_intersects(&ray) {
                 if fraction <= 1.0 {
-                    if let Some(mut tmp_hit) =
-                        potential_collider.collides_with_line(ray.start, ray.end)
+                    if let Some(mut hit) = potential_collider.collides_with_line(ray.start, ray.end)
                     {
-                        /* if potential.contains_point(ray.start) {
-                            bevy::log::info!("contains point");
-                            continue;
-                        } */
-
-                        tmp_hit.collider = Some(*potential);
-                        self.cell_hits.push(tmp_hit);
-
-                        self.tmp_hit = Some(tmp_hit);
+                        hit.collider = Some(*potential);
+                        hit.centroid = potential_collider.center();
+                        self.hits.push(hit);
+                        any_hit = true;
                     }
                 }
             }
         }
 
-        if self.cell_hits.is_empty() {
-            return false;
-        }
+        any_hit
+    }
+
+    /// Gets the closest of the hits collected so far.
+    pub fn closest_hit(&self) -> Option<&RaycastHit> {
+        self.hits
+            .iter()
+            .min_by(|a, b| a.distance.total_cmp(&b.distance))
+    }
 
-        self.cell_hits.sort_by(|a, b| {
+    /// Sorts all the collected hits by distance, keeps at most `max_hits` of them
+    /// and resets the parser.
+    pub fn finish(&mut self, max_hits: usize) -> Vec<RaycastHit> {
+        let mut hits = std::mem::take(&mut self.hits);
+        hits.sort_by(|a, b| {
             a.distance
                 .partial_cmp(&b.distance)
                 .unwrap_or(std::cmp::Ordering::Equal)
         });
+        hits.truncate(max_hits);
+
+        self.reset();
+
+        hits
+    }
+
+    pub fn reset(&mut self) {
+        self.checked_colliders.clear();
+        self.hits.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    #[test]
+    fn linecast_includes_triggers_only_if_asked() {
+        let mut app = physics_app();
+        let mut trigger = circle(8.0);
+        trigger.is_trigger = true;
+        let trigger = spawn_collider(&mut app, trigger, Vec2::new(50.0, 0.0));
+        let wall = spawn_collider(&mut app, rect(16.0, 64.0), Vec2::new(100.0, 0.0));
+
+        let (start, end) = (Vec2::ZERO, Vec2::new(200.0, 0.0));
+        let (count, hits) = with_hash(&mut app, |hash, colliders| {
+            hash.linecast(colliders, start, end, None, ALL_LAYERS, false)
+        });
+        assert_eq!(count, 1);
+        assert_eq!(hits[0].collider, Some(wall));
 
-        for i in 0..self.cell_hits.len() {
-            //self.hits.as_mut().unwrap()[self.hit_counter as usize] = self.cell_hits[i];
-            raycasts.push(self.cell_hits[i]);
+        let (count, hits) = with_hash(&mut app, |hash, colliders| {
+            hash.linecast(colliders, start, end, None, ALL_LAYERS, true)
+        });
+        assert_eq!(count, 2);
+        assert_eq!(hits[0].collider, Some(trigger));
+        assert_eq!(hits[1].collider, Some(wall));
+    }
+
+    #[test]
+    fn linecast_collects_hits_across_cells_sorted() {
+        let mut app = physics_app();
+        let middle = spawn_collider(&mut app, circle(5.0), Vec2::new(150.0, 0.0));
+        let near = spawn_collider(&mut app, circle(5.0), Vec2::new(50.0, 0.0));
+        let far = spawn_collider(&mut app, circle(5.0), Vec2::new(250.0, 0.0));
+
+        let (count, hits) = with_hash(&mut app, |hash, colliders| {
+            hash.linecast(
+                colliders,
+                Vec2::ZERO,
+                Vec2::new(300.0, 0.0),
+                None,
+                ALL_LAYERS,
+                false,
+            )
+        });
+        assert_eq!(count, 3);
+        let order: Vec<_> = hits.iter().map(|hit| hit.collider).collect();
+        assert_eq!(order, vec![Some(near), Some(middle), Some(far)]);
+        assert!(hits.windows(2).all(|w| w[0].distance <= w[1].distance));
+    }
 
-            self.hit_counter += 1;
-            if self.hit_counter as usize == raycasts.len() {
-                return true;
+    #[test]
+    fn linecast_hits_collider_spanning_cells_once() {
+        let mut app = physics_app();
+        let wall = spawn_collider(&mut app, rect(200.0, 100.0), Vec2::new(150.0, 0.0));
+        with_hash(&mut app, |hash, _| {
+            let bounds = hash.registered_bounds(wall).unwrap();
+            assert!(bounds.width > hash.cell_size() as f32 * 2.0);
+        });
+
+        let (count, hits) = with_hash(&mut app, |hash, colliders| {
+            hash.linecast(
+                colliders,
+                Vec2::ZERO,
+                Vec2::new(300.0, 30.0),
+                None,
+                ALL_LAYERS,
+                false,
+            )
+        });
+        assert_eq!(count, 1);
+        assert_eq!(hits[0].collider, Some(wall));
+    }
+
+    #[test]
+    fn nearest_returns_true_nearest_not_first_found() {
+        let mut app = physics_app();
+        let point = Vec2::new(1.0, 1.0);
+        // shares the cell with the point but lies at its far corner
+        let same_cell = spawn_collider(&mut app, circle(1.0), Vec2::new(38.0, 38.0));
+        // lies in the neighboring cell, but much closer
+        let neighbor = spawn_collider(&mut app, circle(1.0), Vec2::new(-5.0, 1.0));
+
+        let nearest = with_hash(&mut app, |hash, colliders| {
+            hash.nearest(colliders, point, 100.0, None, None)
+        });
+        let (entity, distance) = nearest.unwrap();
+        assert_eq!(entity, neighbor);
+        assert!((distance - 5.0).abs() < 1e-3);
+
+        let nearest = with_hash(&mut app, |hash, colliders| {
+            hash.nearest(colliders, point, 100.0, Some(neighbor), None)
+        });
+        assert_eq!(nearest.map(|(entity, _)| entity), Some(same_cell));
+    }
+
+    #[test]
+    fn hitscan_through_trigger_zone_and_wall() {
+        let mut app = physics_app();
+        let shooter = spawn_collider(&mut app, circle(8.0), Vec2::ZERO);
+        let mut zone = rect(32.0, 64.0);
+        zone.is_trigger = true;
+        let zone = spawn_collider(&mut app, zone, Vec2::new(60.0, 0.0));
+        let wall = spawn_collider(&mut app, rect(16.0, 64.0), Vec2::new(120.0, 0.0));
+        spawn_collider(&mut app, circle(8.0), Vec2::new(180.0, 0.0));
+
+        let (start, end) = (Vec2::ZERO, Vec2::new(300.0, 0.0));
+        let hit = with_hash(&mut app, |hash, colliders| {
+            hash.hitscan(colliders, start, end, Some(shooter), ALL_LAYERS, false)
+        });
+        let (entity, point) = hit.unwrap();
+        assert_eq!(entity, wall);
+        assert!(point.abs_diff_eq(Vec2::new(112.0, 0.0), 1e-3));
+
+        let hit = with_hash(&mut app, |hash, colliders| {
+            hash.hitscan(colliders, start, end, Some(shooter), ALL_LAYERS, true)
+        });
+        let (entity, point) = hit.unwrap();
+        assert_eq!(entity, zone);
+        assert!(point.abs_diff_eq(Vec2::new(44.0, 0.0), 1e-3));
+    }
+
+    #[test]
+    fn emptied_cells_are_dropped() {
+        let mut app = physics_app();
+        let mover = spawn_collider(&mut app, circle(8.0), Vec2::new(20.0, 20.0));
+        let initial = with_hash(&mut app, |hash, _| hash.cell_count());
+
+        for _ in 0..100 {
+            move_by(&mut app, mover, Vec2::new(40.0, 0.0));
+            tick(&mut app);
+        }
+
+        assert!(position(&app, mover).x > 4000.0);
+        with_hash(&mut app, |hash, _| {
+            assert_eq!(hash.cell_count(), initial);
+            assert_eq!(hash.registered_count(), 1);
+        });
+    }
+
+    #[test]
+    fn keys_of_negative_cells_dont_alias() {
+        // sign extension of `y` used to overwrite the bits of `x`
+        assert_ne!(get_key(0, -1), get_key(-1, -1));
+        assert_ne!(get_key(i32::MAX, i32::MIN), get_key(i32::MIN, i32::MAX));
+        assert_ne!(get_key(1, 0), get_key(0, 1));
+
+        let mut app = physics_app();
+        let first = spawn_collider(&mut app, circle(4.0), Vec2::new(20.0, -20.0));
+        let second = spawn_collider(&mut app, circle(4.0), Vec2::new(-1_000_020.0, -20.0));
+        with_hash(&mut app, |hash, _| {
+            let nearby = hash.get_nearby_pos(Vec2::new(20.0, -20.0));
+            assert!(nearby.contains(&first));
+            assert!(!nearby.contains(&second));
+        });
+    }
+
+    #[test]
+    fn overlap_circle_sorted_returns_nearest_first_up_to_cap() {
+        let mut app = physics_app();
+        let third = spawn_collider(&mut app, circle(4.0), Vec2::new(0.0, -60.0));
+        let first = spawn_collider(&mut app, circle(4.0), Vec2::new(20.0, 0.0));
+        let fourth = spawn_collider(&mut app, circle(4.0), Vec2::new(-80.0, 0.0));
+        let second = spawn_collider(&mut app, circle(4.0), Vec2::new(30.0, 30.0));
+        spawn_collider(&mut app, circle(4.0), Vec2::new(200.0, 0.0));
+
+        let all = with_hash(&mut app, |hash, colliders| {
+            hash.overlap_circle_sorted(colliders, Vec2::ZERO, 100.0, None, None, usize::MAX)
+        });
+        let order: Vec<_> = all.iter().map(|(entity, _)| *entity).collect();
+        assert_eq!(order, vec![first, second, third, fourth]);
+        assert!((all[0].1 - 16.0).abs() < 1e-3);
+
+        let nearest = with_hash(&mut app, |hash, colliders| {
+            hash.overlap_circle_sorted(colliders, Vec2::ZERO, 100.0, None, None, 2)
+        });
+        assert_eq!(nearest, all[..2]);
+    }
+
+    #[test]
+    fn overlap_sector_checks_arc_edges() {
+        let mut app = physics_app();
+        let at_angle =
+            |degrees: f32, distance: f32| Vec2::from_angle(degrees.to_radians()) * distance;
+        let inside = spawn_collider(&mut app, circle(2.0), at_angle(44.0, 50.0));
+        let mirrored = spawn_collider(&mut app, circle(2.0), at_angle(-44.0, 50.0));
+        spawn_collider(&mut app, circle(2.0), at_angle(46.0, 50.0));
+        spawn_collider(&mut app, circle(2.0), at_angle(-46.0, 50.0));
+        let near_radius = spawn_collider(&mut app, circle(2.0), Vec2::new(99.0, 0.0));
+        spawn_collider(&mut app, circle(2.0), Vec2::new(101.0, 0.0));
+
+        let mut found = with_hash(&mut app, |hash, colliders| {
+            hash.overlap_sector(
+                colliders,
+                Vec2::ZERO,
+                Vec2::new(2.0, 0.0),
+                45f32.to_radians(),
+                100.0,
+                None,
+                None,
+                None,
+            )
+        });
+        found.sort();
+        let mut expected = vec![inside, mirrored, near_radius];
+        expected.sort();
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn overlap_sector_skips_candidates_out_of_sight() {
+        let mut app = physics_app();
+        let visible = spawn_collider(&mut app, circle(2.0), Vec2::new(50.0, -30.0));
+        let hidden = spawn_collider(&mut app, circle(2.0), Vec2::new(50.0, 30.0));
+        let mut wall = rect(4.0, 20.0);
+        wall.physics_layer = 2;
+        spawn_collider(&mut app, wall, Vec2::new(30.0, 18.0));
+
+        let sector = |hash: &SpatialHash, colliders: &Query<&Collider>, sight: Option<i32>| {
+            hash.overlap_sector(
+                colliders,
+                Vec2::ZERO,
+                Vec2::X,
+                60f32.to_radians(),
+                100.0,
+                None,
+                Some(1),
+                sight,
+            )
+        };
+        let found = with_hash(&mut app, |hash, colliders| sector(hash, colliders, None));
+        assert_eq!(found.len(), 2);
+        assert!(found.contains(&hidden));
+        let found = with_hash(&mut app, |hash, colliders| sector(hash, colliders, Some(2)));
+        assert_eq!(found, vec![visible]);
+    }
+
+    #[test]
+    fn raycast_hit_resolves_to_entity() {
+        let mut app = physics_app();
+        spawn_collider(&mut app, circle(8.0), Vec2::new(100.0, 0.0));
+        let wall = spawn_collider(&mut app, rect(16.0, 64.0), Vec2::new(50.0, 0.0));
+        app.world_mut().entity_mut(wall).insert(Name::new("wall"));
+
+        let hit = with_hash(&mut app, |hash, colliders| {
+            let hit = hash
+                .query_ray_first(
+                    colliders,
+                    Vec2::ZERO,
+                    Vec2::new(200.0, 0.0),
+                    None,
+                    ALL_LAYERS,
+                )
+                .unwrap();
+            let collider = colliders.get(hit.collider.unwrap()).unwrap();
+            assert_eq!(hit.centroid, collider.absolute_position());
+            hit
+        });
+
+        let name = app.world().get::<Name>(hit.collider.unwrap()).unwrap();
+        assert_eq!(name.as_str(), "wall");
+    }
+
+    #[test]
+    fn query_ray_first_matches_linecast_and_stops_early() {
+        let mut app = physics_app();
+        spawn_collider(&mut app, circle(8.0), Vec2::new(60.0, 5.0));
+        spawn_collider(&mut app, circle(8.0), Vec2::new(300.0, 0.0));
+        let mut trigger = circle(8.0);
+        trigger.is_trigger = true;
+        spawn_collider(&mut app, trigger, Vec2::new(20.0, 0.0));
+
+        let (start, end) = (Vec2::ZERO, Vec2::new(400.0, 0.0));
+        with_hash(&mut app, |hash, colliders| {
+            let (_, hits) = hash.linecast(colliders, start, end, None, ALL_LAYERS, false);
+            let first = hash.query_ray_first(colliders, start, end, None, ALL_LAYERS);
+            assert_eq!(first.map(|hit| hit.collider), Some(hits[0].collider));
+            assert_eq!(first.map(|hit| hit.point), Some(hits[0].point));
+        });
+
+        // An entity without a `Collider` panics once its cell is visited,
+        // so the cells past the closest hit must be skipped
+        let stale = app.world_mut().spawn_empty().id();
+        with_hash_mut(&mut app, |hash, _| hash.cell_map.insert_entity(5, 0, stale));
+        let first = with_hash(&mut app, |hash, colliders| {
+            hash.query_ray_first(colliders, start, end, None, ALL_LAYERS)
+        });
+        assert!(first.is_some());
+    }
+
+    #[test]
+    fn overlap_rectangle_tests_actual_shapes() {
+        let mut app = physics_app();
+        let area = FRect::new(0.0, 0.0, 10.0, 10.0);
+        // in the same cell as the area, but apart from it
+        spawn_collider(&mut app, rect(10.0, 10.0), Vec2::new(30.0, 30.0));
+        let overlapping = spawn_collider(&mut app, rect(10.0, 10.0), Vec2::new(12.0, 5.0));
+        // its bounds overlap the area's corner, but the circle itself doesn't
+        spawn_collider(&mut app, circle(5.0), Vec2::new(14.0, 14.0));
+        let overlapping_circle = spawn_collider(&mut app, circle(5.0), Vec2::new(5.0, 14.0));
+
+        let mut results = vec![];
+        let count = with_hash(&mut app, |hash, colliders| {
+            hash.overlap_rectangle(colliders, area, None, Some(&mut results), None)
+        });
+        results.sort();
+        let mut expected = vec![overlapping, overlapping_circle];
+        expected.sort();
+        assert_eq!(count, 2);
+        assert_eq!(results, expected);
+    }
+
+    #[test]
+    fn registering_twice_keeps_single_membership() {
+        let mut hash = SpatialHash::new(32);
+        let entity = Entity::from_raw(1);
+        let mut collider = circle(8.0);
+        collider.set_position(Vec2::new(32.0, 32.0));
+
+        hash.register(&collider, entity);
+        let cells = hash.cell_count();
+        hash.register(&collider, entity);
+
+        assert_eq!(hash.registered_count(), 1);
+        assert_eq!(hash.cell_count(), cells);
+        assert_eq!(hash.max_cell_occupancy(), 1);
+        let memberships = hash
+            .cell_map
+            .store
+            .values()
+            .filter(|cell| cell.contains(&entity))
+            .count();
+        assert_eq!(memberships, cells);
+
+        hash.remove_entity(entity);
+        assert_eq!(hash.cell_count(), 0);
+        assert!(hash.get_all().is_empty());
+    }
+
+    #[test]
+    fn candidate_pairs_are_unique() {
+        let mut app = physics_app();
+        // overlapping each other and spanning the same four cells
+        let entities = [
+            spawn_collider(&mut app, circle(8.0), Vec2::new(-4.0, 0.0)),
+            spawn_collider(&mut app, circle(8.0), Vec2::new(0.0, 4.0)),
+            spawn_collider(&mut app, circle(8.0), Vec2::new(4.0, 0.0)),
+        ];
+
+        let pairs = with_hash(&mut app, |hash, _| {
+            hash.candidate_pairs().collect::<Vec<_>>()
+        });
+
+        assert_eq!(pairs.len(), 3);
+        let unique = pairs.iter().copied().collect::<HashSet<_>>();
+        assert_eq!(unique.len(), 3);
+        for (i, a) in entities.iter().enumerate() {
+            for b in &entities[i + 1..] {
+                assert!(unique.contains(&(*a.min(b), *a.max(b))));
             }
         }
+    }
 
-        false
+    #[test]
+    fn query_filter_fields() {
+        let mut app = physics_app();
+        let mut on_layer_2 = circle(8.0);
+        on_layer_2.physics_layer = 0b10;
+        let on_layer_2 = spawn_collider(&mut app, on_layer_2, Vec2::new(20.0, 0.0));
+        let mut trigger = circle(8.0);
+        trigger.is_trigger = true;
+        let trigger = spawn_collider(&mut app, trigger, Vec2::new(40.0, 0.0));
+        let solid = spawn_collider(&mut app, circle(8.0), Vec2::new(60.0, 0.0));
+
+        let overlap = |app: &mut App, filter: QueryFilter| {
+            let mut found = with_hash(app, |hash, colliders| {
+                hash.overlap_circle_filtered(colliders, Vec2::new(40.0, 0.0), 40.0, &filter)
+            });
+            found.sort();
+            found
+        };
+        let hit_entities = |app: &mut App, filter: QueryFilter| {
+            with_hash(app, |hash, colliders| {
+                hash.linecast_filtered(
+                    colliders,
+                    Vec2::ZERO,
+                    Vec2::new(100.0, 0.0),
+                    &filter,
+                    usize::MAX,
+                )
+            })
+            .iter()
+            .map(|hit| hit.collider.unwrap())
+            .collect::<Vec<_>>()
+        };
+
+        let mut all = vec![on_layer_2, trigger, solid];
+        all.sort();
+        assert_eq!(overlap(&mut app, QueryFilter::new()), all);
+        assert_eq!(
+            hit_entities(&mut app, QueryFilter::new()),
+            vec![on_layer_2, trigger, solid]
+        );
+
+        let layer_mask = QueryFilter::new().with_layer_mask(0b10);
+        assert_eq!(overlap(&mut app, layer_mask.clone()), vec![on_layer_2]);
+        assert_eq!(hit_entities(&mut app, layer_mask), vec![on_layer_2]);
+
+        let exclude = QueryFilter::new().exclude(on_layer_2).exclude(solid);
+        assert_eq!(overlap(&mut app, exclude.clone()), vec![trigger]);
+        assert_eq!(hit_entities(&mut app, exclude), vec![trigger]);
+
+        let no_triggers = QueryFilter::new().with_triggers(false);
+        let mut solids = vec![on_layer_2, solid];
+        solids.sort();
+        assert_eq!(overlap(&mut app, no_triggers.clone()), solids);
+        assert_eq!(hit_entities(&mut app, no_triggers), vec![on_layer_2, solid]);
     }
 
-    pub fn reset(&mut self) {
-        self.checked_colliders.clear();
-        self.cell_hits.clear();
+    #[test]
+    fn overlap_circle_detects_colliders_just_inside_radius() {
+        let mut app = physics_app();
+        let center = Vec2::new(100.0, -50.0);
+        let circle_inside = spawn_collider(&mut app, circle(8.0), center + Vec2::new(47.5, 0.0));
+        let box_inside = spawn_collider(&mut app, rect(16.0, 16.0), center + Vec2::new(0.0, -47.5));
+        spawn_collider(&mut app, circle(8.0), center + Vec2::new(-48.5, 0.0));
+        spawn_collider(&mut app, rect(16.0, 16.0), center + Vec2::new(0.0, 48.5));
+
+        let mut results = vec![];
+        let count = with_hash(&mut app, |hash, colliders| {
+            hash.overlap_circle(colliders, center, 40.0, None, Some(&mut results), None)
+        });
+
+        assert_eq!(count, 2);
+        results.sort();
+        let mut expected = vec![circle_inside, box_inside];
+        expected.sort();
+        assert_eq!(results, expected);
+    }
+
+    #[test]
+    fn hits_identify_colliders_by_stable_entities() {
+        let mut app = physics_app();
+        let first = spawn_collider(&mut app, circle(8.0), Vec2::new(50.0, 0.0));
+        let second = spawn_collider(&mut app, circle(8.0), Vec2::new(100.0, 0.0));
+        assert_ne!(first, second);
+
+        let hit_entities = |app: &mut App| {
+            with_hash(app, |hash, colliders| {
+                hash.linecast_filtered(
+                    colliders,
+                    Vec2::ZERO,
+                    Vec2::new(200.0, 0.0),
+                    &QueryFilter::new(),
+                    usize::MAX,
+                )
+            })
+            .iter()
+            .map(|hit| hit.collider.unwrap())
+            .collect::<Vec<_>>()
+        };
+        assert_eq!(hit_entities(&mut app), vec![first, second]);
+
+        // moving the collider keeps its identity
+        move_by(&mut app, first, Vec2::new(100.0, 0.0));
+        tick(&mut app);
+        assert_eq!(hit_entities(&mut app), vec![second, first]);
+    }
+
+    #[test]
+    fn cast_from_inside_excluded_collider_hits_next_one() {
+        let mut app = physics_app();
+        let caster = spawn_collider(&mut app, circle(8.0), Vec2::ZERO);
+        let wall = spawn_collider(&mut app, rect(16.0, 64.0), Vec2::new(100.0, 0.0));
+
+        let (start, end) = (Vec2::ZERO, Vec2::new(200.0, 0.0));
+        let (_, hits) = with_hash(&mut app, |hash, colliders| {
+            hash.linecast(colliders, start, end, None, ALL_LAYERS, false)
+        });
+        assert_eq!(hits[0].collider, Some(caster));
+
+        let (count, hits) = with_hash(&mut app, |hash, colliders| {
+            hash.linecast(colliders, start, end, Some(caster), ALL_LAYERS, false)
+        });
+        assert_eq!(count, 1);
+        assert_eq!(hits[0].collider, Some(wall));
+
+        let hit = with_hash(&mut app, |hash, colliders| {
+            hash.query_ray_first(colliders, start, end, Some(caster), ALL_LAYERS)
+        });
+        assert_eq!(hit.and_then(|hit| hit.collider), Some(wall));
+        assert_eq!(
+            with_hash(&mut app, |hash, colliders| {
+                hash.hitscan(colliders, start, end, Some(caster), ALL_LAYERS, false)
+            })
+            .map(|(entity, _)| entity),
+            Some(wall)
+        );
+    }
+
+    #[test]
+    fn raycast_hit_carries_collider_centroid() {
+        let mut app = physics_app();
+        let mut wall = rect(16.0, 64.0);
+        wall.local_offset = Vec2::new(0.0, 10.0);
+        spawn_collider(&mut app, wall, Vec2::new(100.0, 0.0));
+        spawn_collider(&mut app, circle(8.0), Vec2::new(150.0, 0.0));
+
+        let (_, hits) = with_hash(&mut app, |hash, colliders| {
+            hash.linecast(
+                colliders,
+                Vec2::ZERO,
+                Vec2::new(200.0, 0.0),
+                None,
+                ALL_LAYERS,
+                false,
+            )
+        });
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].centroid, Vec2::new(100.0, 10.0));
+        assert_eq!(hits[1].centroid, Vec2::new(150.0, 0.0));
+    }
+
+    #[test]
+    fn rehash_keeps_query_results() {
+        let mut app = physics_app();
+        for i in 0..20 {
+            let position = Vec2::new(i as f32 * 37.0 - 300.0, (i % 5) as f32 * 53.0 - 100.0);
+            spawn_collider(&mut app, circle(10.0), position);
+        }
+        spawn_collider(&mut app, rect(400.0, 20.0), Vec2::new(0.0, 150.0));
+
+        let queries = |hash: &SpatialHash, colliders: &Query<&Collider>| {
+            let mut results: Vec<Vec<Entity>> = [
+                FRect::new(-320.0, -120.0, 100.0, 100.0),
+                FRect::new(-50.0, 0.0, 200.0, 160.0),
+                FRect::new(150.0, 140.0, 10.0, 10.0),
+            ]
+            .into_iter()
+            .map(|bounds| {
+                let mut found = vec![];
+                hash.overlap_rectangle(colliders, bounds, None, Some(&mut found), None);
+                found
+            })
+            .collect();
+            let mut in_circle = vec![];
+            hash.overlap_circle(
+                colliders,
+                Vec2::ZERO,
+                120.0,
+                None,
+                Some(&mut in_circle),
+                None,
+            );
+            results.push(in_circle);
+            for result in &mut results {
+                result.sort_unstable();
+            }
+            results
+        };
+
+        let before = with_hash(&mut app, queries);
+        assert!(before.iter().all(|result| !result.is_empty()));
+
+        for cell_size in [8, 256] {
+            with_hash_mut(&mut app, |hash, colliders| {
+                hash.rehash(cell_size, colliders)
+            });
+            let after = with_hash(&mut app, |hash, colliders| {
+                assert_eq!(hash.cell_size(), cell_size);
+                assert_eq!(hash.inverse_cell_size(), 1.0 / cell_size as f32);
+                assert_eq!(hash.registered_count(), 21);
+                queries(hash, colliders)
+            });
+            assert_eq!(after, before, "cell size {cell_size}");
+        }
+    }
+
+    #[test]
+    fn query_region_returns_colliders_inside_viewport() {
+        let mut app = physics_app();
+        let inside = spawn_collider(&mut app, circle(10.0), Vec2::new(100.0, 100.0));
+        let on_edge = spawn_collider(&mut app, circle(10.0), Vec2::new(805.0, 300.0));
+        let spanning = spawn_collider(&mut app, rect(2000.0, 20.0), Vec2::new(400.0, 300.0));
+        spawn_collider(&mut app, circle(10.0), Vec2::new(900.0, 300.0));
+        spawn_collider(&mut app, circle(10.0), Vec2::new(-50.0, -50.0));
+        spawn_collider(&mut app, rect(2000.0, 20.0), Vec2::new(400.0, -20.0));
+        let mut decor = circle(10.0);
+        decor.physics_layer = 0b10;
+        let decor = spawn_collider(&mut app, decor, Vec2::new(400.0, 500.0));
+
+        let viewport = FRect::new(0.0, 0.0, 800.0, 600.0);
+        let mut expected = vec![inside, on_edge, spanning, decor];
+        expected.sort_unstable();
+        let visible = with_hash(&mut app, |hash, colliders| {
+            hash.query_region(colliders, viewport, None)
+        });
+        assert_eq!(visible, expected);
+
+        expected.retain(|entity| *entity != decor);
+        let visible = with_hash(&mut app, |hash, colliders| {
+            hash.query_region(colliders, viewport, Some(0b01))
+        });
+        assert_eq!(visible, expected);
+    }
+
+    #[test]
+    fn child_collider_is_registered_at_world_position() {
+        let mut app = physics_app();
+        let mut child = Entity::PLACEHOLDER;
+        let parent = app
+            .world_mut()
+            .spawn(SpatialBundle::from_transform(Transform::from_xyz(
+                400.0, 200.0, 0.0,
+            )))
+            .with_children(|parent| {
+                child = parent
+                    .spawn((
+                        SpatialBundle::from_transform(Transform::from_xyz(10.0, 0.0, 0.0)),
+                        circle(4.0),
+                    ))
+                    .id();
+            })
+            .id();
+
+        let world_position = Vec2::new(410.0, 200.0);
+        with_hash(&mut app, |hash, _| {
+            assert_eq!(
+                hash.registered_bounds(child).unwrap().center(),
+                world_position
+            );
+            assert!(hash.get_nearby_pos(world_position).contains(&child));
+            assert!(!hash.get_nearby_pos(Vec2::new(10.0, 0.0)).contains(&child));
+        });
+
+        app.world_mut()
+            .get_mut::<Transform>(parent)
+            .unwrap()
+            .translation
+            .x = -400.0;
+        tick(&mut app);
+        with_hash(&mut app, |hash, _| {
+            let bounds = hash.registered_bounds(child).unwrap();
+            assert_eq!(bounds.center(), Vec2::new(-390.0, 200.0));
+        });
+    }
+
+    #[test]
+    fn recursively_despawned_colliders_are_removed_from_hash() {
+        let mut app = physics_app();
+        let parent = spawn_collider(&mut app, circle(8.0), Vec2::new(50.0, 50.0));
+        app.world_mut().entity_mut(parent).with_children(|parent| {
+            parent.spawn((
+                SpatialBundle::from_transform(Transform::from_xyz(100.0, 0.0, 0.0)),
+                rect(16.0, 16.0),
+            ));
+        });
+        let other = spawn_collider(&mut app, circle(8.0), Vec2::new(-60.0, 20.0));
+        assert_eq!(with_hash(&mut app, |hash, _| hash.registered_count()), 3);
+
+        app.world_mut().entity_mut(parent).despawn_recursive();
+        tick(&mut app);
+
+        with_hash(&mut app, |hash, _| {
+            assert_eq!(hash.registered_count(), 1);
+            assert_eq!(hash.get_all(), HashSet::from([other]));
+            assert_eq!(hash.cell_count(), 1);
+        });
+    }
+
+    #[test]
+    fn none_collider_is_not_registered() {
+        let mut app = physics_app();
+        let empty = spawn_collider(&mut app, Collider::default(), Vec2::new(20.0, 0.0));
+        let solid = spawn_collider(&mut app, circle(8.0), Vec2::ZERO);
+
+        with_hash(&mut app, |hash, colliders| {
+            assert_eq!(hash.get_all(), HashSet::from([solid]));
+            assert!(hash.registered_bounds(empty).is_none());
+            assert!(hash
+                .overlap_circle_sorted(colliders, Vec2::new(20.0, 0.0), 50.0, None, None, 10)
+                .iter()
+                .all(|(entity, _)| *entity != empty));
+        });
+
+        move_by(&mut app, empty, Vec2::new(-20.0, 0.0));
+        tick(&mut app);
+        assert_eq!(position(&app, empty), Vec2::ZERO);
+        with_hash(&mut app, |hash, _| assert_eq!(hash.registered_count(), 1));
     }
 }
@@ -1,4 +1,5 @@
 use bevy::prelude::*;
+use common::FRect;
 use num_enum::FromPrimitive;
 
 
@@ -57,7 +58,7 @@ pub fn circle_to_circle(
     circle_radius_2: f32,
 ) -> bool {
     Vec2::distance_squared(circle_center_1, circle_center_2)
-        < (circle_radius_1 + circle_radius_2) * (circle_radius_1 * circle_radius_2)
+        < (circle_radius_1 + circle_radius_2) * (circle_radius_1 + circle_radius_2)
 }
 
 pub fn circle_to_line(circle_center: Vec2, radius: f32, line_from: Vec2, line_to: Vec2) -> bool {
@@ -83,37 +84,29 @@ pub fn rect_to_circle(
         return true;
     }
 
-    let mut edge_from;
-    let mut edge_to;
+    let [top_left, top_right, bottom_right, bottom_left] =
+        FRect::new(rect_x, rect_y, rect_w, rect_h).corners();
     let sector = get_sector(rect_x, rect_y, rect_w, rect_h, circle_center) as u8;
 
-    if (sector & PointSectors::Top as u8) != 0 {
-        edge_from = Vec2::new(rect_x, rect_y);
-        edge_to = Vec2::new(rect_x + rect_w, rect_y);
-        if circle_to_line(circle_center, radius, edge_from, edge_to) {
-            return true;
-        }
+    if (sector & PointSectors::Top as u8) != 0
+        && circle_to_line(circle_center, radius, top_left, top_right)
+    {
+        return true;
     }
-    if (sector & PointSectors::Bottom as u8) != 0 {
-        edge_from = Vec2::new(rect_x, rect_y + rect_h);
-        edge_to = Vec2::new(rect_x + rect_w, rect_y + rect_h);
-        if circle_to_line(circle_center, radius, edge_from, edge_to) {
-            return true;
-        }
+    if (sector & PointSectors::Bottom as u8) != 0
+        && circle_to_line(circle_center, radius, bottom_left, bottom_right)
+    {
+        return true;
     }
-    if (sector & PointSectors::Left as u8) != 0 {
-        edge_from = Vec2::new(rect_x, rect_y);
-        edge_to = Vec2::new(rect_x, rect_y + rect_h);
-        if circle_to_line(circle_center, radius, edge_from, edge_to) {
-            return true;
-        }
+    if (sector & PointSectors::Left as u8) != 0
+        && circle_to_line(circle_center, radius, top_left, bottom_left)
+    {
+        return true;
     }
-    if (sector & PointSectors::Right as u8) != 0 {
-        edge_from = Vec2::new(rect_x + rect_w, rect_y);
-        edge_to = Vec2::new(rect_x + rect_w, rect_y + rect_h);
-        if circle_to_line(circle_center, radius, edge_from, edge_to) {
-            return true;
-        }
+    if (sector & PointSectors::Right as u8) != 0
+        && circle_to_line(circle_center, radius, top_right, bottom_right)
+    {
+        return true;
     }
 
     false
@@ -0,0 +1,104 @@
+use bevy::{
+    diagnostic::{Diagnostic, DiagnosticPath, Diagnostics, RegisterDiagnostic},
+    prelude::*,
+};
+
+use crate::collisions::spatial_hash::SpatialHash;
+
+/// Counters of the physics work done during the last `FixedUpdate` tick.
+/// Inserted and updated by `PhysicsPlugin`, the values are also reported
+/// to Bevy's `DiagnosticsStore` under the `physics/*` paths.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq, Reflect)]
+pub struct CollisionStats {
+    /// Number of colliders registered in the `SpatialHash`.
+    pub colliders_registered: usize,
+    /// Number of colliders returned by the broadphase of the moving colliders.
+    pub broadphase_candidates: usize,
+    /// Number of narrowphase collision tests performed.
+    pub collision_tests: usize,
+    /// Number of raycasts performed through the `SpatialHash`.
+    pub raycasts: usize,
+    /// Largest number of colliders stored in a single cell of the `SpatialHash`.
+    pub max_cell_occupancy: usize,
+}
+
+impl CollisionStats {
+    pub const COLLIDERS_REGISTERED: DiagnosticPath =
+        DiagnosticPath::const_new("physics/colliders_registered");
+    pub const BROADPHASE_CANDIDATES: DiagnosticPath =
+        DiagnosticPath::const_new("physics/broadphase_candidates");
+    pub const COLLISION_TESTS: DiagnosticPath =
+        DiagnosticPath::const_new("physics/collision_tests");
+    pub const RAYCASTS: DiagnosticPath = DiagnosticPath::const_new("physics/raycasts");
+    pub const MAX_CELL_OCCUPANCY: DiagnosticPath =
+        DiagnosticPath::const_new("physics/max_cell_occupancy");
+
+    /// Resets the per-tick counters.
+    pub(crate) fn reset_tick(&mut self) {
+        self.broadphase_candidates = 0;
+        self.collision_tests = 0;
+    }
+}
+
+pub(crate) fn register_diagnostics(app: &mut App) {
+    app.init_resource::<CollisionStats>()
+        .register_diagnostic(Diagnostic::new(CollisionStats::COLLIDERS_REGISTERED))
+        .register_diagnostic(Diagnostic::new(CollisionStats::BROADPHASE_CANDIDATES))
+        .register_diagnostic(Diagnostic::new(CollisionStats::COLLISION_TESTS))
+        .register_diagnostic(Diagnostic::new(CollisionStats::RAYCASTS))
+        .register_diagnostic(Diagnostic::new(CollisionStats::MAX_CELL_OCCUPANCY));
+}
+
+/// Collects the counters of the `SpatialHash` and reports all the stats to the `DiagnosticsStore`.
+pub(crate) fn update_collision_stats(
+    mut stats: ResMut<CollisionStats>,
+    spatial_hash: Res<SpatialHash>,
+    mut diagnostics: Diagnostics,
+) {
+    stats.colliders_registered = spatial_hash.registered_count();
+    stats.max_cell_occupancy = spatial_hash.max_cell_occupancy();
+    stats.raycasts = spatial_hash.take_raycast_count();
+
+    let stats = *stats;
+    diagnostics.add_measurement(&CollisionStats::COLLIDERS_REGISTERED, || {
+        stats.colliders_registered as f64
+    });
+    diagnostics.add_measurement(&CollisionStats::BROADPHASE_CANDIDATES, || {
+        stats.broadphase_candidates as f64
+    });
+    diagnostics.add_measurement(&CollisionStats::COLLISION_TESTS, || {
+        stats.collision_tests as f64
+    });
+    diagnostics.add_measurement(&CollisionStats::RAYCASTS, || stats.raycasts as f64);
+    diagnostics.add_measurement(&CollisionStats::MAX_CELL_OCCUPANCY, || {
+        stats.max_cell_occupancy as f64
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    #[test]
+    fn candidates_are_counted_for_near_colliders() {
+        let mut app = physics_app();
+        let mover = spawn_collider(&mut app, circle(8.0), Vec2::ZERO);
+        spawn_collider(&mut app, circle(8.0), Vec2::new(500.0, 0.0));
+
+        move_by(&mut app, mover, Vec2::new(2.0, 0.0));
+        tick(&mut app);
+        let stats = *app.world().resource::<CollisionStats>();
+        assert_eq!(stats.colliders_registered, 2);
+        assert_eq!(stats.broadphase_candidates, 0);
+        assert_eq!(stats.collision_tests, 0);
+
+        spawn_collider(&mut app, circle(8.0), Vec2::new(20.0, 0.0));
+        move_by(&mut app, mover, Vec2::new(2.0, 0.0));
+        tick(&mut app);
+        let stats = *app.world().resource::<CollisionStats>();
+        assert_eq!(stats.colliders_registered, 3);
+        assert_eq!(stats.broadphase_candidates, 1);
+        assert!(stats.collision_tests >= 1);
+    }
+}
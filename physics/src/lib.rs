@@ -19,17 +19,47 @@
 //!             // This collider should collide only with layers 1 and 2 (equivalent to 0b11)
 //!             collides_with_layers: 1 | 2,
 //!             // This collider shouldn't be a trigger
-//!             is_trigger: false
+//!             is_trigger: false,
+//!             ..default()
 //!         }
 //!     ))
 //! }
 //! ```
 //!
 //! You also can make the collider move by adding `SteerSeek` (or other behavior)
-//! as well as `SteeringTargetVec2`, with an alternative to `SteeringTargetEntity(my_target_entity)`.
+//! as well as `SteeringTargetVec2`, with an alternative to `SteeringTargetEntity::new(my_target_entity)`.
 //!
 //! You can mutate steering targets freely at any time.
 //!
+//! Movement without any steering goes through the same collision resolution
+//! by sending a `MovementCalculateEvent`:
+//! ```
+//! # use bevy::prelude::*;
+//! # use physics::MovementCalculateEvent;
+//! # #[derive(Component)]
+//! # struct KeyboardMover;
+//! fn keyboard_mover(
+//!     keyboard_input: Res<ButtonInput<KeyCode>>,
+//!     movers: Query<Entity, With<KeyboardMover>>,
+//!     mut evt_movement: EventWriter<MovementCalculateEvent>,
+//! ) {
+//!     let mut dir = Vec2::ZERO;
+//!     if keyboard_input.pressed(KeyCode::KeyA) {
+//!         dir.x -= 1.0;
+//!     }
+//!     if keyboard_input.pressed(KeyCode::KeyD) {
+//!         dir.x += 1.0;
+//!     }
+//!
+//!     for entity in &movers {
+//!         evt_movement.send(MovementCalculateEvent {
+//!             entity,
+//!             movement: dir * 2.0,
+//!         });
+//!     }
+//! }
+//! ```
+//!
 //! You can also observe collisions by calling `observe(on_collision)` after spawning an entity:
 //! ```
 //! fn on_collision(trigger: Trigger<CollideEvent>) {
@@ -41,23 +71,48 @@
 use bevy::prelude::*;
 
 pub mod collisions;
+pub mod diagnostics;
 pub mod movement;
 pub mod plugin;
 pub mod prelude;
+#[cfg(test)]
+mod test_utils;
 
+/// A request to move an entity by `movement` with collision resolution applied.
+///
+/// The event is sent by the built-in steering every `FixedUpdate`, but it can also be sent
+/// by any user system through `EventWriter<MovementCalculateEvent>` in order to drive movement
+/// without steering behaviors (e.g. a grid-snapped mover or a networked authority).
+/// The entity is required to have a `Transform`, `SteeringHost` is not required.
+///
+/// For every `MovementCalculateEvent` exactly one `PositionUpdateEvent` is sent during the same
+/// `FixedUpdate` with the movement corrected by the collision resolution. If the entity has no
+/// `Collider` or its collider is a trigger, the movement is passed through unchanged.
+/// `CollideEvent` and `InvokeTriggerEvent` are sent the same way as for the steering entities.
+///
+/// Events sent before the `calc_movement` system (i.e. before `PhysicsSet::Resolve`)
+/// are processed in the same tick, the later ones are processed in the next tick.
 #[derive(Debug, Event)]
-pub(crate) struct MovementCalculateEvent {
-    entity: Entity,
-    movement: Vec2,
+pub struct MovementCalculateEvent {
+    /// The `Entity` which is going to move.
+    pub entity: Entity,
+    /// Desired delta movement, not multiplied by delta time.
+    pub movement: Vec2,
 }
 
+/// A resolved movement of an entity. The movement is applied to the entity's `Transform`
+/// and the `SpatialHash` is updated by the `update_position` system.
+/// Can be read through `EventReader<PositionUpdateEvent>` to observe the final movement.
 #[derive(Debug, Event)]
-pub(crate) struct PositionUpdateEvent {
-    entity: Entity,
-    movement: Vec2,
+pub struct PositionUpdateEvent {
+    /// The `Entity` which moves.
+    pub entity: Entity,
+    /// Delta movement with collisions resolved.
+    pub movement: Vec2,
 }
 
 /// An event that triggers when a collider collides with a trigger.
+/// A moving trigger sends it as `entity_main` when it enters another trigger.
 /// This event can be accessed through `EventReader<InvokeTriggerEvent>`.
 #[derive(Debug, Event)]
 pub struct InvokeTriggerEvent {
@@ -75,6 +130,10 @@ pub struct InvokeTriggerEvent {
 /// ```
 /// commands.spawn(SpatialBundle::default()).observe(my_observe_system);
 /// ```
+///
+/// The targeted event is sent to the moving entity. If it hits a `RigidBodyStatic` collider,
+/// the static entity is targeted as well, with `entity_main` and `collided_with` swapped,
+/// so the static geometry (e.g. breakable walls) can react to the hits.
 #[derive(Debug, Event)]
 pub struct CollideEvent {
     /// The main `Entity` which moved and thus collided another `Entity`.
@@ -82,3 +141,99 @@ pub struct CollideEvent {
     /// The `Entity` main collider collided with.
     pub collided_with: Entity,
 }
+
+/// An event that triggers right before a collision of a moving collider with another
+/// non-trigger collider gets resolved. Observed the same way as `CollideEvent`.
+///
+/// Observers run after the resolution has already happened, so use `ResolutionVeto`
+/// in order to skip the resolution.
+#[derive(Debug, Clone, Copy, Event)]
+pub struct PreResolveCollision {
+    /// The `Entity` which moved and thus collided another `Entity`.
+    pub entity_main: Entity,
+    /// The `Entity` main collider collided with.
+    pub collided_with: Entity,
+    /// A normal vector of the collision.
+    pub normal: Vec2,
+    /// The translation which is going to be subtracted from the main entity's motion.
+    pub min_translation: Vec2,
+}
+
+/// A stable identity of a contact between two non-trigger colliders. The entities are stored
+/// in a fixed order, so the id is the same no matter which of them moved into the other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Reflect)]
+pub struct ContactId {
+    pub a: Entity,
+    pub b: Entity,
+}
+
+impl ContactId {
+    pub fn new(first: Entity, second: Entity) -> Self {
+        if first <= second {
+            Self {
+                a: first,
+                b: second,
+            }
+        } else {
+            Self {
+                a: second,
+                b: first,
+            }
+        }
+    }
+
+    /// Checks whether the entity is one of the two entities of the contact.
+    pub fn involves(&self, entity: Entity) -> bool {
+        self.a == entity || self.b == entity
+    }
+
+    /// Gets the other entity of the contact or `None` if the entity isn't a part of it.
+    pub fn other(&self, entity: Entity) -> Option<Entity> {
+        if self.a == entity {
+            Some(self.b)
+        } else if self.b == entity {
+            Some(self.a)
+        } else {
+            None
+        }
+    }
+}
+
+/// An event that is sent when two non-trigger colliders come into contact, i.e. on the first
+/// tick a `CollideEvent` is sent for the pair. While the colliders keep colliding or touching
+/// (being within `PhysicsConfig::skin_width` of each other) every tick, `ContactPersisted`
+/// is sent instead, and `ContactEnded` is sent on the first tick they don't.
+/// These events can be accessed through `EventReader<ContactBegan>` etc.
+#[derive(Debug, Clone, Copy, Event)]
+pub struct ContactBegan {
+    pub id: ContactId,
+    /// A normal vector of the collision, pointing from `id.b` towards `id.a`.
+    pub normal: Vec2,
+    pub point: Vec2,
+}
+
+/// An event that is sent every tick a contact lasts after `ContactBegan`.
+#[derive(Debug, Clone, Copy, Event)]
+pub struct ContactPersisted {
+    pub id: ContactId,
+    /// A normal vector of the collision, pointing from `id.b` towards `id.a`.
+    pub normal: Vec2,
+    pub point: Vec2,
+}
+
+/// An event that is sent on the first tick the colliders of a contact don't collide
+/// anymore, including the ticks they're despawned or stop moving on.
+#[derive(Debug, Clone, Copy, Event)]
+pub struct ContactEnded {
+    pub id: ContactId,
+}
+
+/// An event that is sent once a host with `ArrivalDetection` comes within
+/// `ArrivalDetection::arrival_epsilon` of its steering target. Sent once per arrival:
+/// the host has to leave the radius before the event can be sent again.
+/// This event can be accessed through `EventReader<TargetReachedEvent>`.
+#[derive(Debug, Clone, Copy, Event)]
+pub struct TargetReachedEvent {
+    /// The `Entity` which reached its target.
+    pub entity: Entity,
+}
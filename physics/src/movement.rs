@@ -1,3 +1,6 @@
+pub mod animation;
 pub mod behaviors;
+pub mod grid;
+pub mod interpolation;
 pub mod paths;
 pub mod steering;
@@ -0,0 +1,183 @@
+use bevy::prelude::*;
+
+use super::steering::SteeringHost;
+
+/// The direction a sprite faces in its texture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+pub enum SpriteFacing {
+    Left,
+    Right,
+}
+
+/// Atlas indices of the frames picked by the direction of the velocity.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Reflect)]
+pub struct DirectionalFrames {
+    /// The frame used while the host is slower than `VelocityAnimation::min_speed`.
+    pub idle: usize,
+    pub up: usize,
+    pub down: usize,
+    /// The frame used while moving to the left or to the right.
+    /// The direction is shown via flipping, see `VelocityAnimation::flip`.
+    pub side: usize,
+}
+
+/// Animates the sprite of a `SteeringHost` by its velocity: flips the `Sprite`
+/// and/or selects the `TextureAtlas` index. Both are optional, the entity is only
+/// required to have the components the enabled parts write to.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use physics::prelude::{animation::*, steering::SteeringBundle};
+/// # fn spawn_player(mut commands: Commands) {
+/// commands.spawn((
+///     SpriteBundle::default(),
+///     TextureAtlas::default(),
+///     SteeringBundle::default(),
+///     VelocityAnimation::default()
+///         .with_flip(SpriteFacing::Left)
+///         .with_frames(DirectionalFrames { idle: 0, up: 1, down: 0, side: 2 }),
+/// ));
+/// # }
+/// ```
+#[derive(Component, Debug, Clone, Copy, PartialEq, Reflect)]
+pub struct VelocityAnimation {
+    /// Speed below which the host is considered idle. The sprite keeps its flip while idle.
+    /// Defaults to `1.0`.
+    pub min_speed: f32,
+    /// The direction the sprite faces in the texture, `Sprite::flip_x` is set while
+    /// the host moves to the opposite side. `None` disables the flipping.
+    /// Defaults to `None`.
+    pub flip: Option<SpriteFacing>,
+    /// The frames selected by the direction of the velocity. The vertical frames are used
+    /// if the vertical speed is not lower than the horizontal one.
+    /// `None` leaves the atlas index as is. Defaults to `None`.
+    pub frames: Option<DirectionalFrames>,
+}
+
+impl Default for VelocityAnimation {
+    fn default() -> Self {
+        Self {
+            min_speed: 1.0,
+            flip: None,
+            frames: None,
+        }
+    }
+}
+
+impl VelocityAnimation {
+    pub fn with_flip(mut self, facing: SpriteFacing) -> Self {
+        self.flip = Some(facing);
+        self
+    }
+
+    pub fn with_frames(mut self, frames: DirectionalFrames) -> Self {
+        self.frames = Some(frames);
+        self
+    }
+
+    /// Gets the `flip_x` value for the velocity or `None` if the flip shouldn't change.
+    pub fn flip_x(&self, velocity: Vec2) -> Option<bool> {
+        let facing = self.flip?;
+        if velocity.x.abs() < self.min_speed {
+            return None;
+        }
+
+        let moving_left = velocity.x < 0.0;
+        Some(moving_left != (facing == SpriteFacing::Left))
+    }
+
+    /// Gets the atlas index for the velocity or `None` if there are no `frames`.
+    pub fn frame(&self, velocity: Vec2) -> Option<usize> {
+        let frames = self.frames?;
+        if velocity.length() < self.min_speed {
+            return Some(frames.idle);
+        }
+
+        let index = if velocity.y.abs() >= velocity.x.abs() {
+            if velocity.y > 0.0 {
+                frames.up
+            } else {
+                frames.down
+            }
+        } else {
+            frames.side
+        };
+
+        Some(index)
+    }
+}
+
+pub(crate) fn update_velocity_animations(
+    mut hosts: Query<(
+        &VelocityAnimation,
+        &SteeringHost,
+        Option<&mut Sprite>,
+        Option<&mut TextureAtlas>,
+    )>,
+) {
+    for (animation, host, sprite, atlas) in &mut hosts {
+        if let (Some(mut sprite), Some(flip_x)) = (sprite, animation.flip_x(host.velocity)) {
+            if sprite.flip_x != flip_x {
+                sprite.flip_x = flip_x;
+            }
+        }
+
+        if let (Some(mut atlas), Some(index)) = (atlas, animation.frame(host.velocity)) {
+            if atlas.index != index {
+                atlas.index = index;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn leftward_velocity_flips_and_selects_side_frame() {
+        let mut app = App::new();
+        app.add_systems(Update, update_velocity_animations);
+        let frames = DirectionalFrames {
+            idle: 0,
+            up: 1,
+            down: 2,
+            side: 3,
+        };
+        let host = app
+            .world_mut()
+            .spawn((
+                Sprite::default(),
+                TextureAtlas::default(),
+                SteeringHost {
+                    velocity: Vec2::new(-100.0, 20.0),
+                    ..default()
+                },
+                VelocityAnimation::default()
+                    .with_flip(SpriteFacing::Right)
+                    .with_frames(frames),
+            ))
+            .id();
+
+        app.update();
+        assert!(app.world().get::<Sprite>(host).unwrap().flip_x);
+        assert_eq!(app.world().get::<TextureAtlas>(host).unwrap().index, 3);
+
+        // moving straight up keeps the flip
+        app.world_mut()
+            .get_mut::<SteeringHost>(host)
+            .unwrap()
+            .velocity = Vec2::new(0.0, 100.0);
+        app.update();
+        assert!(app.world().get::<Sprite>(host).unwrap().flip_x);
+        assert_eq!(app.world().get::<TextureAtlas>(host).unwrap().index, 1);
+
+        app.world_mut()
+            .get_mut::<SteeringHost>(host)
+            .unwrap()
+            .velocity = Vec2::new(100.0, 0.0);
+        app.update();
+        assert!(!app.world().get::<Sprite>(host).unwrap().flip_x);
+        assert_eq!(app.world().get::<TextureAtlas>(host).unwrap().index, 3);
+    }
+}
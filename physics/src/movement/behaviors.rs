@@ -1,10 +1,13 @@
-use crate::prelude::{colliders::Collider, spatial_hash::SpatialHash};
+use crate::prelude::{colliders::Collider, shapes::ShapeType, spatial_hash::SpatialHash};
+
+use crate::TargetReachedEvent;
 
 use super::steering::{
-    PhysicalParams, SteeringHost, SteeringTarget, SteeringTargetEntity, SteeringTargetFull,
+    ArrivalDetection, PhysicalParams, SteeringBundle, SteeringEnabled, SteeringHost,
+    SteeringTarget, SteeringTargetCursor, SteeringTargetEntity, SteeringTargetFull,
     SteeringTargetVec2,
 };
-use bevy::prelude::*;
+use bevy::{prelude::*, window::PrimaryWindow};
 use common::math::rng_f32;
 
 pub trait SteeringBehavior: Component {
@@ -21,6 +24,7 @@ pub trait SteeringBehavior: Component {
 #[derive(Component, Default)]
 pub struct SteerSeek;
 
+#[allow(clippy::type_complexity)]
 pub(crate) fn steer_entity<T: SteeringBehavior + Component>(
     mut hosts: Query<(
         &mut T,
@@ -28,25 +32,120 @@ pub(crate) fn steer_entity<T: SteeringBehavior + Component>(
         &mut SteeringHost,
         &Transform,
         &PhysicalParams,
+        Option<&SteeringEnabled>,
     )>,
-    targets: Query<(&Transform, &SteeringHost), Without<SteeringTargetEntity>>,
+    targets: Query<(&Transform, Option<&SteeringHost>), Without<SteeringTargetEntity>>,
 ) {
-    for (mut behavior, target_entity, mut host, transform, params) in hosts.iter_mut() {
-        if let Ok((target_transform, target_host)) = targets.get(target_entity.0) {
-            let steering = behavior.steer(
-                transform,
-                &host,
-                params,
-                &SteeringTargetFull {
-                    position: target_transform.translation.xy(),
-                    velocity: target_host.velocity,
-                },
-            );
+    for (mut behavior, target_entity, mut host, transform, params, enabled) in hosts.iter_mut() {
+        if !SteeringEnabled::is_enabled(enabled) {
+            continue;
+        }
+        if let Ok((target_transform, target_host)) = targets.get(target_entity.entity) {
+            let steering = match target_host {
+                Some(target_host) if target_entity.predict_velocity => {
+                    behavior.steer(transform, &host, params, &(target_transform, target_host))
+                }
+                _ => behavior.steer(transform, &host, params, target_transform),
+            };
             host.steer(steering);
         }
     }
 }
 
+/// Removes `SteeringTargetEntity` from the hosts whose target entity was despawned
+/// (or lost its `Transform`), so they don't keep a dangling target forever.
+pub(crate) fn clear_lost_targets(
+    hosts: Query<(&SteeringTargetEntity, Entity)>,
+    targets: Query<(), With<Transform>>,
+    mut commands: Commands,
+) {
+    for (target_entity, entity) in &hosts {
+        if !targets.contains(target_entity.entity) {
+            commands.entity(entity).remove::<SteeringTargetEntity>();
+        }
+    }
+}
+
+/// Writes the cursor's world position into `SteeringTargetVec2` of the `SteeringTargetCursor` hosts.
+pub(crate) fn update_cursor_targets(
+    mut hosts: Query<(Option<&mut SteeringTargetVec2>, Entity), With<SteeringTargetCursor>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    mut commands: Commands,
+) {
+    let Some(cursor) = cursor_world_position(&windows, &cameras) else {
+        return;
+    };
+
+    for (target, entity) in hosts.iter_mut() {
+        match target {
+            Some(mut target) => target.0 = Some(cursor),
+            None => {
+                commands
+                    .entity(entity)
+                    .insert(SteeringTargetVec2::new(cursor));
+            }
+        }
+    }
+}
+
+/// Gets the cursor position in the world space of the first active camera.
+/// Returns `None` if there is no primary window or camera or the cursor is outside of the window.
+fn cursor_world_position(
+    windows: &Query<&Window, With<PrimaryWindow>>,
+    cameras: &Query<(&Camera, &GlobalTransform)>,
+) -> Option<Vec2> {
+    let cursor = windows.get_single().ok()?.cursor_position()?;
+    let (camera, camera_transform) = cameras.iter().find(|(camera, _)| camera.is_active)?;
+    camera.viewport_to_world_2d(camera_transform, cursor)
+}
+
+/// Sends `TargetReachedEvent` for the `ArrivalDetection` hosts which came close enough
+/// to their targets. Hosts without a target are considered not arrived.
+#[allow(clippy::type_complexity)]
+pub(crate) fn detect_target_reached(
+    mut hosts: Query<(
+        &mut ArrivalDetection,
+        &Transform,
+        Option<&SteeringTargetVec2>,
+        Option<&SteeringTargetFull>,
+        Option<&SteeringTargetEntity>,
+        Option<&SteeringEnabled>,
+        Entity,
+    )>,
+    targets: Query<&Transform>,
+    mut evt_target_reached: EventWriter<TargetReachedEvent>,
+) {
+    for (mut detection, transform, target_vec2, target_full, target_entity, enabled, entity) in
+        &mut hosts
+    {
+        if !SteeringEnabled::is_enabled(enabled) {
+            continue;
+        }
+        let target = target_vec2
+            .and_then(|target| target.0)
+            .or_else(|| target_full.map(|target| target.position()))
+            .or_else(|| {
+                target_entity
+                    .and_then(|target| targets.get(target.entity).ok())
+                    .map(|target| target.translation.xy())
+            });
+
+        let reached = target.is_some_and(|target| {
+            transform.translation.xy().distance_squared(target)
+                <= detection.arrival_epsilon * detection.arrival_epsilon
+        });
+
+        if reached && !detection.reached {
+            evt_target_reached.send(TargetReachedEvent { entity });
+        }
+        if detection.reached != reached {
+            detection.reached = reached;
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
 pub(crate) fn steer_vec2<T: SteeringBehavior + Component>(
     mut hosts: Query<(
         &mut T,
@@ -54,16 +153,21 @@ pub(crate) fn steer_vec2<T: SteeringBehavior + Component>(
         &mut SteeringHost,
         &Transform,
         &PhysicalParams,
+        Option<&SteeringEnabled>,
     )>,
 ) {
-    for (mut behavior, target, mut host, transform, params) in hosts.iter_mut() {
-        if target.0 != Vec2::ZERO {
-            let steering = behavior.steer(transform, &host, params, &target.0);
+    for (mut behavior, target, mut host, transform, params, enabled) in hosts.iter_mut() {
+        if !SteeringEnabled::is_enabled(enabled) {
+            continue;
+        }
+        if let Some(target) = target.0 {
+            let steering = behavior.steer(transform, &host, params, &target);
             host.steer(steering);
         }
     }
 }
 
+#[allow(clippy::type_complexity)]
 pub(crate) fn steer_collision_avoidance(
     mut hosts: Query<(
         &mut SteerCollisionAvoidance,
@@ -71,22 +175,18 @@ pub(crate) fn steer_collision_avoidance(
         &mut SteeringHost,
         &Transform,
         &PhysicalParams,
+        Option<&SteeringEnabled>,
         Entity,
     )>,
     colliders: Query<&Collider>,
     hash: Res<SpatialHash>,
-    mut gizmos: Gizmos,
 ) {
-    for (mut behavior, collider, mut host, transform, params, entity) in hosts.iter_mut() {
+    for (mut behavior, collider, mut host, transform, params, enabled, entity) in hosts.iter_mut() {
+        if !SteeringEnabled::is_enabled(enabled) {
+            continue;
+        }
         let steering = behavior.steer(
-            transform,
-            &host,
-            collider,
-            entity,
-            &colliders,
-            &hash,
-            params,
-            &mut gizmos,
+            transform, &host, collider, entity, &colliders, &hash, params,
         );
         //host.steer(steering);
         host.steering += steering;
@@ -128,18 +228,46 @@ impl SteeringBehavior for SteerFlee {
     }
 }
 
+/// Determines how `SteerArrival` slows down within its slowing radius.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Reflect)]
+pub enum ArrivalCurve {
+    /// The speed is proportional to the distance to the target.
+    #[default]
+    Linear,
+    /// The speed drops faster when the host enters the slowing radius
+    /// and the host approaches the target slowly.
+    Quadratic,
+    /// The speed changes smoothly both at the edge of the slowing radius and near the target.
+    SmoothStep,
+}
+
+impl ArrivalCurve {
+    /// Maps the normalized distance to the target (`distance / slowing_radius`, in range [0, 1])
+    /// to the velocity factor.
+    pub fn apply(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            ArrivalCurve::Linear => t,
+            ArrivalCurve::Quadratic => t * t,
+            ArrivalCurve::SmoothStep => t * t * (3.0 - 2.0 * t),
+        }
+    }
+}
+
 /// Moves towards the specified target slowing down gradually as the host is
 /// getting closer. The slowing starts when the host is within circle with radius
-/// `slowing_radius`.
+/// `slowing_radius`. The way the host slows down is defined by `curve`.
 #[derive(Component, Debug, Clone, Copy, Reflect)]
 pub struct SteerArrival {
     pub slowing_radius: f32,
+    pub curve: ArrivalCurve,
 }
 
 impl Default for SteerArrival {
     fn default() -> Self {
         Self {
             slowing_radius: 16.0,
+            curve: ArrivalCurve::Linear,
         }
     }
 }
@@ -155,7 +283,9 @@ impl SteeringBehavior for SteerArrival {
         let dv = target.position() - position.translation.xy();
         let distance = dv.length();
         let dv = if distance < self.slowing_radius {
-            dv.normalize_or_zero() * params.max_velocity * (distance / self.slowing_radius)
+            dv.normalize_or_zero()
+                * params.max_velocity
+                * self.curve.apply(distance / self.slowing_radius)
         } else {
             dv.normalize_or_zero() * params.max_velocity
         };
@@ -176,7 +306,7 @@ impl SteerEvade {
         target: &impl SteeringTarget,
     ) -> Vec2 {
         let distance = (target.position() - position.translation.xy()).length();
-        let updates_ahead = distance / params.max_velocity;
+        let updates_ahead = params.prediction_time(distance);
 
         let future_pos = target.position() + target.velocity() * updates_ahead;
 
@@ -196,7 +326,7 @@ impl SteerPursuit {
         target: &impl SteeringTarget,
     ) -> Vec2 {
         let distance = (target.position() - position.translation.xy()).length();
-        let updates_ahead = distance / params.max_velocity;
+        let updates_ahead = params.prediction_time(distance);
 
         let future_pos = target.position() + target.velocity() * updates_ahead;
 
@@ -204,8 +334,206 @@ impl SteerPursuit {
     }
 }
 
+/// Moves to the midpoint between two entities predicting their future positions,
+/// e.g. a bodyguard placing itself between a VIP and a threat.
+/// Both entities are required to have a `Transform`, their velocities are taken from
+/// `SteeringHost` if they have one. The host arrives at the midpoint the same way as `SteerArrival`.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct SteerInterpose {
+    pub a: Entity,
+    pub b: Entity,
+    pub arrival: SteerArrival,
+}
+
+impl SteerInterpose {
+    pub fn new(a: Entity, b: Entity) -> Self {
+        Self {
+            a,
+            b,
+            arrival: SteerArrival::default(),
+        }
+    }
+
+    pub fn steer(
+        &mut self,
+        position: &Transform,
+        host: &SteeringHost,
+        params: &PhysicalParams,
+        a: &impl SteeringTarget,
+        b: &impl SteeringTarget,
+    ) -> Vec2 {
+        // predicting where the targets will be when the host reaches the current midpoint
+        let midpoint = (a.position() + b.position()) / 2.0;
+        let distance = (midpoint - position.translation.xy()).length();
+        let updates_ahead = params.prediction_time(distance);
+
+        let future_a = a.position() + a.velocity() * updates_ahead;
+        let future_b = b.position() + b.velocity() * updates_ahead;
+
+        self.arrival
+            .steer(position, host, params, &((future_a + future_b) / 2.0))
+    }
+}
+
+pub(crate) fn steer_interpose(
+    mut hosts: Query<(
+        &mut SteerInterpose,
+        &mut SteeringHost,
+        &Transform,
+        &PhysicalParams,
+        Option<&SteeringEnabled>,
+    )>,
+    targets: Query<(&Transform, Option<&SteeringHost>), Without<SteerInterpose>>,
+) {
+    let as_target = |(transform, host): (&Transform, Option<&SteeringHost>)| SteeringTargetFull {
+        position: transform.translation.xy(),
+        velocity: host.map_or(Vec2::ZERO, |host| host.velocity),
+    };
+
+    for (mut behavior, mut host, transform, params, enabled) in hosts.iter_mut() {
+        if !SteeringEnabled::is_enabled(enabled) {
+            continue;
+        }
+        let (Ok(a), Ok(b)) = (targets.get(behavior.a), targets.get(behavior.b)) else {
+            continue;
+        };
+
+        let steering = behavior.steer(transform, &host, params, &as_target(a), &as_target(b));
+        host.steer(steering);
+    }
+}
+
+/// Hides from the `from` entity behind the colliders on `cover_layer`.
+/// The hiding spot of an obstacle is on its far side relative to the threat, `cover_distance`
+/// units away from the obstacle's edge. The host arrives at the nearest hiding spot among
+/// the obstacles within `search_radius`, if there are none, it evades the threat.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct SteerHide {
+    pub from: Entity,
+    /// Bitmask of layers of the colliders used as cover.
+    pub cover_layer: i32,
+    pub cover_distance: f32,
+    pub search_radius: f32,
+    pub arrival: SteerArrival,
+    hiding_spot: Option<Vec2>,
+}
+
+impl SteerHide {
+    pub fn new(from: Entity, cover_layer: i32, cover_distance: f32) -> Self {
+        Self {
+            from,
+            cover_layer,
+            cover_distance,
+            search_radius: 200.0,
+            arrival: SteerArrival::default(),
+            hiding_spot: None,
+        }
+    }
+
+    /// Gets the hiding spot the host moved to during the last update or `None` if there was
+    /// no cover nearby.
+    pub fn hiding_spot(&self) -> Option<Vec2> {
+        self.hiding_spot
+    }
+
+    /// Gets the position on the far side of an obstacle relative to the threat.
+    pub fn hiding_spot_behind(
+        obstacle_position: Vec2,
+        obstacle_radius: f32,
+        threat_position: Vec2,
+        cover_distance: f32,
+    ) -> Vec2 {
+        let dir = (obstacle_position - threat_position).normalize_or_zero();
+        obstacle_position + dir * (obstacle_radius + cover_distance)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn steer(
+        &mut self,
+        position: &Transform,
+        host: &SteeringHost,
+        params: &PhysicalParams,
+        threat: &impl SteeringTarget,
+        entity: Entity,
+        colliders: &Query<&Collider>,
+        hash: &SpatialHash,
+    ) -> Vec2 {
+        let host_pos = position.translation.xy();
+
+        let mut obstacles = vec![];
+        hash.overlap_circle(
+            colliders,
+            host_pos,
+            self.search_radius,
+            Some(entity),
+            Some(&mut obstacles),
+            Some(self.cover_layer),
+        );
+
+        self.hiding_spot = obstacles
+            .into_iter()
+            .filter(|obstacle| *obstacle != self.from)
+            .filter_map(|obstacle| colliders.get(obstacle).ok())
+            .filter(|collider| !collider.is_trigger)
+            .map(|collider| {
+                let radius = match collider.shape.scaled_type() {
+                    ShapeType::Circle { radius } => radius,
+                    ShapeType::Box { width, height } => Vec2::new(width, height).length() / 2.0,
+                    ShapeType::None => 0.0,
+                };
+                Self::hiding_spot_behind(
+                    collider.absolute_position(),
+                    radius,
+                    threat.position(),
+                    self.cover_distance,
+                )
+            })
+            .min_by(|a, b| {
+                a.distance_squared(host_pos)
+                    .total_cmp(&b.distance_squared(host_pos))
+            });
+
+        match self.hiding_spot {
+            Some(spot) => self.arrival.steer(position, host, params, &spot),
+            None => SteerEvade.steer(position, host, params, threat),
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub(crate) fn steer_hide(
+    mut hosts: Query<(
+        &mut SteerHide,
+        &mut SteeringHost,
+        &Transform,
+        &PhysicalParams,
+        Option<&SteeringEnabled>,
+        Entity,
+    )>,
+    threats: Query<(&Transform, Option<&SteeringHost>), Without<SteerHide>>,
+    colliders: Query<&Collider>,
+    hash: Res<SpatialHash>,
+) {
+    for (mut behavior, mut host, transform, params, enabled, entity) in hosts.iter_mut() {
+        if !SteeringEnabled::is_enabled(enabled) {
+            continue;
+        }
+        let Ok((threat_transform, threat_host)) = threats.get(behavior.from) else {
+            continue;
+        };
+
+        let threat = SteeringTargetFull {
+            position: threat_transform.translation.xy(),
+            velocity: threat_host.map_or(Vec2::ZERO, |host| host.velocity),
+        };
+
+        let steering = behavior.steer(transform, &host, params, &threat, entity, &colliders, &hash);
+        host.steer(steering);
+    }
+}
+
 /// Wanders around randomly changing host's angle.
-#[derive(Debug, Clone, Copy, Reflect)]
+#[derive(Component, Debug, Clone, Copy, Reflect)]
 pub struct SteerWander {
     pub circle_distance: f32,
     pub circle_radius: f32,
@@ -249,10 +577,149 @@ impl SteerWander {
     }
 }
 
+pub(crate) fn steer_wander(
+    mut hosts: Query<(
+        &mut SteerWander,
+        &mut SteeringHost,
+        &PhysicalParams,
+        Option<&SteeringEnabled>,
+    )>,
+) {
+    for (mut behavior, mut host, params, enabled) in hosts.iter_mut() {
+        if !SteeringEnabled::is_enabled(enabled) {
+            continue;
+        }
+        let steering = behavior.steer(&host, params);
+        host.steer(steering);
+    }
+}
+
+/// Brakes to a stop decelerating at `decel` units per second squared, e.g. at the end of a dash.
+/// The steering opposes the current velocity and never overshoots, i.e. the host stops
+/// instead of moving backwards. Friction is still applied on top of the braking.
+/// The steering accumulated by the other behaviors is replaced while braking,
+/// so `steer_brake` runs after the other behavior systems.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Reflect)]
+pub struct SteerBrake {
+    pub decel: f32,
+}
+
+impl SteerBrake {
+    pub fn new(decel: f32) -> Self {
+        Self { decel }
+    }
+
+    pub fn steer(&self, host: &SteeringHost, params: &PhysicalParams, delta_seconds: f32) -> Vec2 {
+        let speed = host.speed();
+        if speed == 0.0 {
+            return Vec2::ZERO;
+        }
+
+        let speed_change = (self.decel * delta_seconds).min(speed);
+        // The steering is divided by the mass when it's applied
+        let mass = if params.mass > 0.0 { params.mass } else { 1.0 };
+        -host.velocity / speed * speed_change * mass
+    }
+}
+
+pub(crate) fn steer_brake(
+    mut hosts: Query<(
+        &SteerBrake,
+        &mut SteeringHost,
+        &PhysicalParams,
+        Option<&SteeringEnabled>,
+    )>,
+    time: Res<Time>,
+) {
+    for (behavior, mut host, params, enabled) in hosts.iter_mut() {
+        if !SteeringEnabled::is_enabled(enabled) {
+            continue;
+        }
+        // Replacing the steering of the other behaviors and what's left of the last tick's
+        // steering, otherwise the host would overshoot
+        host.steering = behavior.steer(&host, params, time.delta_seconds());
+    }
+}
+
+/// A `SteeringBundle` seeking the target, which is either `SteeringTargetVec2`
+/// or `SteeringTargetEntity`.
+#[derive(Bundle)]
+pub struct SeekBundle<T: Component> {
+    pub behavior: SteerSeek,
+    pub target: T,
+    pub steering: SteeringBundle,
+}
+
+impl<T: Component> SeekBundle<T> {
+    pub fn new(target: T) -> Self {
+        Self {
+            behavior: SteerSeek,
+            target,
+            steering: SteeringBundle::default(),
+        }
+    }
+}
+
+/// A `SteeringBundle` arriving at the target, which is either `SteeringTargetVec2`
+/// or `SteeringTargetEntity`.
+///
+/// ```
+/// use bevy::prelude::*;
+/// use physics::prelude::{behaviors::ArriveBundle, steering::SteeringTargetEntity};
+/// # #[derive(Component)]
+/// # struct Player;
+///
+/// fn spawn_enemy(mut commands: Commands, player: Query<Entity, With<Player>>) {
+///     let player = player.single();
+///     commands.spawn((
+///         SpatialBundle::default(),
+///         ArriveBundle::new(SteeringTargetEntity::new(player)),
+///     ));
+/// }
+/// # let mut app = App::new();
+/// # app.world_mut().spawn(Player);
+/// # app.add_systems(Update, spawn_enemy);
+/// # app.update();
+/// # let mut arrivals = app.world_mut().query::<&SteeringTargetEntity>();
+/// # assert_eq!(arrivals.iter(app.world()).count(), 1);
+/// ```
+#[derive(Bundle)]
+pub struct ArriveBundle<T: Component> {
+    pub behavior: SteerArrival,
+    pub target: T,
+    pub steering: SteeringBundle,
+}
+
+impl<T: Component> ArriveBundle<T> {
+    pub fn new(target: T) -> Self {
+        Self {
+            behavior: SteerArrival::default(),
+            target,
+            steering: SteeringBundle::default(),
+        }
+    }
+}
+
+/// A `SteeringBundle` wandering around, doesn't need a target.
+#[derive(Bundle, Default)]
+pub struct WanderBundle {
+    pub behavior: SteerWander,
+    pub steering: SteeringBundle,
+}
+
+/// Tries to avoid collisions with the colliders on its way. The threats are the colliders
+/// the host would hit moving `max_see_ahead` units along its velocity, so the colliders
+/// which are close but off the path are ignored.
+/// The avoidance is blended from up to `max_threats` closest threats.
 #[derive(Component, Debug, Clone, Copy)]
 pub struct SteerCollisionAvoidance {
     pub max_see_ahead: f32,
     pub avoid_force: f32,
+    /// Max number of threats the avoidance is blended from. Defaults to `3`.
+    pub max_threats: usize,
+    /// Bitmask of layers which are considered threats, e.g. only the static world geometry.
+    /// If `None`, the host collider's `collides_with_layers` is used. Defaults to `None`.
+    pub layer_mask: Option<i32>,
     ahead: Vec2,
     avoidance: Vec2,
 }
@@ -262,6 +729,8 @@ impl Default for SteerCollisionAvoidance {
         Self {
             max_see_ahead: 18.0,
             avoid_force: 200.0,
+            max_threats: 3,
+            layer_mask: None,
             ahead: Vec2::ZERO,
             avoidance: Vec2::ZERO,
         }
@@ -269,6 +738,13 @@ impl Default for SteerCollisionAvoidance {
 }
 
 impl SteerCollisionAvoidance {
+    /// Considers only the colliders on `layer_mask` as threats.
+    pub fn with_layer_mask(mut self, layer_mask: i32) -> Self {
+        self.layer_mask = Some(layer_mask);
+        self
+    }
+
+    #[allow(clippy::too_many_arguments)]
     fn steer(
         &mut self,
         transform: &Transform,
@@ -278,171 +754,236 @@ impl SteerCollisionAvoidance {
         query: &Query<&Collider>,
         hash: &SpatialHash,
         params: &PhysicalParams,
-        gizmos: &mut Gizmos,
     ) -> Vec2 {
-        let dv = host.velocity.normalize_or_zero()
-            * (self.max_see_ahead * host.velocity.length() / params.max_velocity);
+        let dv = if params.max_velocity > 0.0 {
+            host.velocity.normalize_or_zero()
+                * (self.max_see_ahead * host.velocity.length() / params.max_velocity)
+        } else {
+            Vec2::ZERO
+        };
 
         self.ahead = transform.translation.xy() + dv;
 
-        let circle_center = collider.center()
-            - collider.bounds().width / 2.0 // account for the radius
-            + Vec2::new(self.ahead.x, self.ahead.y);
-
-        //gizmos.circle_2d(circle_center, 16., Color::srgb(1.0, 1.0, 0.0));
-        let mut neighbors = vec![];
-        hash.overlap_circle(
+        let neighbors = hash.aabb_broadphase(
             query,
-            circle_center,
-            16.,
+            collider.bounds().swept(dv),
             Some(entity),
-            Some(&mut neighbors),
-            Some(collider.collides_with_layers),
+            Some(self.layer_mask.unwrap_or(collider.collides_with_layers)),
         );
 
-        let mut distance = f32::MAX;
-        let mut closest = None;
-
-        for neighbor_id in neighbors {
-            let neighbor = query.get(neighbor_id).ok().unwrap();
-            let d = (neighbor.absolute_position() - collider.absolute_position()).length();
-            if d < distance {
-                distance = d;
-                closest = Some(neighbor);
-            }
-        }
-
-        if let Some(closest) = closest {
-            self.avoidance =
-                (self.ahead - closest.absolute_position()).normalize_or_zero() * self.avoid_force;
-        } else {
-            self.avoidance *= 0.0;
-        }
+        let position = collider.absolute_position();
+        let mut threats: Vec<(Vec2, f32)> = neighbors
+            .into_iter()
+            .filter_map(|neighbor_id| {
+                let neighbor = query.get(neighbor_id).ok()?;
+                if neighbor.is_trigger {
+                    return None;
+                }
+
+                // Only the colliders which are actually on the host's way are threats
+                collider.collides_with_motion(neighbor, dv)?;
+
+                let neighbor_position = neighbor.absolute_position();
+                Some((neighbor_position, neighbor_position.distance(position)))
+            })
+            .collect();
+        threats.sort_by(|a, b| a.1.total_cmp(&b.1));
+        threats.truncate(self.max_threats);
+
+        // Closer threats have more influence on the avoidance direction
+        let direction: Vec2 = threats
+            .iter()
+            .map(|(threat_position, distance)| {
+                (self.ahead - *threat_position).normalize_or_zero() / distance.max(1.0)
+            })
+            .sum();
+
+        self.avoidance = direction.normalize_or_zero() * self.avoid_force;
 
         self.avoidance
     }
 }
 
-/*
-/// Tries to avoid all collisions by checking the closest threatening collider on its way.
-#[derive(Debug, Clone, Copy, Reflect)]
-pub struct SteerCollisionAvoidance {
-    pub max_see_ahead: f32,
-    pub avoid_force: f32,
-    ahead: Vec2,
-    avoidance: Vec2,
+/// Tries to separate from other colliders.
+/// Separation radius is defined by the `radius` field.
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+pub struct SteerSeparation {
+    pub radius: f32,
+    pub max_force: f32,
+    /// Bitmask of layers to separate from. If `None`, all the layers are used.
+    /// Defaults to `None`.
+    pub layer_mask: Option<i32>,
 }
 
-impl Default for SteerCollisionAvoidance {
+impl Default for SteerSeparation {
     fn default() -> Self {
         Self {
-            max_see_ahead: 16.0,
-            avoid_force: 75.0,
-            ..default()
+            radius: 32.0,
+            max_force: 75.0,
+            layer_mask: None,
         }
     }
 }
 
-impl SteerCollisionAvoidance {
+impl SteerSeparation {
     pub fn steer(
-        &mut self,
+        &self,
         position: &Transform,
-        host: &SteeringHost,
-        params: &PhysicalParams,
-        collider: &Collider,
+        entity: Entity,
+        colliders: &Query<&Collider>,
         hash: &SpatialHash,
-        layer_mask: Option<i32>,
     ) -> Vec2 {
-        let dv = host.velocity.normalize_or_zero()
-            * (self.max_see_ahead * host.velocity.length() / params.max_velocity);
-
-        self.ahead = position.translation.xy() + dv;
-
-        let mut rect = collider.bounds();
-        rect.x += self.ahead.x;
-        rect.y += self.ahead.y;
+        let host_pos = position.translation.xy();
 
-        let neighbors =
-            hash.aabb_broadphase(collider.id, rect, layer_mask);
+        let mut neighbors = vec![];
+        let neighbor_count = hash.overlap_circle(
+            colliders,
+            host_pos,
+            self.radius,
+            Some(entity),
+            Some(&mut neighbors),
+            self.layer_mask,
+        );
 
-        let mut distance = f32::MAX;
-        let mut closest = None;
+        if neighbor_count == 0 {
+            return Vec2::ZERO;
+        }
 
-        for neighbor_id in neighbors {
-            let neighbor = collider_store.get(neighbor_id).unwrap();
+        let force: Vec2 = neighbors
+            .into_iter()
+            .filter_map(|neighbor| colliders.get(neighbor).ok())
+            .map(|neighbor| neighbor.absolute_position() - host_pos)
+            .sum();
 
-            let d = (neighbor.position() - collider.position()).length();
-            if d < distance {
-                distance = d;
-                closest = Some(neighbor);
-            }
-        }
+        -force.normalize_or_zero() * self.max_force
+    }
+}
 
-        if let Some(closest) = closest {
-            self.avoidance =
-                (self.ahead - closest.position()).normalize_or_zero() * self.avoid_force;
-        } else {
-            self.avoidance *= 0.0;
+pub(crate) fn steer_separation(
+    mut hosts: Query<(
+        &SteerSeparation,
+        &mut SteeringHost,
+        &Transform,
+        Option<&SteeringEnabled>,
+        Entity,
+    )>,
+    colliders: Query<&Collider>,
+    hash: Res<SpatialHash>,
+) {
+    for (behavior, mut host, transform, enabled, entity) in hosts.iter_mut() {
+        if !SteeringEnabled::is_enabled(enabled) {
+            continue;
         }
-
-        self.avoidance
+        let steering = behavior.steer(transform, entity, &colliders, &hash);
+        host.steer(steering);
     }
 }
 
-/// Tries to separate from other colliders.
-/// Separation radius is defined by the `radius` field.
-#[derive(Debug, Reflect)]
-pub struct SteerSeparation {
-    pub radius: f32,
-    pub max_force: f32,
+/// Avoids obstacles by casting three feeler rays from the host's collider: one along
+/// the velocity and two at `feeler_angle` to the sides of it. The host is pushed
+/// along the surface normal of the closest hit, the closer the hit the stronger the push.
+/// Unlike `SteerCollisionAvoidance`, works with the actual surfaces of the colliders,
+/// so the hosts follow walls smoothly instead of bouncing off their centers.
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+pub struct SteerRaycastAvoidance {
+    /// Length of the central feeler. Defaults to `48.0`.
+    pub feeler_length: f32,
+    /// Length of the side feelers. Defaults to `32.0`.
+    pub side_feeler_length: f32,
+    /// Angle between the velocity and the side feelers in radians. Defaults to 30 degrees.
+    pub feeler_angle: f32,
+    pub avoid_force: f32,
+    /// Bitmask of layers the feelers hit. If `None`, the host collider's
+    /// `collides_with_layers` is used. Defaults to `None`.
+    pub layer_mask: Option<i32>,
 }
 
-impl Default for SteerSeparation {
+impl Default for SteerRaycastAvoidance {
     fn default() -> Self {
         Self {
-            radius: 32.0,
-            max_force: 75.0,
+            feeler_length: 48.0,
+            side_feeler_length: 32.0,
+            feeler_angle: 30f32.to_radians(),
+            avoid_force: 200.0,
+            layer_mask: None,
         }
     }
 }
 
-impl SteerSeparation {
+impl SteerRaycastAvoidance {
     pub fn steer(
         &self,
-        position: &Transform,
+        host: &SteeringHost,
         collider: &Collider,
-        layer_mask: Option<i32>,
+        entity: Entity,
+        query: &Query<&Collider>,
+        hash: &SpatialHash,
     ) -> Vec2 {
-        let mut force = Vec2::ZERO;
-
-        let mut rect = collider_store.get(collider.id).unwrap().bounds();
-        rect.inflate(self.radius, self.radius);
-
-        // TODO: Check if this method works, if not, use aabb_broadphase()
-        let neighbors =
-            collider_store.overlap_circle(position.translation.xy(), self.radius, Some(collider.id), layer_mask);
-        let neighbor_count = neighbors.len();
+        let Some(direction) = host.velocity.try_normalize() else {
+            return Vec2::ZERO;
+        };
 
-        for neighbor_id in neighbors {
-            let neighbor = collider_store.get(neighbor_id).unwrap();
-            force += neighbor.position() - position.translation.xy();
+        let origin = collider.absolute_position();
+        let layer_mask = self.layer_mask.unwrap_or(collider.collides_with_layers);
+        let feelers = [
+            direction * self.feeler_length,
+            Vec2::from_angle(self.feeler_angle).rotate(direction) * self.side_feeler_length,
+            Vec2::from_angle(-self.feeler_angle).rotate(direction) * self.side_feeler_length,
+        ];
+
+        // The feelers differ in length, so the hits are compared by the distance
+        // and each one is weighted by the length of its own feeler
+        let closest = feelers
+            .into_iter()
+            .filter_map(|feeler| {
+                hash.query_ray_first(query, origin, origin + feeler, Some(entity), layer_mask)
+                    .map(|hit| (hit, feeler.length()))
+            })
+            .min_by(|(a, _), (b, _)| a.distance.total_cmp(&b.distance));
+
+        match closest {
+            Some((hit, feeler_length)) if feeler_length > 0.0 => {
+                hit.normal * self.avoid_force * (1.0 - hit.distance / feeler_length).max(0.0)
+            }
+            _ => Vec2::ZERO,
         }
+    }
+}
 
-        if neighbor_count != 0 {
-            force /= neighbor_count as f32;
-            force *= -1.0;
+pub(crate) fn steer_raycast_avoidance(
+    mut hosts: Query<(
+        &SteerRaycastAvoidance,
+        &Collider,
+        &mut SteeringHost,
+        Option<&SteeringEnabled>,
+        Entity,
+    )>,
+    colliders: Query<&Collider>,
+    hash: Res<SpatialHash>,
+) {
+    for (behavior, collider, mut host, enabled, entity) in hosts.iter_mut() {
+        if !SteeringEnabled::is_enabled(enabled) {
+            continue;
         }
-
-        force.normalize_or_zero() * self.max_force
+        let steering = behavior.steer(&host, collider, entity, &colliders, &hash);
+        host.steer(steering);
     }
 }
 
-#[derive(Debug, Clone, Copy, Reflect)]
+/// Imitates a smooth queue of steering hosts by slowing down if another host
+/// is ahead of it.
+#[derive(Component, Debug, Clone, Copy, Reflect)]
 pub struct SteerQueue {
     pub max_radius: f32,
     pub max_ahead: f32,
     pub brake_coef: f32,
+    /// While the neighbor ahead is within `max_radius`, the host additionally steers towards
+    /// its velocity multiplied by this value, so it slows down harder. The slowdown goes
+    /// through `max_force` like any other steering instead of scaling the velocity directly.
     pub velocity_mult: f32,
+    /// Bitmask of layers of the hosts to queue behind. If `None`, all the layers are used.
+    /// Defaults to `None`.
+    pub layer_mask: Option<i32>,
 }
 
 impl Default for SteerQueue {
@@ -452,14 +993,16 @@ impl Default for SteerQueue {
             max_ahead: 16.0,
             brake_coef: 0.8,
             velocity_mult: 0.3,
+            layer_mask: None,
         }
     }
 }
 
-/// Imitates a smooth queue of steering hosts by slowing down if another host
-/// is ahead of it.
+/// A result of `SteerQueue::steer`.
 pub struct SteerQueueResult {
     pub steering: Vec2,
+    /// The multiplier of the desired velocity included in `steering`,
+    /// `1.0` if there's no neighbor within `max_radius`.
     pub velocity_multiplier: f32,
 }
 
@@ -468,22 +1011,20 @@ impl SteerQueue {
         &self,
         position: &Transform,
         host: &SteeringHost,
-        collider: &Collider,
-        layer_mask: Option<i32>,
+        entity: Entity,
+        colliders: &Query<&Collider>,
+        hash: &SpatialHash,
     ) -> SteerQueueResult {
-        let mut velocity = host.velocity;
         let mut brake = Vec2::ZERO;
         let mut velocity_multiplier = 1.0;
 
-        let neighbor =
-            self.get_neighbor_ahead(position, host, collider, collider_store, layer_mask);
-        if let Some(neighbor) = neighbor {
-            brake = -host.steering * self.brake_coef;
-            velocity *= -1.0;
-            brake += velocity;
+        if let Some(neighbor) = self.get_neighbor_ahead(position, host, entity, colliders, hash) {
+            brake = -host.steering * self.brake_coef - host.velocity;
 
-            if (neighbor.position() - position.translation.xy()).length() <= self.max_radius {
+            if (neighbor - position.translation.xy()).length() <= self.max_radius {
                 velocity_multiplier = self.velocity_mult;
+                let desired = host.velocity * velocity_multiplier;
+                brake += desired - host.velocity;
             }
         }
 
@@ -493,84 +1034,748 @@ impl SteerQueue {
         }
     }
 
+    /// Gets the position of the closest collider ahead of the host.
     fn get_neighbor_ahead(
         &self,
         position: &Transform,
         host: &SteeringHost,
-        collider: &Collider,
-        layer_mask: Option<i32>,
-    ) -> Option<&Collider> {
-        let qa = host.velocity.normalize_or_zero() * self.max_ahead;
-
-        let ahead = position.translation.xy() + qa;
+        entity: Entity,
+        colliders: &Query<&Collider>,
+        hash: &SpatialHash,
+    ) -> Option<Vec2> {
+        let ahead = position.translation.xy() + host.velocity.normalize_or_zero() * self.max_ahead;
 
-        let collider = collider_store.get(collider.id).unwrap();
-        // TODO: Check if this method works, if not, use aabb_broadphase()
-        let neighbors = collider_store.overlap_circle(
-            position.translation.xy(),
+        let mut neighbors = vec![];
+        hash.overlap_circle(
+            colliders,
+            ahead,
             self.max_radius,
-            Some(collider.id),
-            layer_mask,
+            Some(entity),
+            Some(&mut neighbors),
+            self.layer_mask,
         );
 
-        let mut closest = None;
-        let mut distance = f32::MAX;
+        neighbors
+            .into_iter()
+            .filter_map(|neighbor| colliders.get(neighbor).ok())
+            .map(|neighbor| neighbor.absolute_position())
+            .min_by(|a, b| {
+                a.distance_squared(ahead)
+                    .total_cmp(&b.distance_squared(ahead))
+            })
+    }
+}
 
-        for neighbor_id in neighbors {
-            let neighbor = collider_store.get(neighbor_id).unwrap();
-            let d = (neighbor.position() - ahead).length();
-            if d < distance {
-                distance = d;
-                closest = Some(neighbor);
-            }
+pub(crate) fn steer_queue(
+    mut hosts: Query<(
+        &SteerQueue,
+        &mut SteeringHost,
+        &Transform,
+        Option<&SteeringEnabled>,
+        Entity,
+    )>,
+    colliders: Query<&Collider>,
+    hash: Res<SpatialHash>,
+) {
+    for (behavior, mut host, transform, enabled, entity) in hosts.iter_mut() {
+        if !SteeringEnabled::is_enabled(enabled) {
+            continue;
         }
-
-        closest
+        let res = behavior.steer(transform, &host, entity, &colliders, &hash);
+        host.steer(res.steering);
     }
 }
 
-/// Follows a specified "leader" entity. The host will try to stay behind the leader
-/// by moving away from leader's field of view.
+/// Follows a specified `leader` entity. The host will try to stay behind the leader
+/// by arriving at the point `leader_behind_dist` units behind it and moving away from
+/// the leader's way if it's on the leader's sight.
 ///
 /// `steer` method updates `ahead` and `behind` vectors. You can check if your
 /// steering host is on leader's sight by calling the `is_on_leader_sight()` method.
-#[derive(Debug, Clone, Copy, Reflect)]
+#[derive(Component, Debug, Clone, Copy, Reflect)]
 pub struct SteerLeaderFollowing {
+    pub leader: Entity,
     pub leader_behind_dist: f32,
     pub leader_sight_radius: f32,
+    pub arrival: SteerArrival,
     pub ahead: Vec2,
     pub behind: Vec2,
 }
 
-impl Default for SteerLeaderFollowing {
-    fn default() -> Self {
+impl SteerLeaderFollowing {
+    pub fn new(leader: Entity) -> Self {
         Self {
+            leader,
             leader_behind_dist: 32.0,
             leader_sight_radius: 32.0,
-            ..default()
+            arrival: SteerArrival::default(),
+            ahead: Vec2::ZERO,
+            behind: Vec2::ZERO,
         }
     }
+
+    pub fn steer(
+        &mut self,
+        position: &Transform,
+        host: &SteeringHost,
+        params: &PhysicalParams,
+        leader: &impl SteeringTarget,
+    ) -> Vec2 {
+        let dv = leader.velocity().normalize_or_zero() * self.leader_behind_dist;
+
+        self.ahead = leader.position() + dv;
+        self.behind = leader.position() - dv;
+
+        let mut force = self.arrival.steer(position, host, params, &self.behind);
+        if self.is_on_leader_sight(leader.position(), position) {
+            force += SteerEvade.steer(position, host, params, leader);
+        }
+
+        force
+    }
+
+    pub fn is_on_leader_sight(&self, leader_position: Vec2, position: &Transform) -> bool {
+        let position = position.translation.xy();
+        (self.ahead - position).length() <= self.leader_sight_radius
+            || (leader_position - position).length() <= self.leader_sight_radius
+    }
 }
 
-impl SteerLeaderFollowing {
-    pub fn steer(&mut self, position: &Transform, host: &SteeringHost) -> Vec2 {
-        let mut dv = host.velocity.normalize_or_zero() * self.leader_behind_dist;
+pub(crate) fn steer_leader_following(
+    mut hosts: Query<(
+        &mut SteerLeaderFollowing,
+        &mut SteeringHost,
+        &Transform,
+        &PhysicalParams,
+        Option<&SteeringEnabled>,
+    )>,
+    leaders: Query<(&Transform, Option<&SteeringHost>), Without<SteerLeaderFollowing>>,
+) {
+    for (mut behavior, mut host, transform, params, enabled) in hosts.iter_mut() {
+        if !SteeringEnabled::is_enabled(enabled) {
+            continue;
+        }
+        let Ok((leader_transform, leader_host)) = leaders.get(behavior.leader) else {
+            continue;
+        };
 
-        self.ahead = position.translation.xy() + dv;
-        dv *= -1.0;
-        self.behind = position.translation.xy() + dv;
+        let leader = SteeringTargetFull {
+            position: leader_transform.translation.xy(),
+            velocity: leader_host.map_or(Vec2::ZERO, |host| host.velocity),
+        };
 
-        self.behind
+        let steering = behavior.steer(transform, &host, params, &leader);
+        host.steer(steering);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    fn arrival_speed(curve: ArrivalCurve, distance: f32) -> f32 {
+        let mut arrival = SteerArrival {
+            slowing_radius: 100.0,
+            curve,
+        };
+        let params = PhysicalParams::default();
+        let desired = arrival.steer(
+            &Transform::default(),
+            &SteeringHost::default(),
+            &params,
+            &Vec2::new(distance, 0.0),
+        );
+        desired.length() / params.max_velocity
+    }
+
+    #[test]
+    fn arrival_curves_at_half_slowing_radius() {
+        assert_eq!(arrival_speed(ArrivalCurve::Linear, 50.0), 0.5);
+        assert_eq!(arrival_speed(ArrivalCurve::Quadratic, 50.0), 0.25);
+        assert_eq!(arrival_speed(ArrivalCurve::SmoothStep, 50.0), 0.5);
+        assert_eq!(ArrivalCurve::SmoothStep.apply(0.25), 0.15625);
+    }
+
+    #[test]
+    fn arrival_curves_are_full_speed_outside_slowing_radius() {
+        for curve in [
+            ArrivalCurve::Linear,
+            ArrivalCurve::Quadratic,
+            ArrivalCurve::SmoothStep,
+        ] {
+            assert_eq!(arrival_speed(curve, 150.0), 1.0);
+            assert_eq!(arrival_speed(curve, 0.0), 0.0);
+        }
+        assert_eq!(SteerArrival::default().curve, ArrivalCurve::Linear);
+    }
+
+    #[test]
+    fn follower_target_is_cleared_when_target_despawns() {
+        let mut app = physics_app();
+        let leader = app
+            .world_mut()
+            .spawn(SpatialBundle::from_transform(Transform::from_xyz(
+                100.0, 0.0, 0.0,
+            )))
+            .id();
+        let follower = app
+            .world_mut()
+            .spawn((
+                SpatialBundle::default(),
+                SteeringBundle::default(),
+                SteerSeek,
+                SteeringTargetEntity::new(leader),
+            ))
+            .id();
+
+        tick(&mut app);
+        assert!(position(&app, follower).x > 0.0);
+        assert!(app.world().get::<SteeringTargetEntity>(follower).is_some());
+
+        app.world_mut().despawn(leader);
+        tick(&mut app);
+        assert!(app.world().get::<SteeringTargetEntity>(follower).is_none());
+    }
+
+    #[test]
+    fn collision_avoidance_avoids_threats_on_path_only() {
+        let mut app = physics_app();
+        let host_entity = spawn_collider(&mut app, circle(8.0), Vec2::ZERO);
+        // close to the host, but the host moves away from it
+        spawn_collider(&mut app, circle(3.0), Vec2::new(-10.0, -10.0));
+        // far away, but right on the host's way
+        spawn_wall(&mut app, Vec2::new(90.0, 10.0), Vec2::new(8.0, 40.0));
+
+        let params = PhysicalParams::default();
+        let host = SteeringHost {
+            velocity: Vec2::new(params.max_velocity, 0.0),
+            ..default()
+        };
+        let mut avoidance = SteerCollisionAvoidance {
+            max_see_ahead: 100.0,
+            ..default()
+        };
+        let steering = with_hash(&mut app, |hash, colliders| {
+            avoidance.steer(
+                &Transform::default(),
+                &host,
+                colliders.get(host_entity).unwrap(),
+                host_entity,
+                colliders,
+                hash,
+                &params,
+            )
+        });
+
+        let expected = Vec2::new(1.0, -1.0).normalize() * avoidance.avoid_force;
+        assert!(steering.abs_diff_eq(expected, 1e-3), "{steering}");
+    }
+
+    #[test]
+    fn enemy_avoids_wall_between_it_and_target() {
+        const WALL_LAYER: i32 = 0b01;
+        const ENEMY_LAYER: i32 = 0b10;
+
+        let mut app = physics_app();
+        spawn_wall(&mut app, Vec2::new(80.0, 20.0), Vec2::new(16.0, 100.0));
+        let mut collider = circle(8.0);
+        collider.physics_layer = ENEMY_LAYER;
+        let enemy = spawn_collider(&mut app, collider, Vec2::ZERO);
+        app.world_mut().entity_mut(enemy).insert((
+            SteeringBundle::default(),
+            SteerSeek,
+            SteeringTargetVec2::new(Vec2::new(300.0, 0.0)),
+            SteerCollisionAvoidance {
+                max_see_ahead: 60.0,
+                ..default()
+            }
+            .with_layer_mask(WALL_LAYER),
+        ));
+
+        let mut sideways = Vec2::ZERO;
+        for _ in 0..20 {
+            tick(&mut app);
+            let avoidance = app.world().get::<SteerCollisionAvoidance>(enemy).unwrap();
+            if avoidance.avoidance != Vec2::ZERO {
+                sideways = avoidance.avoidance;
+            }
+        }
+
+        assert!(sideways.y < 0.0 && sideways.y.abs() > sideways.x.abs() * 0.5);
+        assert!(position(&app, enemy).y < 0.0);
+    }
+
+    #[test]
+    fn interpose_converges_to_midpoint() {
+        let mut app = physics_app();
+        let mut spawn_target = |position: Vec3| {
+            app.world_mut()
+                .spawn(SpatialBundle::from_transform(Transform::from_translation(
+                    position,
+                )))
+                .id()
+        };
+        let a = spawn_target(Vec3::new(-100.0, 100.0, 0.0));
+        let b = spawn_target(Vec3::new(300.0, 100.0, 0.0));
+        let host = app
+            .world_mut()
+            .spawn((
+                SpatialBundle::default(),
+                SteeringBundle::default(),
+                SteerInterpose::new(a, b),
+            ))
+            .id();
+
+        for _ in 0..600 {
+            tick(&mut app);
+        }
+
+        let position = position(&app, host);
+        assert!(
+            position.distance(Vec2::new(100.0, 100.0)) < 1.0,
+            "{position}"
+        );
+    }
+
+    #[test]
+    fn hiding_spot_is_behind_obstacle() {
+        const COVER_LAYER: i32 = 0b100;
+
+        let mut app = physics_app();
+        let threat = app
+            .world_mut()
+            .spawn(SpatialBundle::from_transform(Transform::from_xyz(
+                -100.0, 0.0, 0.0,
+            )))
+            .id();
+        let mut obstacle = circle(16.0);
+        obstacle.physics_layer = COVER_LAYER;
+        spawn_collider(&mut app, obstacle, Vec2::ZERO);
+        let host = app
+            .world_mut()
+            .spawn((
+                SpatialBundle::from_transform(Transform::from_xyz(60.0, 20.0, 0.0)),
+                SteeringBundle::default(),
+                SteerHide::new(threat, COVER_LAYER, 10.0),
+            ))
+            .id();
+
+        tick(&mut app);
+
+        let hiding_spot = app
+            .world()
+            .get::<SteerHide>(host)
+            .unwrap()
+            .hiding_spot()
+            .expect("no hiding spot found");
+        assert!(
+            hiding_spot.abs_diff_eq(Vec2::new(26.0, 0.0), 1e-3),
+            "{hiding_spot}"
+        );
+    }
+
+    #[test]
+    fn cursor_target_follows_synthetic_cursor() {
+        use bevy::{
+            render::{
+                camera::{camera_system, ManualTextureViews},
+                texture::Image,
+            },
+            window::{WindowCreated, WindowResized, WindowScaleFactorChanged},
+        };
+
+        // computing the camera's viewport the same way `RenderPlugin` does, but without rendering
+        let mut app = physics_app();
+        app.init_resource::<Time<Fixed>>()
+            .init_resource::<Assets<Image>>()
+            .init_resource::<ManualTextureViews>()
+            .add_event::<WindowCreated>()
+            .add_event::<WindowResized>()
+            .add_event::<WindowScaleFactorChanged>()
+            .add_event::<AssetEvent<Image>>()
+            .add_systems(
+                Update,
+                camera_system::<OrthographicProjection>.before(update_cursor_targets),
+            );
+
+        let host = app
+            .world_mut()
+            .spawn((
+                SpatialBundle::default(),
+                SteeringBundle::default(),
+                SteerSeek,
+                SteeringTargetCursor,
+            ))
+            .id();
+
+        // no window and camera yet, the target is left unchanged
+        app.world_mut().run_schedule(Update);
+        assert!(app.world().get::<SteeringTargetVec2>(host).is_none());
+
+        let mut window = Window {
+            resolution: (800.0, 600.0).into(),
+            ..default()
+        };
+        window.set_cursor_position(Some(Vec2::new(500.0, 200.0)));
+        let window = app.world_mut().spawn((window, PrimaryWindow)).id();
+        app.world_mut().spawn(Camera2dBundle::default());
+        app.world_mut().run_schedule(Update);
+
+        let cursor = app
+            .world()
+            .get::<SteeringTargetVec2>(host)
+            .unwrap()
+            .0
+            .unwrap();
+        assert!(
+            cursor.abs_diff_eq(Vec2::new(100.0, 100.0), 1e-3),
+            "{cursor}"
+        );
+
+        // the cursor left the window
+        app.world_mut()
+            .get_mut::<Window>(window)
+            .unwrap()
+            .set_cursor_position(None);
+        app.world_mut().run_schedule(Update);
+        let target = app.world().get::<SteeringTargetVec2>(host).unwrap().0;
+        assert_eq!(target, Some(cursor));
+    }
+
+    #[test]
+    fn target_reached_is_sent_once_per_arrival() {
+        let mut app = physics_app();
+        let host = app
+            .world_mut()
+            .spawn((
+                SpatialBundle::default(),
+                SteeringBundle::default(),
+                SteerArrival::default(),
+                SteeringTargetVec2::new(Vec2::new(200.0, 0.0)),
+                ArrivalDetection::default(),
+            ))
+            .id();
+
+        let mut reader = app
+            .world()
+            .resource::<Events<TargetReachedEvent>>()
+            .get_reader();
+        let mut arrivals = |app: &App| {
+            reader
+                .read(app.world().resource::<Events<TargetReachedEvent>>())
+                .map(|event| event.entity)
+                .collect::<Vec<_>>()
+        };
+
+        let mut events = vec![];
+        for _ in 0..600 {
+            tick(&mut app);
+            events.extend(arrivals(&app));
+        }
+
+        assert_eq!(events, vec![host]);
+        assert!(position(&app, host).distance(Vec2::new(200.0, 0.0)) < 8.0);
+        assert!(app
+            .world()
+            .get::<ArrivalDetection>(host)
+            .unwrap()
+            .is_reached());
     }
 
-    pub fn get_leader_ahead(&self, position: &Transform, host: &SteeringHost) -> Vec2 {
-        let dv = host.velocity.normalize_or_zero() * self.leader_behind_dist;
-        position.translation.xy() + dv
+    #[test]
+    fn disabled_host_keeps_behavior_state() {
+        let mut app = physics_app();
+        let threat = app
+            .world_mut()
+            .spawn(SpatialBundle::from_transform(Transform::from_xyz(
+                -100.0, 0.0, 0.0,
+            )))
+            .id();
+        spawn_collider(&mut app, circle(16.0), Vec2::new(50.0, 0.0));
+        let wander = SteerWander::default();
+        let host = app
+            .world_mut()
+            .spawn((
+                SpatialBundle::default(),
+                SteeringBundle::default(),
+                SteeringEnabled(false),
+                wander,
+                SteerHide::new(threat, i32::MAX, 10.0),
+                // the host already stands on its target
+                SteeringTargetVec2::new(Vec2::new(1.0, 0.0)),
+                ArrivalDetection::default(),
+            ))
+            .id();
+
+        for _ in 0..10 {
+            tick(&mut app);
+        }
+
+        let world = app.world();
+        let state = world.get::<SteerWander>(host).unwrap();
+        assert_eq!(state.wander_angle, wander.wander_angle);
+        assert!(world
+            .get::<SteerHide>(host)
+            .unwrap()
+            .hiding_spot()
+            .is_none());
+        assert!(!world.get::<ArrivalDetection>(host).unwrap().is_reached());
+        assert!(world.resource::<Events<TargetReachedEvent>>().is_empty());
+        assert_eq!(
+            world.get::<SteeringHost>(host).unwrap().velocity,
+            Vec2::ZERO
+        );
+        assert_eq!(position(&app, host), Vec2::ZERO);
+    }
+
+    #[test]
+    fn world_origin_is_valid_target() {
+        let mut app = physics_app();
+        let start = Vec2::new(100.0, 50.0);
+        let host = app
+            .world_mut()
+            .spawn((
+                SpatialBundle::from_transform(Transform::from_xyz(start.x, start.y, 0.0)),
+                SteeringBundle::default(),
+                SteerArrival::default(),
+                SteeringTargetVec2::new(Vec2::ZERO),
+            ))
+            .id();
+
+        tick(&mut app);
+        assert!(position(&app, host).length() < start.length());
+
+        for _ in 0..600 {
+            tick(&mut app);
+        }
+        assert!(position(&app, host).length() < 1.0);
+    }
+
+    #[test]
+    fn separation_pushes_canonical_bundles_apart() {
+        let mut app = physics_app();
+        let mut spawn_agent = |x: f32| {
+            let agent = spawn_collider(&mut app, circle(4.0), Vec2::new(x, 0.0));
+            app.world_mut()
+                .entity_mut(agent)
+                .insert((SteeringBundle::default(), SteerSeparation::default()));
+            agent
+        };
+        let left = spawn_agent(-6.0);
+        let right = spawn_agent(6.0);
+
+        for _ in 0..10 {
+            tick(&mut app);
+        }
+
+        assert!(position(&app, left).x < -6.0);
+        assert!(position(&app, right).x > 6.0);
+        assert_eq!(position(&app, left).y, 0.0);
+    }
+
+    #[test]
+    fn brake_stops_host_in_expected_ticks() {
+        let mut app = physics_app();
+        let decel = 400.0;
+        let speed = 200.0;
+        let host = app
+            .world_mut()
+            .spawn((
+                SpatialBundle::default(),
+                SteeringBundle {
+                    physics_params: PhysicalParams {
+                        friction: 1.0,
+                        ..default()
+                    },
+                    ..default()
+                },
+                SteerBrake::new(decel),
+            ))
+            .id();
+        app.world_mut()
+            .get_mut::<SteeringHost>(host)
+            .unwrap()
+            .apply_impulse(Vec2::new(speed, 0.0));
+
+        // 200 / (400 * 1/64) = 32 ticks
+        let expected_ticks = (speed / (decel * TICK.as_secs_f32())).ceil() as usize;
+        assert_eq!(expected_ticks, 32);
+        for ticks in 1..=expected_ticks {
+            tick(&mut app);
+            let velocity = app.world().get::<SteeringHost>(host).unwrap().velocity;
+            assert!(velocity.x >= 0.0 && velocity.y == 0.0, "{velocity}");
+            if ticks < expected_ticks - 1 {
+                assert!(velocity.x > 1.0, "stopped too early: {ticks} {velocity}");
+            }
+        }
+
+        let velocity = app.world().get::<SteeringHost>(host).unwrap().velocity;
+        assert!(velocity.length() < 1e-3, "{velocity}");
     }
 
-    pub fn is_on_leader_sight(&self, leader_position: &Transform, position: &Transform) -> bool {
-        (self.ahead - position.translation.xy()).length() <= self.leader_sight_radius
-            || (leader_position.translation.xy() - position.translation.xy()).length() <= self.leader_sight_radius
+    #[test]
+    fn brake_overrides_seek() {
+        let mut app = physics_app();
+        let host = app
+            .world_mut()
+            .spawn((
+                SpatialBundle::default(),
+                SteeringBundle::default(),
+                SteerSeek,
+                SteeringTargetVec2::new(Vec2::new(1000.0, 0.0)),
+            ))
+            .id();
+        for _ in 0..30 {
+            tick(&mut app);
+        }
+
+        app.world_mut()
+            .entity_mut(host)
+            .insert(SteerBrake::new(400.0));
+        let mut prev_speed = app.world().get::<SteeringHost>(host).unwrap().speed();
+        assert!(prev_speed > 0.0);
+        for _ in 0..300 {
+            tick(&mut app);
+            let velocity = app.world().get::<SteeringHost>(host).unwrap().velocity;
+            assert!(velocity.x >= 0.0, "{velocity}");
+            assert!(velocity.length() <= prev_speed, "{velocity}");
+            prev_speed = velocity.length();
+        }
+
+        assert!(prev_speed < 1e-3, "{prev_speed}");
+    }
+
+    #[test]
+    fn queue_slows_down_without_compounding() {
+        let mut app = physics_app();
+        let params = PhysicalParams {
+            friction: 1.0,
+            ..default()
+        };
+        let host = spawn_collider(&mut app, circle(4.0), Vec2::ZERO);
+        app.world_mut().entity_mut(host).insert((
+            SteeringBundle {
+                physics_params: params,
+                ..default()
+            },
+            SteerQueue::default(),
+        ));
+        // close enough to make the host slow down harder
+        spawn_collider(&mut app, circle(4.0), Vec2::new(16.0, 0.0));
+        app.world_mut()
+            .get_mut::<SteeringHost>(host)
+            .unwrap()
+            .apply_impulse(Vec2::new(100.0, 0.0));
+
+        let max_change = params.max_force / params.mass;
+        let mut speeds = vec![100.0];
+        for _ in 0..5 {
+            tick(&mut app);
+            let velocity = app.world().get::<SteeringHost>(host).unwrap().velocity;
+            assert!(velocity.x >= 0.0 && velocity.y == 0.0, "{velocity}");
+            speeds.push(velocity.x);
+        }
+
+        // the first tick isn't cut to 30 by the multiplier, but slows down by `max_force`
+        assert!(
+            (speeds[1] - (100.0 - max_change)).abs() < 1e-3,
+            "{speeds:?}"
+        );
+        for pair in speeds.windows(2) {
+            assert!(pair[1] < pair[0], "{speeds:?}");
+            assert!(pair[0] - pair[1] <= max_change + 1e-3, "{speeds:?}");
+        }
+    }
+
+    #[test]
+    fn pursuit_leads_moving_host() {
+        let hunter = Transform::default();
+        let params = PhysicalParams::default();
+        let prey_transform = Transform::from_xyz(100.0, 0.0, 0.0);
+        let prey_host = SteeringHost {
+            velocity: Vec2::new(0.0, 100.0),
+            ..default()
+        };
+
+        let leading = SteerPursuit.steer(
+            &hunter,
+            &SteeringHost::default(),
+            &params,
+            &(&prey_transform, &prey_host),
+        );
+        // 100 units at 250 units per second
+        let expected = Vec2::new(100.0, 40.0).normalize() * params.max_velocity;
+        assert!(leading.abs_diff_eq(expected, 1e-3), "{leading}");
+
+        // only the position of a target without velocity is known
+        let position_only =
+            SteerPursuit.steer(&hunter, &SteeringHost::default(), &params, &prey_transform);
+        assert!(position_only.abs_diff_eq(Vec2::new(params.max_velocity, 0.0), 1e-3));
+    }
+
+    #[test]
+    fn zero_max_velocity_gives_finite_steering() {
+        let params = PhysicalParams {
+            max_velocity: 0.0,
+            ..default()
+        };
+        assert_eq!(params.prediction_time(100.0), 0.0);
+
+        let position = Transform::default();
+        let host = SteeringHost::default();
+        let target = SteeringTargetFull {
+            position: Vec2::new(100.0, 0.0),
+            velocity: Vec2::new(0.0, 50.0),
+        };
+        let other = SteeringTargetFull {
+            position: Vec2::new(-100.0, 0.0),
+            velocity: Vec2::new(0.0, -50.0),
+        };
+        for steering in [
+            SteerPursuit.steer(&position, &host, &params, &target),
+            SteerEvade.steer(&position, &host, &params, &target),
+            SteerSeek.steer(&position, &host, &params, &target),
+            SteerArrival::default().steer(&position, &host, &params, &target),
+            SteerInterpose::new(Entity::PLACEHOLDER, Entity::PLACEHOLDER)
+                .steer(&position, &host, &params, &target, &other),
+        ] {
+            assert!(steering.is_finite(), "{steering}");
+        }
+    }
+
+    #[test]
+    fn raycast_avoidance_aligns_with_hit_normal() {
+        let mut app = physics_app();
+        spawn_wall(&mut app, Vec2::new(30.0, 0.0), Vec2::new(16.0, 200.0));
+        let host = spawn_collider(&mut app, circle(4.0), Vec2::ZERO);
+        let avoidance = SteerRaycastAvoidance::default();
+
+        let steer = |app: &mut App, velocity: Vec2| {
+            let host_state = SteeringHost {
+                velocity,
+                ..default()
+            };
+            with_hash(app, |hash, colliders| {
+                let collider = colliders.get(host).unwrap();
+                avoidance.steer(&host_state, collider, host, colliders, hash)
+            })
+        };
+
+        // heading straight at the wall, its face is 22 units ahead
+        let head_on = steer(&mut app, Vec2::new(100.0, 0.0));
+        let expected = avoidance.avoid_force * (1.0 - 22.0 / avoidance.feeler_length);
+        assert!(
+            head_on.abs_diff_eq(Vec2::new(-expected, 0.0), 1e-3),
+            "{head_on}"
+        );
+
+        // heading at the wall at an angle, the push is still along the wall's normal
+        let oblique = steer(&mut app, Vec2::new(100.0, 40.0));
+        assert!(oblique.x < 0.0, "{oblique}");
+        assert!(oblique.y.abs() < 1e-4, "{oblique}");
+
+        // heading away from the wall
+        assert_eq!(steer(&mut app, Vec2::new(-100.0, 0.0)), Vec2::ZERO);
     }
 }
- */
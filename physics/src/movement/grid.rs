@@ -0,0 +1,136 @@
+use bevy::prelude::*;
+
+use crate::{
+    prelude::{colliders::Collider, spatial_hash::SpatialHash},
+    MovementCalculateEvent,
+};
+
+/// Moves an entity cell by cell, one cell per `FixedUpdate` tick, running collision
+/// resolution at each step. Movement along the x axis goes first, then along the y axis.
+///
+/// Before every step the destination cell is checked against the colliders the entity's
+/// `Collider` resolves collisions with (e.g. `RigidBodyStatic` walls spawned by worldgen).
+/// If the destination is blocked, all the pending cells are dropped and the entity
+/// stays in the last free cell.
+///
+/// Doesn't require `SteeringHost`. Don't combine with steering, since both of them move the entity.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Reflect)]
+pub struct GridMovement {
+    /// Size of a single cell.
+    pub cell_size: Vec2,
+    pending: IVec2,
+}
+
+impl GridMovement {
+    pub fn new(cell_size: Vec2) -> Self {
+        Self {
+            cell_size,
+            pending: IVec2::ZERO,
+        }
+    }
+
+    /// Requests to move by the specified amount of cells.
+    /// Adds up to the cells which are still pending.
+    pub fn move_by(&mut self, cells: IVec2) {
+        self.pending += cells;
+    }
+
+    /// Drops all the pending cells.
+    pub fn stop(&mut self) {
+        self.pending = IVec2::ZERO;
+    }
+
+    /// Gets the cells which are still pending.
+    pub fn pending(&self) -> IVec2 {
+        self.pending
+    }
+
+    /// Checks whether there are any pending cells.
+    pub fn is_moving(&self) -> bool {
+        self.pending != IVec2::ZERO
+    }
+
+    fn next_step(&self) -> IVec2 {
+        if self.pending.x != 0 {
+            IVec2::new(self.pending.x.signum(), 0)
+        } else {
+            IVec2::new(0, self.pending.y.signum())
+        }
+    }
+}
+
+pub(crate) fn grid_movement(
+    mut movers: Query<(&mut GridMovement, Option<&Collider>, Entity)>,
+    colliders: Query<&Collider>,
+    spatial_hash: Res<SpatialHash>,
+    mut evt_movement_calc: EventWriter<MovementCalculateEvent>,
+) {
+    for (mut mover, collider, entity) in &mut movers {
+        if !mover.is_moving() {
+            continue;
+        }
+
+        let step = mover.next_step();
+        let motion = step.as_vec2() * mover.cell_size;
+
+        if let Some(collider) = collider {
+            if is_blocked(collider, entity, motion, &colliders, &spatial_hash) {
+                mover.stop();
+                continue;
+            }
+        }
+
+        mover.pending -= step;
+        evt_movement_calc.send(MovementCalculateEvent {
+            entity,
+            movement: motion,
+        });
+    }
+}
+
+fn is_blocked(
+    collider: &Collider,
+    entity: Entity,
+    motion: Vec2,
+    colliders: &Query<&Collider>,
+    spatial_hash: &SpatialHash,
+) -> bool {
+    if collider.is_trigger {
+        return false;
+    }
+
+    let bounds = collider.bounds().swept(motion);
+
+    let neighbors = spatial_hash.aabb_broadphase(colliders, bounds, Some(entity), None);
+    neighbors.into_iter().any(|neighbor_entity| {
+        let neighbor = colliders.get(neighbor_entity).unwrap();
+        !neighbor.is_trigger
+            && collider.resolves_with(neighbor)
+            && collider.collides_with_motion(neighbor, motion).is_some()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    #[test]
+    fn grid_mover_stops_one_cell_short_of_wall() {
+        let mut app = physics_app();
+        let cell_size = Vec2::splat(16.0);
+        spawn_wall(&mut app, Vec2::new(80.0, 0.0), cell_size);
+        let mover = spawn_collider(&mut app, rect(14.0, 14.0), Vec2::ZERO);
+        let mut grid = GridMovement::new(cell_size);
+        grid.move_by(IVec2::new(10, 0));
+        app.world_mut().entity_mut(mover).insert(grid);
+
+        for _ in 0..10 {
+            tick(&mut app);
+        }
+
+        assert_eq!(position(&app, mover), Vec2::new(64.0, 0.0));
+        let grid = app.world().get::<GridMovement>(mover).unwrap();
+        assert!(!grid.is_moving());
+    }
+}
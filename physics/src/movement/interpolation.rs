@@ -0,0 +1,102 @@
+use bevy::prelude::*;
+
+/// Smooths rendering of an entity moved in `FixedUpdate` by interpolating its `Transform`
+/// between the two last fixed-step positions every frame.
+///
+/// The interpolated `Transform` lags behind the physics by up to one fixed step.
+/// The authoritative position is restored before every fixed step, so the collision
+/// resolution, the `Collider` and the `SpatialHash` always use the actual fixed-step position.
+/// Because of that, teleporting the entity by writing its `Transform` outside of `FixedUpdate`
+/// won't work while the component is present.
+///
+/// # Example
+///
+/// Add the component along with the ones moving the entity in `FixedUpdate`,
+/// e.g. a steering behavior:
+/// ```
+/// use physics::prelude::*;
+/// use behaviors::SteerSeek;
+/// use interpolation::PhysicsInterpolation;
+/// use steering::{SteeringBundle, SteeringTargetVec2};
+///
+/// fn spawn_enemy(mut commands: Commands) {
+///     commands.spawn((
+///         SpatialBundle::default(),
+///         SteeringBundle::default(),
+///         SteerSeek,
+///         SteeringTargetVec2::new(Vec2::new(100.0, 0.0)),
+///         // Steered in `FixedUpdate`, but drawn smoothly at any frame rate
+///         PhysicsInterpolation::default(),
+///     ));
+/// }
+///
+/// let mut app = App::new();
+/// app.add_plugins((MinimalPlugins, PhysicsPlugin::default()))
+///     .add_systems(Startup, spawn_enemy);
+/// app.update();
+/// ```
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Reflect)]
+pub struct PhysicsInterpolation {
+    previous: Vec2,
+    current: Vec2,
+}
+
+impl PhysicsInterpolation {
+    /// Gets the position of the previous fixed step.
+    pub fn previous(&self) -> Vec2 {
+        self.previous
+    }
+
+    /// Gets the authoritative position of the last fixed step.
+    pub fn current(&self) -> Vec2 {
+        self.current
+    }
+}
+
+pub(crate) fn on_interpolation_added(
+    trigger: Trigger<OnAdd, PhysicsInterpolation>,
+    mut query: Query<(&mut PhysicsInterpolation, &Transform)>,
+) {
+    if let Ok((mut interpolation, transform)) = query.get_mut(trigger.entity()) {
+        interpolation.previous = transform.translation.xy();
+        interpolation.current = transform.translation.xy();
+    }
+}
+
+pub(crate) fn on_interpolation_removed(
+    trigger: Trigger<OnRemove, PhysicsInterpolation>,
+    mut query: Query<(&PhysicsInterpolation, &mut Transform)>,
+) {
+    if let Ok((interpolation, mut transform)) = query.get_mut(trigger.entity()) {
+        transform.translation.x = interpolation.current.x;
+        transform.translation.y = interpolation.current.y;
+    }
+}
+
+/// Moves the interpolated entities back to their authoritative positions before the fixed step.
+pub(crate) fn restore_fixed_positions(mut query: Query<(&PhysicsInterpolation, &mut Transform)>) {
+    for (interpolation, mut transform) in &mut query {
+        transform.translation.x = interpolation.current.x;
+        transform.translation.y = interpolation.current.y;
+    }
+}
+
+/// Stores the positions produced by the fixed step.
+pub(crate) fn store_fixed_positions(mut query: Query<(&mut PhysicsInterpolation, &Transform)>) {
+    for (mut interpolation, transform) in &mut query {
+        interpolation.previous = interpolation.current;
+        interpolation.current = transform.translation.xy();
+    }
+}
+
+pub(crate) fn interpolate_positions(
+    mut query: Query<(&PhysicsInterpolation, &mut Transform)>,
+    time: Res<Time<Fixed>>,
+) {
+    let t = time.overstep_fraction();
+    for (interpolation, mut transform) in &mut query {
+        let position = interpolation.previous.lerp(interpolation.current, t);
+        transform.translation.x = position.x;
+        transform.translation.y = position.y;
+    }
+}
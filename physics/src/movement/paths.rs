@@ -57,6 +57,41 @@ impl SteerPath {
         }
     }
 
+    /// Creates a path from world-space waypoints (e.g. a pathfinding result).
+    /// The first waypoint becomes the first target and the last one is the destination.
+    /// `max_nodes` is raised to fit all the waypoints.
+    pub fn from_waypoints(
+        waypoints: impl IntoIterator<Item = Vec2>,
+        arrival_radius: f32,
+        target_radius: f32,
+    ) -> Self {
+        let nodes: VecDeque<SteerPathNode> = waypoints
+            .into_iter()
+            .map(|position| SteerPathNode::new(position, arrival_radius, target_radius))
+            .collect();
+
+        Self {
+            max_nodes: nodes.len().max(SteerPath::default().max_nodes),
+            last_added: nodes.back().copied(),
+            nodes,
+        }
+    }
+
+    /// Creates a path going through the centers of the grid cells (e.g. an A* path over
+    /// a tile grid), where the cell `(0, 0)` starts at `origin`.
+    /// A node counts as reached within half of a cell and the arrival slowing starts one cell away.
+    pub fn from_grid_path(cells: &[IVec2], cell_size: Vec2, origin: Vec2) -> Self {
+        let cell_radius = cell_size.min_element() / 2.0;
+
+        Self::from_waypoints(
+            cells
+                .iter()
+                .map(|cell| origin + (cell.as_vec2() + 0.5) * cell_size),
+            cell_radius * 2.0,
+            cell_radius,
+        )
+    }
+
     pub fn clear(&mut self) {
         self.nodes.clear();
     }
@@ -143,27 +178,40 @@ pub enum PathFollowingMode {
 #[derive(Debug, Reflect)]
 pub struct SteerPathFollowing {
     pub mode: PathFollowingMode,
+    /// Distance before a corner at which the steering target starts sliding from the current
+    /// node towards the next one, so the host rounds the corners instead of turning sharply
+    /// at the nodes. The target reaches the next node once the host passes the current one,
+    /// i.e. crosses the bisector of the corner, and the next node becomes the current one.
+    /// U-turns aren't rounded. `None` steers straight to the current node and switches
+    /// to the next one once within the node's `target_radius`.
+    pub look_ahead: Option<f32>,
     cur_node_index: usize,
     path_dir: i32,
+    /// Position of the last passed node of a one-way path, gives the direction of the corner.
+    prev_position: Option<Vec2>,
 }
 
 impl Default for SteerPathFollowing {
     fn default() -> Self {
         Self {
             mode: PathFollowingMode::OneWay,
+            look_ahead: None,
             cur_node_index: 0,
             path_dir: -1,
+            prev_position: None,
         }
     }
 }
 
 impl SteerPathFollowing {
     pub fn new(mode: PathFollowingMode) -> Self {
-        Self {
-            mode,
-            cur_node_index: 0,
-            path_dir: -1,
-        }
+        Self { mode, ..default() }
+    }
+
+    /// Enables the corner smoothing, see `look_ahead`.
+    pub fn with_look_ahead(mut self, look_ahead: f32) -> Self {
+        self.look_ahead = Some(look_ahead);
+        self
     }
 
     pub fn steer<F>(
@@ -188,6 +236,63 @@ impl SteerPathFollowing {
         dist <= target.target_radius
     }
 
+    /// Gets the signed distance the host is ahead of the node along the corner at it,
+    /// or `None` if the corner isn't rounded.
+    fn corner_progress(
+        &self,
+        position: Vec2,
+        prev: Option<Vec2>,
+        node: &SteerPathNode,
+        next: Option<&SteerPathNode>,
+    ) -> Option<f32> {
+        self.look_ahead?;
+        let direction = corner_direction(prev, node.position, next?.position)?;
+        Some((position - node.position).dot(direction))
+    }
+
+    /// Checks whether the host is done with the node and should switch to the next one.
+    fn passed(
+        &self,
+        position: Vec2,
+        prev: Option<Vec2>,
+        node: &SteerPathNode,
+        next: Option<&SteerPathNode>,
+    ) -> bool {
+        self.within_target(position, node)
+            || self
+                .corner_progress(position, prev, node, next)
+                .is_some_and(|progress| progress >= 0.0)
+    }
+
+    /// Blends the current node with the next one by the progress of the host through the corner.
+    /// Returns the current node as is without `look_ahead` or the next node.
+    fn smoothed(
+        &self,
+        position: Vec2,
+        prev: Option<Vec2>,
+        node: SteerPathNode,
+        next: Option<SteerPathNode>,
+    ) -> SteerPathNode {
+        let (Some(look_ahead), Some(next)) = (self.look_ahead, next) else {
+            return node;
+        };
+        let Some(progress) = self.corner_progress(position, prev, &node, Some(&next)) else {
+            return node;
+        };
+        if look_ahead <= 0.0 {
+            return node;
+        }
+
+        // The target is at the next node by the time the host passes the current one,
+        // so it doesn't jump when the host switches to the next node
+        let t = ((look_ahead + progress) / look_ahead).clamp(0.0, 1.0);
+
+        SteerPathNode {
+            position: node.position.lerp(next.position, t),
+            ..node
+        }
+    }
+
     fn one_way<F>(
         &mut self,
         position: &Transform,
@@ -198,51 +303,203 @@ impl SteerPathFollowing {
     where
         F: Fn(SteerPathNode) -> Vec2,
     {
-        if let Some(target) = path.get_target() {
-            if self.within_target(position.translation.xy(), &target) {
+        let position = position.translation.xy();
+
+        let Some(mut target) = path.get_target() else {
+            self.prev_position = None;
+            return -host.velocity;
+        };
+
+        if self.look_ahead.is_none() {
+            if self.within_target(position, &target) {
                 path.remove_target();
             }
-
             return steering_fn(target);
         }
 
-        -host.velocity
+        if self.passed(position, self.prev_position, &target, path.nodes.get(1)) {
+            path.remove_target();
+            self.prev_position = Some(target.position);
+            target = path.get_target().unwrap_or(target);
+        }
+        let next = path.nodes.get(1).copied();
+
+        steering_fn(self.smoothed(position, self.prev_position, target, next))
+    }
+
+    /// Gets the previous and the next nodes of the current node of a patrolled path.
+    fn patrol_neighbors(&self, path: &SteerPath) -> (Option<Vec2>, Option<SteerPathNode>) {
+        let len = path.nodes.len() as i32;
+        let cur = self.cur_node_index as i32;
+        let in_range = |index: i32| (0..len).contains(&index).then_some(index as usize);
+
+        let prev = in_range(cur - self.path_dir).map(|index| path.nodes[index].position);
+        let next = in_range(cur + self.path_dir)
+            .or_else(|| in_range(cur - self.path_dir))
+            .map(|index| path.nodes[index]);
+
+        (prev, next)
     }
 
     fn patrol<F>(&mut self, position: &Transform, path: &mut SteerPath, steering_fn: F) -> Vec2
     where
         F: Fn(SteerPathNode) -> Vec2,
     {
+        let position = position.translation.xy();
         let mut node = path.index(self.cur_node_index);
-        let mut index: i32 = 0;
+        let (prev, next) = self.patrol_neighbors(path);
 
-        if self.within_target(position.translation.xy(), node) {
-            index += self.path_dir;
+        if self.passed(position, prev, node, next.as_ref()) {
+            let mut index = self.cur_node_index as i32 + self.path_dir;
 
             if index >= path.nodes.len() as i32 || index < 0 {
                 self.path_dir *= -1;
-                index += self.path_dir;
+                index += 2 * self.path_dir;
             }
 
-            self.cur_node_index = index as usize;
+            self.cur_node_index = index.clamp(0, path.nodes.len() as i32 - 1) as usize;
 
             node = path.index(self.cur_node_index);
         }
 
-        steering_fn(*node)
+        let (prev, next) = self.patrol_neighbors(path);
+
+        steering_fn(self.smoothed(position, prev, *node, next))
     }
 
     fn looped<F>(&mut self, position: &Transform, path: &mut SteerPath, steering_fn: F) -> Vec2
     where
         F: Fn(SteerPathNode) -> Vec2,
     {
-        let mut node = path.index(self.cur_node_index % path.len());
-
-        if self.within_target(position.translation.xy(), node) {
+        let position = position.translation.xy();
+        let len = path.len();
+        let neighbors = |index: usize| {
+            (len > 1).then(|| {
+                (
+                    path.index((index + len - 1) % len).position,
+                    *path.index((index + 1) % len),
+                )
+            })
+        };
+
+        let mut node = path.index(self.cur_node_index % len);
+        let (prev, next) = neighbors(self.cur_node_index % len).unzip();
+
+        if self.passed(position, prev, node, next.as_ref()) {
             self.cur_node_index += 1;
-            node = path.index(self.cur_node_index % path.len());
+            node = path.index(self.cur_node_index % len);
         }
 
-        steering_fn(*node)
+        let (prev, next) = neighbors(self.cur_node_index % len).unzip();
+
+        steering_fn(self.smoothed(position, prev, *node, next))
+    }
+}
+
+/// Gets the direction across the corner at `node`, the host has passed the node once it's
+/// ahead of the node along it. Without `prev` the path is considered going straight through
+/// the node. Returns `None` for the U-turns.
+fn corner_direction(prev: Option<Vec2>, node: Vec2, next: Vec2) -> Option<Vec2> {
+    let outgoing = (next - node).normalize_or_zero();
+    let incoming = prev.map_or(outgoing, |prev| (node - prev).normalize_or_zero());
+    let direction = incoming + outgoing;
+
+    (direction.length_squared() > 1e-4).then(|| direction.normalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn path_from_waypoints_keeps_order() {
+        let waypoints = [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(10.0, 0.0),
+            Vec2::new(10.0, 10.0),
+        ];
+        let mut path = SteerPath::from_waypoints(waypoints, 8.0, 2.0);
+
+        assert_eq!(path.len(), 3);
+        assert_eq!(path.last_added().unwrap().position, waypoints[2]);
+        for waypoint in waypoints {
+            let node = path.remove_target().unwrap();
+            assert_eq!(node.position, waypoint);
+            assert_eq!(node.arrival_radius, 8.0);
+            assert_eq!(node.target_radius, 2.0);
+        }
+        assert!(path.is_empty());
+
+        let long = SteerPath::from_waypoints((0..100).map(|i| Vec2::splat(i as f32)), 8.0, 2.0);
+        assert_eq!(long.len(), 100);
+        assert_eq!(long.max_nodes, 100);
+    }
+
+    #[test]
+    fn path_from_grid_goes_through_cell_centers() {
+        let cells = [IVec2::new(0, 0), IVec2::new(1, 0), IVec2::new(1, -1)];
+        let path = SteerPath::from_grid_path(&cells, Vec2::new(16.0, 32.0), Vec2::new(100.0, 0.0));
+
+        let positions: Vec<Vec2> = path.iter().map(|node| node.position).collect();
+        assert_eq!(
+            positions,
+            vec![
+                Vec2::new(108.0, 16.0),
+                Vec2::new(124.0, 16.0),
+                Vec2::new(124.0, -16.0)
+            ]
+        );
+        assert_eq!(path.index(0).target_radius, 8.0);
+        assert_eq!(path.index(0).arrival_radius, 16.0);
+    }
+
+    /// Follows an L-shaped path starting at the origin with a host moving by one unit per step
+    /// towards the steering target and returns the max change of the direction between two steps in radians.
+    fn max_turn_around_corner(mut following: SteerPathFollowing) -> f32 {
+        let mut path = SteerPath::from_waypoints(
+            [Vec2::ZERO, Vec2::new(100.0, 0.0), Vec2::new(100.0, 100.0)],
+            8.0,
+            2.0,
+        );
+        let host = SteeringHost::default();
+        let mut position = Vec2::ZERO;
+        let mut prev_direction = None;
+        let mut max_turn: f32 = 0.0;
+        for _ in 0..300 {
+            let transform = Transform::from_xyz(position.x, position.y, 0.0);
+            let steering = following.steer(&transform, &host, &mut path, |node| {
+                node.position - position
+            });
+            if path.is_empty() {
+                break;
+            }
+            // the host is right at the node which has just been switched from
+            let Some(direction) = steering.try_normalize() else {
+                continue;
+            };
+
+            if let Some(prev_direction) = prev_direction {
+                max_turn = max_turn.max(direction.angle_between(prev_direction).abs());
+            }
+            prev_direction = Some(direction);
+            position += direction;
+        }
+
+        assert!(
+            position.distance(Vec2::new(100.0, 100.0)) < 3.0,
+            "{position}"
+        );
+        max_turn
+    }
+
+    #[test]
+    fn look_ahead_turns_continuously_through_corner() {
+        let snap = max_turn_around_corner(SteerPathFollowing::new(PathFollowingMode::OneWay));
+        assert!(snap > 1.0, "{snap}");
+
+        let smooth = max_turn_around_corner(
+            SteerPathFollowing::new(PathFollowingMode::OneWay).with_look_ahead(30.0),
+        );
+        assert!(smooth < 0.15, "{smooth}");
     }
 }
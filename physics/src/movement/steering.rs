@@ -19,9 +19,31 @@ impl SteeringTarget for Transform {
     }
 }
 
+/// Another host as a target: the position is taken from its `Transform`
+/// and the velocity from its `SteeringHost`, so the predicting behaviors
+/// (`SteerPursuit`, `SteerEvade`) can lead it.
+impl SteeringTarget for (&Transform, &SteeringHost) {
+    fn position(&self) -> Vec2 {
+        self.0.translation.xy()
+    }
+
+    fn velocity(&self) -> Vec2 {
+        self.1.velocity
+    }
+}
+
 /// A component that stores only the position part of the `SteeringTarget` trait.
+/// `None` means there is no target and the behaviors don't steer the host at all,
+/// any position including `Vec2::ZERO` is a valid target.
 #[derive(Component, Debug, Default)]
-pub struct SteeringTargetVec2(pub Vec2);
+pub struct SteeringTargetVec2(pub Option<Vec2>);
+
+impl SteeringTargetVec2 {
+    /// Creates the component targeting `position`.
+    pub fn new(position: Vec2) -> Self {
+        Self(Some(position))
+    }
+}
 
 /// A component that stores both `position` and `velocity` of the `SteeringTarget` trait.
 #[derive(Component, Debug, Default)]
@@ -43,21 +65,56 @@ impl SteeringTarget for SteeringTargetFull {
 /// A component that stores an `Entity` that is served as a target.
 /// Implements the `SteeringTarget` trait, but `position()` and `velocity()` always return `Vec2::ZERO`.
 /// For actual values you'll need to query them: `Transform` for position and `SteeringHost` for velocity.
+/// The target entity is only required to have a `Transform`, its velocity is zero
+/// if it doesn't have a `SteeringHost`.
+/// The component is removed automatically once the target entity is despawned.
 #[derive(Component, Debug)]
-pub struct SteeringTargetEntity(pub Entity);
+pub struct SteeringTargetEntity {
+    pub entity: Entity,
+    /// Whether the target's velocity is passed to the behaviors, letting `SteerPursuit`
+    /// and `SteerEvade` predict its movement. Defaults to `true`.
+    pub predict_velocity: bool,
+}
 
-impl SteeringTarget for SteeringTargetEntity {
-    fn position(&self) -> Vec2 {
-        Vec2::ZERO
+impl SteeringTargetEntity {
+    /// Targets the entity along with its velocity.
+    pub fn new(entity: Entity) -> Self {
+        Self {
+            entity,
+            predict_velocity: true,
+        }
+    }
+
+    /// Targets only the position of the entity, as if it was standing still.
+    pub fn position_only(entity: Entity) -> Self {
+        Self {
+            entity,
+            predict_velocity: false,
+        }
     }
 }
 
-impl SteeringTarget for SteeringTargetVec2 {
+impl SteeringTarget for SteeringTargetEntity {
     fn position(&self) -> Vec2 {
-        self.0
+        Vec2::ZERO
     }
 }
 
+/// A marker component making the entity's target follow the cursor.
+/// Every frame the cursor's world position (resolved via the primary window and the active camera)
+/// is written into the entity's `SteeringTargetVec2`, which is inserted if it's missing.
+/// If there is no camera or the cursor is outside of the window, the target is left unchanged.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use physics::prelude::{behaviors::SteerSeek, steering::*};
+/// # fn spawn_follower(mut commands: Commands) {
+/// commands.spawn((SteerSeek, SteeringTargetCursor, SteeringBundle::default()));
+/// # }
+/// ```
+#[derive(Component, Debug, Default, Clone, Copy)]
+pub struct SteeringTargetCursor;
+
 #[derive(Component, Debug, Clone, Copy, PartialEq, Reflect)]
 pub struct PhysicalParams {
     /// Determines how fast an object can move. This value is multiplied by delta time, so it
@@ -75,6 +132,23 @@ pub struct PhysicalParams {
     /// Should be in range [0, 1] where 0 - instant stop, 1 - no deceleration at all.
     /// Defaults to `0.98`.
     pub friction: f32,
+    /// Hard limit of the velocity applied every tick.
+    /// When `None`, steering still can't accelerate an object above `max_velocity`,
+    /// but the speed gained from external impulses (knockbacks, bounces) is kept
+    /// and only decays via `friction`. When `Some`, the velocity is always truncated
+    /// to the specified value.
+    /// Defaults to `None`.
+    pub speed_limit: Option<f32>,
+    /// Maximum angular speed of the velocity's direction in radians per second.
+    /// When `Some`, the velocity can't turn faster than this value, so the object moves
+    /// along smooth arcs instead of pivoting instantly. The speed itself is not affected.
+    /// Defaults to `None`, which means the direction can change instantly.
+    pub max_turn_rate: Option<f32>,
+    /// Maximum change of the velocity per second caused by the steering, applied after
+    /// the steering force is truncated by `max_force` and divided by `mass`.
+    /// Lets the responsiveness be tuned directly, the same for any `mass` and tick rate.
+    /// Defaults to `None`, which means only `max_force` limits the steering.
+    pub max_acceleration: Option<f32>,
 }
 
 impl Default for PhysicalParams {
@@ -84,10 +158,30 @@ impl Default for PhysicalParams {
             max_force: 150.0,
             mass: 4.0,
             friction: 0.9,
+            speed_limit: None,
+            max_turn_rate: None,
+            max_acceleration: None,
         }
     }
 }
 
+impl PhysicalParams {
+    /// Upper limit of `prediction_time`, so the far targets aren't predicted
+    /// unreasonably far into the future.
+    pub const MAX_PREDICTION_TIME: f32 = 4.0;
+
+    /// Gets the time in seconds it takes to cover `distance` at `max_velocity`, used by
+    /// the behaviors predicting the targets' future positions. Clamped to `MAX_PREDICTION_TIME`.
+    /// Returns `0.0` if `max_velocity` is not positive, since the host doesn't move at all.
+    pub fn prediction_time(&self, distance: f32) -> f32 {
+        if self.max_velocity <= 0.0 || !distance.is_finite() {
+            return 0.0;
+        }
+
+        (distance / self.max_velocity).min(Self::MAX_PREDICTION_TIME)
+    }
+}
+
 /// Represents a `Component` which stores movement data.
 #[derive(Component, Debug, Default, Clone, PartialEq, Reflect)]
 pub struct SteeringHost {
@@ -107,6 +201,113 @@ impl SteeringHost {
     pub fn steer(&mut self, steering_vec: Vec2) {
         self.steering += steering_vec;
     }
+
+    /// Applies an instantaneous impulse (e.g. a knockback) by adding it directly to the velocity.
+    /// Unlike steering, the impulse is neither truncated by `max_force` nor divided by `mass`,
+    /// and it decays only via friction (see `PhysicalParams::speed_limit`).
+    pub fn apply_impulse(&mut self, impulse: Vec2) {
+        self.velocity += impulse;
+    }
+
+    /// Gets the current speed (length of the velocity).
+    pub fn speed(&self) -> f32 {
+        self.velocity.length()
+    }
+
+    /// Checks whether the host moves faster than `threshold`.
+    /// The host moving exactly at `threshold` is considered stationary.
+    pub fn is_moving(&self, threshold: f32) -> bool {
+        self.velocity.length_squared() > threshold * threshold
+    }
+
+    /// Gets the normalized velocity or `None` if the host is not moving.
+    pub fn heading(&self) -> Option<Vec2> {
+        self.velocity.try_normalize()
+    }
+}
+
+/// An optional `Component` which turns the steering behaviors of a `SteeringHost` on and off.
+/// While it's `false`, the behavior systems skip the host, leaving the behaviors' state
+/// (e.g. the wander angle or `ArrivalDetection`) untouched, and any steering added manually
+/// is discarded, so the host isn't steered at all (not even braked towards its target)
+/// and coasts to a stop via `friction`. `ExternalForce` and impulses are still applied.
+/// Hosts without the component are always enabled.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+pub struct SteeringEnabled(pub bool);
+
+impl Default for SteeringEnabled {
+    fn default() -> Self {
+        Self(true)
+    }
+}
+
+impl SteeringEnabled {
+    /// Checks whether a host with the optional component is steered.
+    pub fn is_enabled(enabled: Option<&Self>) -> bool {
+        enabled.is_none_or(|enabled| enabled.0)
+    }
+}
+
+/// A `Component` which stores an external force (e.g. wind or a conveyor belt) applied to a `SteeringHost`.
+/// The force is added to the host's steering every `FixedUpdate` before it's truncated by
+/// `max_force` and divided by `mass`, then the force is cleared.
+/// Set it again every tick for a continuous force or use `SteeringHost::apply_impulse`
+/// for an instantaneous one.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Reflect)]
+pub struct ExternalForce(pub Vec2);
+
+/// A `Component` which makes the host send `TargetReachedEvent` when it reaches its target,
+/// i.e. the target of `SteeringTargetVec2`, `SteeringTargetFull` or `SteeringTargetEntity`.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Reflect)]
+pub struct ArrivalDetection {
+    /// The distance to the target at which the target is considered reached.
+    /// Defaults to `8.0`.
+    pub arrival_epsilon: f32,
+    pub(crate) reached: bool,
+}
+
+impl Default for ArrivalDetection {
+    fn default() -> Self {
+        Self::new(8.0)
+    }
+}
+
+impl ArrivalDetection {
+    pub fn new(arrival_epsilon: f32) -> Self {
+        Self {
+            arrival_epsilon,
+            reached: false,
+        }
+    }
+
+    /// Checks whether the host is currently within `arrival_epsilon` of the target.
+    pub fn is_reached(&self) -> bool {
+        self.reached
+    }
+}
+
+/// An optional `Component` which caps the speed of a `SteeringHost` on each axis independently,
+/// e.g. to make the vertical movement slower than the horizontal one for a fake perspective.
+/// Applied after the velocity is truncated by `max_velocity` and `speed_limit`,
+/// so diagonal movement gets shaped by the caps instead of being a circle.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Reflect)]
+pub struct AxisSpeedLimit {
+    /// Maximum absolute speed on the `x` and `y` axes.
+    pub max: Vec2,
+}
+
+impl AxisSpeedLimit {
+    pub fn new(max_x: f32, max_y: f32) -> Self {
+        Self {
+            max: Vec2::new(max_x, max_y),
+        }
+    }
+
+    /// Clamps both components of the velocity to the limits. The sign of the limits is ignored.
+    pub fn apply(&self, velocity: Vec2) -> Vec2 {
+        let max = self.max.abs();
+        velocity.clamp(-max, max)
+    }
 }
 
 #[derive(Bundle, Default)]
@@ -114,3 +315,277 @@ pub struct SteeringBundle {
     pub steering: SteeringHost,
     pub physics_params: PhysicalParams,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{movement::behaviors::SteerSeek, test_utils::*, CollideEvent};
+
+    fn host(velocity: Vec2) -> SteeringHost {
+        SteeringHost {
+            velocity,
+            ..default()
+        }
+    }
+
+    #[test]
+    fn is_moving_at_threshold_boundary() {
+        assert!(!host(Vec2::new(3.0, 4.0)).is_moving(5.0));
+        assert!(host(Vec2::new(3.0, 4.001)).is_moving(5.0));
+        assert!(!host(Vec2::new(0.0, -4.999)).is_moving(5.0));
+        assert!(!host(Vec2::ZERO).is_moving(0.0));
+        assert!(host(Vec2::new(0.001, 0.0)).is_moving(0.0));
+    }
+
+    #[test]
+    fn speed_and_heading() {
+        let moving = host(Vec2::new(-3.0, 4.0));
+        assert_eq!(moving.speed(), 5.0);
+        assert_eq!(moving.heading(), Some(Vec2::new(-0.6, 0.8)));
+        assert_eq!(host(Vec2::ZERO).speed(), 0.0);
+        assert_eq!(host(Vec2::ZERO).heading(), None);
+    }
+
+    #[test]
+    fn canonical_bundle_splits_state_and_params() {
+        let bundle = SteeringBundle {
+            physics_params: PhysicalParams {
+                max_velocity: 100.0,
+                mass: 2.0,
+                ..default()
+            },
+            ..default()
+        };
+
+        assert_eq!(bundle.steering, SteeringHost::default());
+        assert_eq!(bundle.physics_params.max_velocity, 100.0);
+        assert_eq!(bundle.physics_params.mass, 2.0);
+        assert_eq!(
+            bundle.physics_params.max_force,
+            PhysicalParams::default().max_force
+        );
+    }
+
+    #[test]
+    fn impulse_is_not_clamped_to_max_velocity() {
+        let mut app = physics_app();
+        let params = PhysicalParams::default();
+        let host = spawn_host(&mut app, params);
+        let impulse = Vec2::new(params.max_velocity * 4.0, 0.0);
+        app.world_mut()
+            .get_mut::<SteeringHost>(host)
+            .unwrap()
+            .apply_impulse(impulse);
+
+        tick(&mut app);
+
+        let moved = position(&app, host).x;
+        assert!((moved - impulse.x * TICK.as_secs_f32()).abs() < 1e-3);
+        let velocity = app.world().get::<SteeringHost>(host).unwrap().velocity;
+        assert_eq!(velocity, impulse * params.friction);
+    }
+
+    #[test]
+    fn impulse_is_clamped_to_speed_limit() {
+        let mut app = physics_app();
+        let params = PhysicalParams {
+            speed_limit: Some(300.0),
+            ..default()
+        };
+        let host = spawn_host(&mut app, params);
+        app.world_mut()
+            .get_mut::<SteeringHost>(host)
+            .unwrap()
+            .apply_impulse(Vec2::new(1000.0, 0.0));
+
+        tick(&mut app);
+
+        let moved = position(&app, host).x;
+        assert!((moved - 300.0 * TICK.as_secs_f32()).abs() < 1e-3);
+    }
+
+    #[test]
+    fn impulse_on_collision_knocks_host_back() {
+        let mut app = physics_app();
+        spawn_wall(&mut app, Vec2::new(40.0, 0.0), Vec2::new(16.0, 64.0));
+        let host = spawn_host(&mut app, PhysicalParams::default());
+        app.world_mut()
+            .entity_mut(host)
+            .insert(circle(8.0))
+            .observe(
+                |trigger: Trigger<CollideEvent>, mut hosts: Query<&mut SteeringHost>| {
+                    let mut host = hosts.get_mut(trigger.entity()).unwrap();
+                    host.apply_impulse(Vec2::new(-1000.0, 0.0));
+                },
+            );
+        app.world_mut()
+            .get_mut::<SteeringHost>(host)
+            .unwrap()
+            .apply_impulse(Vec2::new(800.0, 0.0));
+
+        let mut max_x = 0.0f32;
+        for _ in 0..30 {
+            tick(&mut app);
+            max_x = max_x.max(position(&app, host).x);
+        }
+
+        let end = position(&app, host);
+        assert!(max_x > 20.0 && max_x <= 24.0);
+        assert!(end.x < 0.0);
+        assert_eq!(end.y, 0.0);
+    }
+
+    #[test]
+    fn turn_rate_limits_reversing_target() {
+        let mut app = physics_app();
+        let max_turn_rate = std::f32::consts::PI;
+        let host = spawn_host(
+            &mut app,
+            PhysicalParams {
+                max_turn_rate: Some(max_turn_rate),
+                ..default()
+            },
+        );
+        app.world_mut()
+            .entity_mut(host)
+            .insert((SteerSeek, SteeringTargetVec2::new(Vec2::new(1000.0, 0.0))));
+        for _ in 0..30 {
+            tick(&mut app);
+        }
+
+        app.world_mut()
+            .get_mut::<SteeringTargetVec2>(host)
+            .unwrap()
+            .0 = Some(Vec2::new(-1000.0, 0.0));
+
+        let max_angle = max_turn_rate * TICK.as_secs_f32() + 1e-4;
+        let mut heading = Vec2::X;
+        let mut ticks = 0;
+        while heading.x > -0.99 {
+            tick(&mut app);
+            ticks += 1;
+            assert!(ticks < 500, "the host never turned around");
+
+            let velocity = app.world().get::<SteeringHost>(host).unwrap().velocity;
+            assert!(heading.angle_between(velocity).abs() <= max_angle);
+            heading = velocity.normalize();
+        }
+
+        assert!(ticks > 1);
+    }
+
+    #[test]
+    fn axis_speed_limit_shapes_diagonal_movement() {
+        let mut app = physics_app();
+        let host = spawn_host(&mut app, PhysicalParams::default());
+        app.world_mut().entity_mut(host).insert((
+            AxisSpeedLimit::new(200.0, 50.0),
+            SteerSeek,
+            SteeringTargetVec2::new(Vec2::new(10_000.0, -10_000.0)),
+        ));
+
+        let mut prev = Vec2::ZERO;
+        let mut movement = Vec2::ZERO;
+        for _ in 0..60 {
+            tick(&mut app);
+            let position = position(&app, host);
+            movement = (position - prev) / TICK.as_secs_f32();
+            assert!(movement.x > 0.0 && movement.x <= 200.0 + 1e-2, "{movement}");
+            assert!(movement.y < 0.0 && movement.y >= -50.0 - 1e-2, "{movement}");
+            prev = position;
+        }
+
+        // seeking at 45 degrees, but the vertical speed is capped, unlike the horizontal one
+        assert!((movement.y + 50.0).abs() < 1e-2, "{movement}");
+        assert!(movement.x > 100.0, "{movement}");
+    }
+
+    #[test]
+    fn disabled_steering_coasts_to_stop_via_friction() {
+        let mut app = physics_app();
+        let params = PhysicalParams::default();
+        let host = spawn_host(&mut app, params);
+        app.world_mut()
+            .entity_mut(host)
+            .insert((SteerSeek, SteeringTargetVec2::new(Vec2::new(1000.0, 0.0))));
+        for _ in 0..30 {
+            tick(&mut app);
+        }
+
+        // the target stays where it is, only the steering is turned off
+        app.world_mut()
+            .entity_mut(host)
+            .insert(SteeringEnabled(false));
+        let mut velocity = app.world().get::<SteeringHost>(host).unwrap().velocity;
+        assert!(velocity.x > 0.0);
+        for _ in 0..200 {
+            tick(&mut app);
+            let next = app.world().get::<SteeringHost>(host).unwrap().velocity;
+            assert!(
+                (next - velocity * params.friction).length() < 1e-3,
+                "{next}"
+            );
+            velocity = next;
+        }
+
+        assert!(velocity.length() < 1e-3, "{velocity}");
+        assert!(position(&app, host).x < 1000.0);
+    }
+
+    #[test]
+    fn zero_max_velocity_and_mass_keep_host_finite() {
+        let mut app = physics_app();
+        let host = spawn_host(
+            &mut app,
+            PhysicalParams {
+                max_velocity: 0.0,
+                mass: 0.0,
+                ..default()
+            },
+        );
+        app.world_mut()
+            .entity_mut(host)
+            .insert((SteerSeek, SteeringTargetVec2::new(Vec2::new(100.0, 0.0))));
+
+        for _ in 0..10 {
+            tick(&mut app);
+        }
+
+        let host_state = app.world().get::<SteeringHost>(host).unwrap();
+        assert!(host_state.velocity.is_finite() && host_state.steering.is_finite());
+        assert_eq!(position(&app, host), Vec2::ZERO);
+    }
+
+    #[test]
+    fn acceleration_is_capped_across_masses() {
+        let max_acceleration = 300.0;
+        let max_delta = max_acceleration * TICK.as_secs_f32() + 1e-3;
+        for mass in [0.25, 1.0, 4.0, 20.0] {
+            let mut app = physics_app();
+            let params = PhysicalParams {
+                mass,
+                friction: 1.0,
+                max_force: 10_000.0,
+                max_acceleration: Some(max_acceleration),
+                ..default()
+            };
+            let host = spawn_host(&mut app, params);
+            app.world_mut()
+                .entity_mut(host)
+                .insert((SteerSeek, SteeringTargetVec2::new(Vec2::new(0.0, 1000.0))));
+
+            let mut prev = Vec2::ZERO;
+            let mut max_seen: f32 = 0.0;
+            for _ in 0..60 {
+                tick(&mut app);
+                let velocity = app.world().get::<SteeringHost>(host).unwrap().velocity;
+                max_seen = max_seen.max(velocity.distance(prev));
+                prev = velocity;
+            }
+
+            assert!(max_seen <= max_delta, "mass {mass}: {max_seen}");
+            // all of them hit the cap, so they accelerate the same way regardless of the mass
+            assert!(max_seen > max_delta - 2e-3, "mass {mass}: {max_seen}");
+        }
+    }
+}
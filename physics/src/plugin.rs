@@ -1,44 +1,347 @@
 use crate::{
-    prelude::*, CollideEvent, InvokeTriggerEvent, MovementCalculateEvent, PositionUpdateEvent,
+    diagnostics::{register_diagnostics, update_collision_stats},
+    prelude::*,
+    CollideEvent, ContactBegan, ContactEnded, ContactId, ContactPersisted, InvokeTriggerEvent,
+    MovementCalculateEvent, PositionUpdateEvent, PreResolveCollision, TargetReachedEvent,
 };
+use animation::update_velocity_animations;
 use behaviors::{
-    steer_collision_avoidance, steer_entity, steer_vec2, SteerArrival, SteerFlee, SteerSeek,
+    clear_lost_targets, detect_target_reached, steer_brake, steer_collision_avoidance,
+    steer_entity, steer_hide, steer_interpose, steer_leader_following, steer_queue,
+    steer_raycast_avoidance, steer_separation, steer_vec2, steer_wander, update_cursor_targets,
+    SteerArrival, SteerFlee, SteerSeek,
+};
+use bevy::{
+    color::palettes::css::{AQUA, DODGER_BLUE, FUCHSIA, GRAY, LIME, ORANGE, RED, WHITE, YELLOW},
+    prelude::*,
+    utils::{HashMap, HashSet},
 };
-use bevy::{color::palettes::css::RED, prelude::*};
 use colliders::Collider;
-use common::math::{is_flag_set, truncate_vec2};
+use common::{
+    math::{is_flag_set, truncate_vec2},
+    FRect,
+};
+use grid::grid_movement;
+use interpolation::{
+    interpolate_positions, on_interpolation_added, on_interpolation_removed,
+    restore_fixed_positions, store_fixed_positions,
+};
+use raycast_debug::{collect_raycasts, stop_ray_recording, RaycastDebugBuffer};
 use spatial_hash::SpatialHash;
 use steering::*;
 
 #[derive(Component, Default)]
 pub struct RigidBodyStatic;
 
+/// Pushes a freshly spawned collider out of the colliders it overlaps, e.g. enemies of a batch
+/// spawned on top of each other or inside a wall. Every iteration the collider is moved by
+/// the sum of the minimum translations of its collisions, only the entity itself is moved.
+/// The component is removed after the depenetration in the next `FixedUpdate`.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+pub struct DepenetrationOnSpawn {
+    /// Max number of the depenetration passes. Defaults to `4`.
+    pub iterations: u32,
+}
+
+impl Default for DepenetrationOnSpawn {
+    fn default() -> Self {
+        Self { iterations: 4 }
+    }
+}
+
+/// Raw velocity of an entity in units per second, for entities which need moving colliders
+/// without the steering system. Entities with `Velocity` and without `SteeringHost` are moved
+/// by `velocity * delta_seconds` every `FixedUpdate` through the same collision resolution
+/// as steering entities.
+///
+/// If an entity has both `Velocity` and `SteeringHost`, the steering is the producer of
+/// the velocity: `Velocity` is overwritten with `SteeringHost::velocity` every tick.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Reflect)]
+pub struct Velocity(pub Vec2);
+
+/// The rectangle of the world the `ConfineToWorld` entities are kept in.
+/// Not inserted by `PhysicsPlugin`, insert it once the world is known, e.g. with
+/// the bounds of the generated world. Nothing is confined while it's missing.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Reflect)]
+pub struct WorldBounds(pub FRect);
+
+/// Keeps the entity within `WorldBounds`. Once the movement is applied, the position is
+/// clamped to the bounds and the velocity component pointing out of them is removed.
+/// Clamps `Transform::translation`, so the entity is expected to be at the root
+/// of the hierarchy.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Reflect)]
+pub struct ConfineToWorld {
+    /// Distance kept from the bounds, e.g. the radius of the entity's collider.
+    /// Defaults to `0.0`.
+    pub margin: f32,
+}
+
+impl ConfineToWorld {
+    pub fn new(margin: f32) -> Self {
+        Self { margin }
+    }
+}
+
+/// A filter consulted inline by the collision resolution right before a collision is resolved.
+/// If the function returns `true`, the resolution is skipped and the moving entity passes
+/// through the other one. Both entities of the collision are checked, so the component can be
+/// added either to the mover (e.g. a bullet) or to the obstacle (e.g. a one-way platform).
+/// `CollideEvent` and `PreResolveCollision` are still sent.
+#[derive(Component, Clone, Copy)]
+pub struct ResolutionVeto(pub fn(&PreResolveCollision) -> bool);
+
+/// System sets of the physics pipeline running in `FixedUpdate`, configured in this order.
+/// Use them to order user systems relative to the physics, e.g.
+/// `.after(PhysicsSet::Apply)` to read the final positions of the tick.
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PhysicsSet {
+    /// Steering behaviors. Accumulate the steering forces consumed by `PhysicsSet::Steer`
+    /// in the same tick, so the behaviors' strength doesn't depend on the frame rate.
+    Behaviors,
+    /// Steering and velocity integration. Produces `MovementCalculateEvent`s.
+    Steer,
+    /// Runs before the collision resolution. Brings the `SpatialHash` in sync with
+    /// the colliders changed outside of the physics. Custom movers sending
+    /// `MovementCalculateEvent`s can go here to be resolved in the same tick.
+    Broadphase,
+    /// Collision resolution. Reads `MovementCalculateEvent`s, sends `CollideEvent`,
+    /// `InvokeTriggerEvent` and `PositionUpdateEvent`s.
+    Resolve,
+    /// Applies the resolved movement to `Transform`s and updates the `SpatialHash`.
+    Apply,
+}
+
+/// Extension for `Commands` to spawn a lot of colliders at once, e.g. an enemy wave.
+pub trait SpawnCollidersExt {
+    /// Spawns all the bundles in one pass via `World::spawn_batch` and reserves
+    /// the `SpatialHash` capacity for them beforehand, so the hash doesn't reallocate
+    /// while the colliders are being registered.
+    fn spawn_colliders<I>(&mut self, bundles: I)
+    where
+        I: IntoIterator + Send + Sync + 'static,
+        I::Item: Bundle;
+}
+
+impl SpawnCollidersExt for Commands<'_, '_> {
+    fn spawn_colliders<I>(&mut self, bundles: I)
+    where
+        I: IntoIterator + Send + Sync + 'static,
+        I::Item: Bundle,
+    {
+        self.add(move |world: &mut World| {
+            let bundles = bundles.into_iter();
+            let (count, _) = bundles.size_hint();
+            if let Some(mut hash) = world.get_resource_mut::<SpatialHash>() {
+                hash.reserve(count);
+            }
+            world.spawn_batch(bundles);
+        });
+    }
+}
+
+/// Runtime configuration of the physics. Inserted by `PhysicsPlugin` and can be changed at any time.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Reflect)]
+pub struct PhysicsConfig {
+    /// A gap kept between the resolved colliders. Resolving a collision exactly onto
+    /// the contact surface makes entities sliding along walls jitter between colliding
+    /// and not colliding every tick, so the resolved collider is pushed out a bit further
+    /// along the collision's minimum translation vector.
+    /// Defaults to `0.01`.
+    pub skin_width: f32,
+    /// Max number of the collision resolution passes per movement. Resolving one collision
+    /// may push the collider into another one (e.g. when it's wedged into a corner), so
+    /// the pass is repeated with the adjusted motion until no penetration is left.
+    /// `0` is treated as `1`.
+    /// Defaults to `4`.
+    pub solver_iterations: u32,
+}
+
+impl Default for PhysicsConfig {
+    fn default() -> Self {
+        Self {
+            skin_width: 0.01,
+            solver_iterations: 4,
+        }
+    }
+}
+
+/// Bitmask of physics layers whose collisions aren't resolved globally, as if they were removed
+/// from `collides_with_layers` of every collider. Inserted by `PhysicsPlugin`, set the bits
+/// at runtime for gameplay modes like a ghost mode or a power-up phasing through enemies.
+/// `CollideEvent` is still sent. Defaults to `0`, i.e. no layers are disabled.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq, Reflect)]
+pub struct DisabledLayers(pub i32);
+
+impl DisabledLayers {
+    /// Checks whether the collision of `collider` with `other` is resolved, i.e. `other`
+    /// isn't on a disabled layer and `collider` resolves with it.
+    pub fn resolves_with(&self, collider: &Collider, other: &Collider) -> bool {
+        !is_flag_set(self.0, other.physics_layer) && collider.resolves_with(other)
+    }
+}
+
+/// A contact between two colliders recorded during the tick.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Contact {
+    /// A normal vector of the collision, pointing from `ContactId::b` towards `ContactId::a`.
+    pub normal: Vec2,
+    pub point: Vec2,
+}
+
+/// The contacts between the non-trigger colliders of the last tick, keyed by `ContactId`.
+/// Inserted and updated by `PhysicsPlugin`, see `ContactBegan` for the events.
+#[derive(Resource, Debug, Default)]
+pub struct Contacts {
+    current: HashMap<ContactId, Contact>,
+    previous: HashMap<ContactId, Contact>,
+}
+
+impl Contacts {
+    /// Gets the contact of the last tick.
+    pub fn get(&self, id: ContactId) -> Option<&Contact> {
+        self.previous.get(&id)
+    }
+
+    /// Iterates the contacts of the last tick.
+    pub fn iter(&self) -> impl Iterator<Item = (&ContactId, &Contact)> {
+        self.previous.iter()
+    }
+
+    /// Records a collision of `main` with `other`, `normal` points from `other` towards `main`.
+    fn record(&mut self, main: Entity, other: Entity, normal: Vec2, point: Vec2) {
+        let id = ContactId::new(main, other);
+        let normal = if id.a == main { normal } else { -normal };
+        self.current.insert(id, Contact { normal, point });
+    }
+}
+
 /// The main plugin. Required for collisions and movement to work.
-pub struct PhysicsPlugin;
+#[derive(Default)]
+pub struct PhysicsPlugin {
+    pub config: PhysicsConfig,
+    /// Creates the `SpatialHash` with `SpatialHash::with_layer_sharding`.
+    /// Useful if most of the colliders are on layers the layer-masked queries ignore.
+    /// Defaults to `false`.
+    pub layer_sharding: bool,
+}
 
 impl Plugin for PhysicsPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(SpatialHash::new(40))
+        let spatial_hash = if self.layer_sharding {
+            SpatialHash::with_layer_sharding(40)
+        } else {
+            SpatialHash::new(40)
+        };
+
+        app.insert_resource(spatial_hash)
+            .insert_resource(self.config)
+            .init_resource::<DisabledLayers>()
             .add_event::<MovementCalculateEvent>()
             .add_event::<PositionUpdateEvent>()
             .add_event::<InvokeTriggerEvent>()
+            .add_event::<TargetReachedEvent>()
+            .add_event::<ContactBegan>()
+            .add_event::<ContactPersisted>()
+            .add_event::<ContactEnded>()
+            .init_resource::<Contacts>()
+            .register_type::<CollisionStats>()
+            .register_type::<DisabledLayers>()
+            .register_type::<WorldBounds>()
+            .register_type::<ConfineToWorld>()
+            .register_type::<colliders::CollisionLayers>()
+            .register_type::<animation::VelocityAnimation>()
+            .add_systems(Update, (update_cursor_targets, update_velocity_animations))
+            .configure_sets(
+                FixedUpdate,
+                (
+                    PhysicsSet::Behaviors,
+                    PhysicsSet::Steer,
+                    PhysicsSet::Broadphase,
+                    PhysicsSet::Resolve,
+                    PhysicsSet::Apply,
+                )
+                    .chain(),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    (
+                        (
+                            clear_lost_targets,
+                            steer_entity::<SteerSeek>,
+                            steer_vec2::<SteerSeek>,
+                            steer_entity::<SteerArrival>,
+                            steer_vec2::<SteerArrival>,
+                            steer_collision_avoidance,
+                            steer_raycast_avoidance,
+                            steer_interpose,
+                            steer_hide,
+                            steer_wander,
+                            steer_separation,
+                            steer_queue,
+                            steer_leader_following,
+                        ),
+                        // Braking overrides the steering of the other behaviors
+                        steer_brake,
+                    )
+                        .chain()
+                        .in_set(PhysicsSet::Behaviors),
+                    (steer, apply_velocity, grid_movement)
+                        .chain()
+                        .in_set(PhysicsSet::Steer),
+                    (
+                        sync_reparented_colliders,
+                        sync_changed_colliders,
+                        depenetrate_spawned,
+                    )
+                        .chain()
+                        .in_set(PhysicsSet::Broadphase),
+                    calc_movement.in_set(PhysicsSet::Resolve),
+                    (
+                        update_position,
+                        confine_to_world,
+                        update_contacts,
+                        update_collision_stats,
+                        detect_target_reached,
+                    )
+                        .chain()
+                        .in_set(PhysicsSet::Apply),
+                ),
+            )
+            .add_systems(FixedFirst, restore_fixed_positions)
+            .add_systems(FixedLast, store_fixed_positions)
             .add_systems(
-                Update,
+                Last,
                 (
-                    steer_entity::<SteerSeek>,
-                    steer_vec2::<SteerSeek>,
-                    steer_entity::<SteerArrival>,
-                    steer_vec2::<SteerArrival>,
-                    steer_collision_avoidance,
+                    collect_raycasts.run_if(resource_exists::<RaycastDebugBuffer>),
+                    stop_ray_recording.run_if(resource_removed::<RaycastDebugBuffer>()),
                 ),
             )
-            .add_systems(FixedUpdate, (steer, calc_movement, update_position).chain())
+            .add_systems(Update, interpolate_positions)
             .observe(on_collider_added)
-            .observe(on_collider_removed);
+            .observe(on_collider_removed)
+            .observe(on_interpolation_added)
+            .observe(on_interpolation_removed);
+
+        register_diagnostics(app);
+    }
+}
+
+/// Colors of the physics layers used by `debug_draw`, keyed on the lowest set bit of the layer.
+const LAYER_PALETTE: [Srgba; 8] = [RED, LIME, DODGER_BLUE, YELLOW, FUCHSIA, ORANGE, AQUA, WHITE];
+
+/// Gets the debug color of the physics layer. Colliders without any layer are drawn gray.
+fn layer_color(physics_layer: i32) -> Srgba {
+    if physics_layer == 0 {
+        return GRAY;
     }
+    LAYER_PALETTE[physics_layer.trailing_zeros() as usize % LAYER_PALETTE.len()]
 }
 
-fn debug_draw(
+/// Draws all the registered colliders colored by their `physics_layer`.
+/// Triggers are drawn semi-transparent. If a collider has a `local_offset`,
+/// a line from the entity's origin to the collider's center is drawn as well.
+pub fn debug_draw(
     mut gizmos: Gizmos,
     spatial_hash: Res<SpatialHash>,
     query: Query<&Collider, With<Transform>>,
@@ -47,18 +350,65 @@ fn debug_draw(
     for entity in entities {
         let collider = query.get(entity);
         if let Ok(collider) = collider {
-            match collider.shape.shape_type {
+            let mut color = layer_color(collider.physics_layer);
+            if collider.is_trigger {
+                color = color.with_alpha(0.35);
+            }
+
+            match collider.shape.scaled_type() {
                 shapes::ShapeType::None => {}
                 shapes::ShapeType::Circle { radius } => {
-                    gizmos.circle_2d(collider.absolute_position(), radius, RED);
+                    gizmos.circle_2d(collider.absolute_position(), radius, color);
                 }
                 shapes::ShapeType::Box { width, height } => gizmos.rect_2d(
                     collider.absolute_position(),
                     0.0,
                     Vec2::new(width, height),
-                    RED,
+                    color,
                 ),
             }
+
+            if collider.local_offset != Vec2::ZERO {
+                gizmos.line_2d(collider.position(), collider.center(), color);
+                gizmos.circle_2d(collider.position(), 1.5, color);
+            }
+        }
+    }
+}
+
+/// Computes the up-to-date world transform of an entity by walking up its `Parent`s.
+/// Bevy propagates `GlobalTransform`s only in `PostUpdate`, so the ones stored on the entity
+/// and its ancestors may be outdated if any of them moved during this frame.
+fn world_transform(
+    entity: Entity,
+    transforms: &Query<(&Transform, Option<&Parent>)>,
+) -> GlobalTransform {
+    let mut global = GlobalTransform::IDENTITY;
+    let mut current = Some(entity);
+    while let Some(entity) = current {
+        let Ok((transform, parent)) = transforms.get(entity) else {
+            break;
+        };
+        global = GlobalTransform::from(*transform) * global;
+        current = parent.map(Parent::get);
+    }
+    global
+}
+
+/// Re-syncs the colliders of an entity and all of its descendants with their world transforms,
+/// so e.g. a hitbox attached to a moving player moves along with it.
+fn sync_collider_tree(
+    entity: Entity,
+    transforms: &Query<(&Transform, Option<&Parent>)>,
+    children: &Query<&Children>,
+    colliders: &mut Query<&mut Collider>,
+    spatial_hash: &mut SpatialHash,
+) {
+    for entity in std::iter::once(entity).chain(children.iter_descendants(entity)) {
+        if let Ok(mut collider) = colliders.get_mut(entity) {
+            spatial_hash.remove(&collider, entity);
+            collider.update_from_transform(&world_transform(entity, transforms));
+            spatial_hash.register(&collider, entity);
         }
     }
 }
@@ -66,33 +416,190 @@ fn debug_draw(
 fn on_collider_added(
     trigger: Trigger<OnAdd, Collider>,
     mut spatial_hash: ResMut<SpatialHash>,
-    mut query: Query<(&mut Collider, &Transform)>,
+    mut colliders: Query<&mut Collider>,
+    transforms: Query<(&Transform, Option<&Parent>)>,
 ) {
-    let mut collider = query.get_mut(trigger.entity()).unwrap();
-    collider.0.update_from_transform(collider.1);
-    spatial_hash.register(&collider.0, trigger.entity());
+    let mut collider = colliders.get_mut(trigger.entity()).unwrap();
+    collider.update_from_transform(&world_transform(trigger.entity(), &transforms));
+    spatial_hash.register(&collider, trigger.entity());
 }
 
-fn on_collider_removed(
-    trigger: Trigger<OnRemove, Collider>,
-    mut hash: ResMut<SpatialHash>,
-    query: Query<&Collider, With<Transform>>,
+fn on_collider_removed(trigger: Trigger<OnRemove, Collider>, mut hash: ResMut<SpatialHash>) {
+    // The entity may be already missing its `Transform` if it's being despawned recursively,
+    // so the bounds cached by the hash are used
+    hash.remove_entity(trigger.entity());
+}
+
+/// Re-syncs the colliders of the entities attached to a new parent. Children spawned via
+/// `with_children` get their `Parent` only after their `Collider` has been registered.
+fn sync_reparented_colliders(
+    reparented: Query<Entity, Changed<Parent>>,
+    transforms: Query<(&Transform, Option<&Parent>)>,
+    children: Query<&Children>,
+    mut colliders: Query<&mut Collider>,
+    mut spatial_hash: ResMut<SpatialHash>,
+) {
+    for entity in &reparented {
+        sync_collider_tree(
+            entity,
+            &transforms,
+            &children,
+            &mut colliders,
+            &mut spatial_hash,
+        );
+    }
+}
+
+/// Re-registers the colliders whose footprint or physics layer was changed outside of
+/// the physics, e.g. via `Collider::set_shape`. The layer matters for the layer sharding.
+fn sync_changed_colliders(
+    colliders: Query<(&Collider, Entity), Changed<Collider>>,
+    mut spatial_hash: ResMut<SpatialHash>,
 ) {
-    let collider = query.get(trigger.entity()).unwrap();
-    hash.remove(collider, trigger.entity());
+    for (collider, entity) in &colliders {
+        if spatial_hash.registered_bounds(entity) != Some(collider.bounds())
+            || spatial_hash.registered_layer(entity) != Some(collider.physics_layer)
+        {
+            spatial_hash.remove(collider, entity);
+            spatial_hash.register(collider, entity);
+        }
+    }
+}
+
+/// Resolves the overlaps of the colliders with `DepenetrationOnSpawn`.
+#[allow(clippy::type_complexity)]
+fn depenetrate_spawned(
+    hosts: Query<(&DepenetrationOnSpawn, Entity)>,
+    mut colliders: ParamSet<(Query<&Collider>, Query<&mut Collider>)>,
+    mut transforms: Query<(&mut Transform, Option<&Parent>)>,
+    children: Query<&Children>,
+    mut spatial_hash: ResMut<SpatialHash>,
+    config: Res<PhysicsConfig>,
+    mut commands: Commands,
+) {
+    for (depenetration, entity) in &hosts {
+        commands.entity(entity).remove::<DepenetrationOnSpawn>();
+
+        for _ in 0..depenetration.iterations {
+            let translation = {
+                let colliders = colliders.p0();
+                let Ok(collider) = colliders.get(entity) else {
+                    break;
+                };
+                if collider.is_trigger {
+                    break;
+                }
+
+                let neighbors = sorted_neighbors(spatial_hash.aabb_broadphase(
+                    &colliders,
+                    collider.bounds(),
+                    Some(entity),
+                    None,
+                ));
+
+                let mut translation = Vec2::ZERO;
+                for neighbor_entity in neighbors {
+                    let neighbor = colliders.get(neighbor_entity).unwrap();
+                    if neighbor.is_trigger
+                        || collider.same_body(neighbor)
+                        || !collider.resolves_with(neighbor)
+                    {
+                        continue;
+                    }
+                    let Some(collision) = collider.collides_with(neighbor) else {
+                        continue;
+                    };
+
+                    let min_translation = collision.min_translation;
+                    translation +=
+                        min_translation + min_translation.normalize_or_zero() * config.skin_width;
+                }
+                translation
+            };
+
+            if translation == Vec2::ZERO {
+                break;
+            }
+
+            let Ok((mut transform, _)) = transforms.get_mut(entity) else {
+                break;
+            };
+            transform.translation.x -= translation.x;
+            transform.translation.y -= translation.y;
+            sync_collider_tree(
+                entity,
+                &transforms.to_readonly(),
+                &children,
+                &mut colliders.p1(),
+                &mut spatial_hash,
+            );
+        }
+    }
 }
 
+type SteeredHost = (
+    &'static mut SteeringHost,
+    &'static PhysicalParams,
+    Option<&'static mut ExternalForce>,
+    Option<&'static mut Velocity>,
+    Option<&'static AxisSpeedLimit>,
+    Option<&'static SteeringEnabled>,
+    Entity,
+);
+
 fn steer(
-    mut host: Query<(&mut SteeringHost, &PhysicalParams, Entity)>,
+    mut host: Query<SteeredHost>,
     time: Res<Time>,
     mut evt_movement_calc: EventWriter<MovementCalculateEvent>,
 ) {
-    for (mut host, params, entity) in &mut host {
+    for (mut host, params, external_force, velocity, axis_limit, enabled, entity) in &mut host {
+        if enabled.is_some_and(|enabled| !enabled.0) {
+            host.steering = Vec2::ZERO;
+        }
+
+        if let Some(mut external_force) = external_force {
+            host.steering += external_force.0;
+            external_force.0 = Vec2::ZERO;
+        }
+
         host.steering = truncate_vec2(host.steering, params.max_force);
-        host.steering /= params.mass;
+        if params.mass > 0.0 {
+            host.steering /= params.mass;
+        }
+        if let Some(max_acceleration) = params.max_acceleration {
+            host.steering = truncate_vec2(host.steering, max_acceleration * time.delta_seconds());
+        }
 
         let steering = host.steering;
-        host.velocity = truncate_vec2(host.velocity + steering, params.max_velocity);
+        let prev_velocity = host.velocity;
+        // Steering alone can't accelerate the host above `max_velocity`,
+        // but it shouldn't cut off the speed gained from external impulses either.
+        let max_velocity = params.max_velocity.max(host.velocity.length());
+        host.velocity = truncate_vec2(host.velocity + steering, max_velocity);
+        if let Some(max_turn_rate) = params.max_turn_rate {
+            host.velocity = limit_turn(
+                prev_velocity,
+                host.velocity,
+                max_turn_rate * time.delta_seconds(),
+            );
+        }
+        if let Some(speed_limit) = params.speed_limit {
+            host.velocity = truncate_vec2(host.velocity, speed_limit);
+        }
+        if let Some(axis_limit) = axis_limit {
+            host.velocity = axis_limit.apply(host.velocity);
+        }
+        // Broken parameters (e.g. zero `max_velocity` in a custom behavior)
+        // may produce NaNs, which would corrupt the `Transform` for good
+        if !host.velocity.is_finite() || !host.steering.is_finite() {
+            warn!("steering of {:?} is not finite, stopping it", entity);
+            host.velocity = Vec2::ZERO;
+            host.steering = Vec2::ZERO;
+        }
+
+        if let Some(mut velocity) = velocity {
+            velocity.0 = host.velocity;
+        }
 
         host.movement = host.velocity * time.delta_seconds();
         let evt = MovementCalculateEvent {
@@ -105,50 +612,235 @@ fn steer(
     }
 }
 
+/// Rotates the direction of `from` towards the direction of `to` by at most `max_angle` radians.
+/// The resulting vector has the length of `to`.
+fn limit_turn(from: Vec2, to: Vec2, max_angle: f32) -> Vec2 {
+    if from == Vec2::ZERO || to == Vec2::ZERO {
+        return to;
+    }
+
+    let angle = from.angle_between(to);
+    if angle.abs() <= max_angle {
+        return to;
+    }
+
+    Vec2::from_angle(max_angle * angle.signum()).rotate(from.normalize()) * to.length()
+}
+
+fn apply_velocity(
+    bodies: Query<(&Velocity, Entity), Without<SteeringHost>>,
+    time: Res<Time>,
+    mut evt_movement_calc: EventWriter<MovementCalculateEvent>,
+) {
+    for (velocity, entity) in &bodies {
+        if velocity.0 == Vec2::ZERO {
+            continue;
+        }
+
+        evt_movement_calc.send(MovementCalculateEvent {
+            entity,
+            movement: velocity.0 * time.delta_seconds(),
+        });
+    }
+}
+
+/// Gets the broadphase result sorted by `Entity`. The iteration order of a `HashSet` differs
+/// from run to run, while the order of the resolution affects the outcome (e.g. of an entity
+/// wedged into a corner), so the neighbors are resolved in a fixed order to keep
+/// the simulation reproducible.
+fn sorted_neighbors(neighbors: HashSet<Entity>) -> Vec<Entity> {
+    let mut neighbors: Vec<Entity> = neighbors.into_iter().collect();
+    neighbors.sort_unstable();
+    neighbors
+}
+
+#[allow(clippy::too_many_arguments)]
 fn calc_movement(
     mut evt_movement_calc: EventReader<MovementCalculateEvent>,
     mut evt_pos_update: EventWriter<PositionUpdateEvent>,
     mut evt_invoke_trigger: EventWriter<InvokeTriggerEvent>,
     mut commands: Commands,
     spatial_hash: Res<SpatialHash>,
-    hosts: Query<&SteeringHost>,
     colliders: Query<&Collider>,
+    vetoes: Query<&ResolutionVeto>,
+    statics: Query<(), With<RigidBodyStatic>>,
+    config: Res<PhysicsConfig>,
+    disabled_layers: Res<DisabledLayers>,
+    mut contacts: ResMut<Contacts>,
+    mut stats: ResMut<CollisionStats>,
 ) {
     info_span!("calc_movement", name = "calc_movement");
-    for evt in evt_movement_calc.read() {
-        let host = hosts.get(evt.entity);
+    stats.reset_tick();
+
+    let events: Vec<&MovementCalculateEvent> = evt_movement_calc.read().collect();
+    // Movement of every dynamic entity during this tick, so the collisions between
+    // two moving entities are checked with their relative motion
+    let mut pending_motions: HashMap<Entity, Vec2> = HashMap::new();
+    for evt in &events {
+        if !statics.contains(evt.entity) {
+            *pending_motions.entry(evt.entity).or_default() += evt.movement;
+        }
+    }
+
+    // The other movers may come from outside the swept bounds of an entity,
+    // so its broadphase is widened by the largest motion of this tick
+    let max_pending_motion = pending_motions
+        .values()
+        .fold(Vec2::ZERO, |max, motion| max.max(motion.abs()));
+
+    // Pairs of moving entities one of which already resolved its half of the collision,
+    // the other one resolves the rest of it in full
+    let mut halved_pairs: HashSet<ContactId> = HashSet::new();
+
+    for evt in events {
         let collider = colliders.get(evt.entity);
 
         let mut motion = evt.movement;
 
         let mut process_collider = |collider: &Collider| {
-            // Host has a collider, calculating correct movement
-            if collider.is_trigger {
-                // Skipping the trigger
-                send_pos_update(&mut evt_pos_update, evt);
-                return;
-            }
-
-            let mut bounds = collider.bounds();
-            bounds.x += evt.movement.x;
-            bounds.y += evt.movement.y;
+            // Entity has a collider, calculating correct movement
+            let mut bounds = collider.bounds().swept(evt.movement);
+            bounds.inflate(max_pending_motion.x, max_pending_motion.y);
 
-            let neighbors = spatial_hash.aabb_broadphase(
+            let mut neighbors = sorted_neighbors(spatial_hash.aabb_broadphase(
                 &colliders,
                 bounds,
                 Some(evt.entity),
                 None,
                 //Some(collider.collides_with_layers),
-            );
+            ));
+            stats.broadphase_candidates += neighbors.len();
 
-            for neighbor_entity in neighbors {
-                let neighbor = colliders.get(neighbor_entity).ok().unwrap();
+            if collider.is_trigger {
+                // Triggers are not blocked by anything, the motion stays the same.
+                // A moving trigger still invokes the triggers it enters.
+                for neighbor_entity in neighbors {
+                    let neighbor = colliders.get(neighbor_entity).ok().unwrap();
+                    if !neighbor.is_trigger || collider.same_body(neighbor) {
+                        continue;
+                    }
+                    stats.collision_tests += 1;
+                    let neighbor_motion = pending_motions.get(&neighbor_entity).copied();
+                    let relative_motion = motion - neighbor_motion.unwrap_or_default();
+                    if collider.overlap_motion(neighbor, relative_motion).is_some()
+                        || collider.passes_through(neighbor, relative_motion)
+                    {
+                        evt_invoke_trigger.send(InvokeTriggerEvent {
+                            entity_main: evt.entity,
+                            entity_trigger: neighbor_entity,
+                        });
+                    }
+                }
+                return;
+            }
+
+            // Neighbors already reported via `CollideEvent` or `InvokeTriggerEvent`,
+            // so the events are sent once per tick regardless of the solver iterations
+            let mut reported = HashSet::new();
+            // Neighbors whose collision was split in half with them. Their half is computed once,
+            // re-resolving it every iteration would shift the entity by 15/16 of the penetration
+            let mut shared = HashSet::new();
+
+            for iteration in 0..config.solver_iterations.max(1) {
+                if iteration > 0 {
+                    // The motion was adjusted by the previous pass, so it may now reach
+                    // colliders which weren't around the original motion
+                    let mut bounds = collider.bounds().swept(motion);
+                    bounds.inflate(max_pending_motion.x, max_pending_motion.y);
+                    neighbors = sorted_neighbors(spatial_hash.aabb_broadphase(
+                        &colliders,
+                        bounds,
+                        Some(evt.entity),
+                        None,
+                    ));
+                    stats.broadphase_candidates += neighbors.len();
+                }
+
+                let mut resolved = false;
 
-                if let Some(collision) = collider.collides_with_motion(neighbor, motion) {
-                    if !neighbor.is_trigger {
-                        if is_flag_set(collider.collides_with_layers, neighbor.physics_layer) {
-                            motion -= collision.min_translation;
+                for &neighbor_entity in &neighbors {
+                    let neighbor = colliders.get(neighbor_entity).ok().unwrap();
+                    if collider.same_body(neighbor) || shared.contains(&neighbor_entity) {
+                        continue;
+                    }
+
+                    let neighbor_motion = pending_motions.get(&neighbor_entity).copied();
+                    stats.collision_tests += 1;
+                    let collision = match neighbor_motion {
+                        Some(neighbor_motion) => collider.collides_with_relative_motion(
+                            neighbor,
+                            motion,
+                            neighbor_motion,
+                        ),
+                        None => collider.collides_with_motion(neighbor, motion),
+                    };
+
+                    let Some(collision) = collision else {
+                        // A fast mover may skip a thin trigger entirely,
+                        // so the path of its center is checked as well
+                        let relative_motion = motion - neighbor_motion.unwrap_or_default();
+                        if neighbor.is_trigger
+                            && collider.passes_through(neighbor, relative_motion)
+                            && reported.insert(neighbor_entity)
+                        {
+                            evt_invoke_trigger.send(InvokeTriggerEvent {
+                                entity_main: evt.entity,
+                                entity_trigger: neighbor_entity,
+                            });
                         }
+                        continue;
+                    };
+                    let first_contact = reported.insert(neighbor_entity);
+
+                    if neighbor.is_trigger {
+                        if first_contact {
+                            evt_invoke_trigger.send(InvokeTriggerEvent {
+                                entity_main: evt.entity,
+                                entity_trigger: neighbor_entity,
+                            });
+                        }
+                        continue;
+                    }
+
+                    if disabled_layers.resolves_with(collider, neighbor) {
+                        let pre_resolve = PreResolveCollision {
+                            entity_main: evt.entity,
+                            collided_with: neighbor_entity,
+                            normal: collision.normal,
+                            min_translation: collision.min_translation,
+                        };
+                        if first_contact {
+                            commands.trigger(pre_resolve);
+                            commands.trigger_targets(pre_resolve, evt.entity);
+                        }
+
+                        let vetoed = vetoes
+                            .iter_many([evt.entity, neighbor_entity])
+                            .any(|veto| (veto.0)(&pre_resolve));
+                        if !vetoed {
+                            let mut translation = collision.min_translation
+                                + collision.min_translation.normalize_or_zero() * config.skin_width;
+                            // The other entity resolves its half of the collision
+                            // when its own movement is calculated
+                            if neighbor_motion.is_some_and(|motion| motion != Vec2::ZERO)
+                                && disabled_layers.resolves_with(neighbor, collider)
+                                && halved_pairs.insert(ContactId::new(evt.entity, neighbor_entity))
+                            {
+                                translation /= 2.0;
+                                shared.insert(neighbor_entity);
+                            }
+                            motion -= translation;
+                            resolved = true;
+                        }
+                    }
+
+                    if first_contact {
+                        contacts.record(
+                            evt.entity,
+                            neighbor_entity,
+                            collision.normal,
+                            collision.point,
+                        );
                         commands.trigger(CollideEvent {
                             entity_main: evt.entity,
                             collided_with: neighbor_entity,
@@ -160,57 +852,226 @@ fn calc_movement(
                             },
                             evt.entity,
                         );
-                    } else {
-                        evt_invoke_trigger.send(InvokeTriggerEvent {
-                            entity_main: evt.entity,
-                            entity_trigger: neighbor_entity,
-                        });
+                        // Static colliders never move, so they'd never be `entity_main`
+                        // of their own events. Lets e.g. breakable walls observe the hits
+                        if statics.contains(neighbor_entity) {
+                            commands.trigger_targets(
+                                CollideEvent {
+                                    entity_main: neighbor_entity,
+                                    collided_with: evt.entity,
+                                },
+                                neighbor_entity,
+                            );
+                        }
                     }
                 }
+
+                // No penetration is left
+                if !resolved {
+                    break;
+                }
             }
-        };
 
-        if let Ok(_host) = host {
-            match collider {
-                Ok(collider) => {
-                    process_collider(collider);
+            // The resolution leaves the colliders `skin_width` apart, so a body resting against
+            // another one doesn't penetrate it on the next tick. The pairs within the skin
+            // are still in contact, otherwise their contacts would end every other tick
+            let skin = config.skin_width * 2.0;
+            let mut bounds = collider.bounds().swept(motion);
+            bounds.inflate(skin, skin);
+            for neighbor_entity in sorted_neighbors(spatial_hash.aabb_broadphase(
+                &colliders,
+                bounds,
+                Some(evt.entity),
+                None,
+            )) {
+                if reported.contains(&neighbor_entity) {
+                    continue;
                 }
-                Err(_) => {
-                    // Host has no colliders, just sending the event further
-                    send_pos_update(&mut evt_pos_update, evt);
+                let neighbor = colliders.get(neighbor_entity).ok().unwrap();
+                if neighbor.is_trigger || collider.same_body(neighbor) {
                     continue;
                 }
-            };
 
-            evt_pos_update.send(PositionUpdateEvent {
-                entity: evt.entity,
-                movement: motion,
+                let neighbor_motion = pending_motions.get(&neighbor_entity).copied();
+                let relative_motion = motion - neighbor_motion.unwrap_or_default();
+                stats.collision_tests += 1;
+                if let Some(collision) =
+                    collider.overlap_motion_within(neighbor, relative_motion, skin)
+                {
+                    contacts.record(
+                        evt.entity,
+                        neighbor_entity,
+                        collision.normal,
+                        collision.point,
+                    );
+                }
+            }
+        };
+
+        match collider {
+            Ok(collider) => {
+                #[cfg(debug_assertions)]
+                if !collider.validate() {
+                    warn!(
+                        "collider of {:?} is out of sync with its shape, \
+                        use `Collider::set_shape` instead of writing `shape` or `local_offset` directly",
+                        evt.entity
+                    );
+                }
+                process_collider(collider);
+                // The later events see the resolved motion of this entity
+                if let Some(pending) = pending_motions.get_mut(&evt.entity) {
+                    *pending += motion - evt.movement;
+                }
+            }
+            Err(_) => {
+                // Entity has no colliders, just sending the event further
+                send_pos_update(&mut evt_pos_update, evt);
+                continue;
+            }
+        };
+
+        evt_pos_update.send(PositionUpdateEvent {
+            entity: evt.entity,
+            movement: motion,
+        });
+    }
+}
+
+/// Sends the contact events comparing the contacts recorded by `calc_movement`
+/// during this tick with the ones of the previous tick.
+fn update_contacts(
+    mut contacts: ResMut<Contacts>,
+    mut evt_began: EventWriter<ContactBegan>,
+    mut evt_persisted: EventWriter<ContactPersisted>,
+    mut evt_ended: EventWriter<ContactEnded>,
+) {
+    let contacts = &mut *contacts;
+
+    for (&id, contact) in &contacts.current {
+        if contacts.previous.contains_key(&id) {
+            evt_persisted.send(ContactPersisted {
+                id,
+                normal: contact.normal,
+                point: contact.point,
+            });
+        } else {
+            evt_began.send(ContactBegan {
+                id,
+                normal: contact.normal,
+                point: contact.point,
             });
         }
     }
+
+    for &id in contacts.previous.keys() {
+        if !contacts.current.contains_key(&id) {
+            evt_ended.send(ContactEnded { id });
+        }
+    }
+
+    contacts.previous = std::mem::take(&mut contacts.current);
 }
 
 fn update_position(
     mut evt_pos_update: EventReader<PositionUpdateEvent>,
-    mut host: Query<&mut Transform, With<SteeringHost>>,
+    mut transforms: Query<(&mut Transform, Option<&Parent>)>,
+    children: Query<&Children>,
     mut spatial_hash: ResMut<SpatialHash>,
     mut colliders: Query<&mut Collider>,
 ) {
     for ev in evt_pos_update.read() {
-        if let Ok(mut transform) = host.get_mut(ev.entity) {
-            transform.translation.x += ev.movement.x;
-            transform.translation.y += ev.movement.y;
-
-            if ev.movement != Vec2::ZERO {
-                let collider = colliders.get_mut(ev.entity);
-                if let Ok(mut collider) = collider {
-                    info_span!("update_position_hash", name = "update_position_hash");
-                    spatial_hash.remove(&collider, ev.entity);
-                    collider.update_from_transform(&transform);
-                    spatial_hash.register(&collider, ev.entity);
-                }
+        let Ok((mut transform, _)) = transforms.get_mut(ev.entity) else {
+            continue;
+        };
+        transform.translation.x += ev.movement.x;
+        transform.translation.y += ev.movement.y;
+
+        if ev.movement != Vec2::ZERO {
+            info_span!("update_position_hash", name = "update_position_hash");
+            sync_collider_tree(
+                ev.entity,
+                &transforms.to_readonly(),
+                &children,
+                &mut colliders,
+                &mut spatial_hash,
+            );
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn confine_to_world(
+    bounds: Option<Res<WorldBounds>>,
+    mut transforms: ParamSet<(
+        Query<
+            (
+                &ConfineToWorld,
+                &mut Transform,
+                Option<&mut SteeringHost>,
+                Option<&mut Velocity>,
+                Entity,
+            ),
+            Changed<Transform>,
+        >,
+        Query<(&Transform, Option<&Parent>)>,
+    )>,
+    children: Query<&Children>,
+    mut colliders: Query<&mut Collider>,
+    mut spatial_hash: ResMut<SpatialHash>,
+) {
+    let Some(bounds) = bounds else {
+        return;
+    };
+
+    let mut confined = Vec::new();
+    for (confine, mut transform, host, velocity, entity) in &mut transforms.p0() {
+        let mut area = bounds.0;
+        area.inflate(-confine.margin, -confine.margin);
+        let min = Vec2::new(area.left(), area.top());
+        let max = Vec2::new(area.right(), area.bottom()).max(min);
+
+        let position = transform.translation.xy();
+        let clamped = position.clamp(min, max);
+        if clamped == position {
+            continue;
+        }
+
+        transform.translation.x = clamped.x;
+        transform.translation.y = clamped.y;
+
+        // Removes only the part of the velocity pointing out of the bounds
+        let remove_outward = |velocity: &mut Vec2| {
+            if (clamped.x > position.x && velocity.x < 0.0)
+                || (clamped.x < position.x && velocity.x > 0.0)
+            {
+                velocity.x = 0.0;
+            }
+            if (clamped.y > position.y && velocity.y < 0.0)
+                || (clamped.y < position.y && velocity.y > 0.0)
+            {
+                velocity.y = 0.0;
             }
+        };
+        if let Some(mut host) = host {
+            remove_outward(&mut host.velocity);
         }
+        if let Some(mut velocity) = velocity {
+            remove_outward(&mut velocity.0);
+        }
+
+        confined.push(entity);
+    }
+
+    let transforms = transforms.p1();
+    for entity in confined {
+        sync_collider_tree(
+            entity,
+            &transforms,
+            &children,
+            &mut colliders,
+            &mut spatial_hash,
+        );
     }
 }
 
@@ -220,3 +1081,432 @@ fn send_pos_update(ew: &mut EventWriter<PositionUpdateEvent>, evt: &MovementCalc
         movement: evt.movement,
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    #[test]
+    fn resolution_veto_lets_mover_pass_through() {
+        let mut app = physics_app();
+        spawn_wall(&mut app, Vec2::new(20.0, 0.0), Vec2::new(16.0, 64.0));
+        let mover = spawn_collider(&mut app, circle(8.0), Vec2::ZERO);
+        app.world_mut()
+            .entity_mut(mover)
+            .insert(ResolutionVeto(|_| true));
+
+        move_by(&mut app, mover, Vec2::new(10.0, 0.0));
+        tick(&mut app);
+        assert_eq!(position(&app, mover).x, 10.0);
+    }
+
+    #[test]
+    fn resolution_veto_on_obstacle_is_consulted() {
+        let mut app = physics_app();
+        let platform = spawn_wall(&mut app, Vec2::new(20.0, 0.0), Vec2::new(16.0, 64.0));
+        // passable only from the left
+        app.world_mut()
+            .entity_mut(platform)
+            .insert(ResolutionVeto(|collision| collision.normal.x < 0.0));
+        let left = spawn_collider(&mut app, circle(8.0), Vec2::ZERO);
+        let right = spawn_collider(&mut app, circle(8.0), Vec2::new(40.0, 0.0));
+
+        move_by(&mut app, left, Vec2::new(10.0, 0.0));
+        move_by(&mut app, right, Vec2::new(-10.0, 0.0));
+        tick(&mut app);
+        assert_eq!(position(&app, left).x, 10.0);
+        assert!(position(&app, right).x >= 36.0);
+    }
+
+    #[test]
+    fn velocity_only_entity_is_resolved_against_wall() {
+        let mut app = physics_app();
+        spawn_wall(&mut app, Vec2::new(40.0, 0.0), Vec2::new(16.0, 64.0));
+        let mover = spawn_collider(&mut app, circle(8.0), Vec2::ZERO);
+        app.world_mut()
+            .entity_mut(mover)
+            .insert(Velocity(Vec2::new(640.0, 0.0)));
+
+        tick(&mut app);
+        assert_eq!(position(&app, mover).x, 10.0);
+
+        for _ in 0..10 {
+            tick(&mut app);
+        }
+        let stopped = position(&app, mover);
+        assert!(stopped.x > 23.9 && stopped.x <= 24.0);
+        assert_eq!(stopped.y, 0.0);
+    }
+
+    fn invoked_triggers(app: &App) -> Vec<(Entity, Entity)> {
+        let events = app.world().resource::<Events<InvokeTriggerEvent>>();
+        events
+            .get_reader()
+            .read(events)
+            .map(|evt| (evt.entity_main, evt.entity_trigger))
+            .collect()
+    }
+
+    #[test]
+    fn moving_trigger_passes_solid_without_invoking() {
+        let mut app = physics_app();
+        spawn_wall(&mut app, Vec2::new(20.0, 0.0), Vec2::new(16.0, 64.0));
+        let mut trigger = circle(8.0);
+        trigger.is_trigger = true;
+        let mover = spawn_collider(&mut app, trigger, Vec2::ZERO);
+
+        move_by(&mut app, mover, Vec2::new(10.0, 0.0));
+        tick(&mut app);
+
+        assert_eq!(position(&app, mover).x, 10.0);
+        assert!(invoked_triggers(&app).is_empty());
+    }
+
+    #[test]
+    fn moving_trigger_invokes_entered_trigger() {
+        let mut app = physics_app();
+        let mut zone = rect(16.0, 64.0);
+        zone.is_trigger = true;
+        let zone = spawn_collider(&mut app, zone, Vec2::new(20.0, 0.0));
+        let mut trigger = circle(8.0);
+        trigger.is_trigger = true;
+        let mover = spawn_collider(&mut app, trigger, Vec2::ZERO);
+
+        move_by(&mut app, mover, Vec2::new(10.0, 0.0));
+        tick(&mut app);
+
+        assert_eq!(position(&app, mover).x, 10.0);
+        assert_eq!(invoked_triggers(&app), vec![(mover, zone)]);
+    }
+
+    #[test]
+    fn box_is_pushed_out_of_wall_along_its_normal() {
+        let mut app = physics_app();
+        spawn_wall(&mut app, Vec2::new(30.0, 20.0), Vec2::new(16.0, 64.0));
+        let mover = spawn_collider(&mut app, rect(16.0, 16.0), Vec2::ZERO);
+
+        move_by(&mut app, mover, Vec2::new(20.0, 5.0));
+        tick(&mut app);
+
+        let position = position(&app, mover);
+        assert!(position.x > 13.9 && position.x <= 14.0);
+        assert_eq!(position.y, 5.0);
+    }
+
+    #[test]
+    fn circle_sliding_along_wall_doesnt_jitter() {
+        let mut app = physics_app();
+        spawn_wall(&mut app, Vec2::new(0.0, -20.0), Vec2::new(1000.0, 16.0));
+        let mover = spawn_collider(&mut app, circle(8.0), Vec2::new(-200.0, 0.0));
+
+        let mut positions = vec![];
+        for _ in 0..20 {
+            move_by(&mut app, mover, Vec2::new(5.0, -10.0));
+            tick(&mut app);
+            positions.push(position(&app, mover));
+        }
+
+        let resting = positions[0].y;
+        assert!((-4.0..-3.9).contains(&resting));
+        for pair in positions.windows(2) {
+            assert!((pair[1].y - resting).abs() < 1e-4, "{}", pair[1]);
+            assert!(pair[1].x > pair[0].x);
+        }
+    }
+
+    #[test]
+    fn colliders_moving_towards_each_other_collide() {
+        let mut app = physics_app();
+        let a = spawn_collider(&mut app, circle(8.0), Vec2::new(-30.0, 0.0));
+        let b = spawn_collider(&mut app, circle(8.0), Vec2::new(30.0, 0.0));
+
+        // each of the motions alone ends far from the other circle's current position
+        move_by(&mut app, a, Vec2::new(25.0, 0.0));
+        move_by(&mut app, b, Vec2::new(-25.0, 0.0));
+        tick(&mut app);
+
+        let (a, b) = (position(&app, a), position(&app, b));
+        assert!(a.x < b.x);
+        assert!(b.x - a.x >= 15.9, "{a} {b}");
+        assert_eq!(a.y, 0.0);
+        assert_eq!(b.y, 0.0);
+    }
+
+    #[test]
+    fn fixed_step_trajectory_doesnt_depend_on_frame_rate() {
+        use std::time::Duration;
+
+        use bevy::time::{TimePlugin, TimeUpdateStrategy};
+
+        #[derive(Resource, Default)]
+        struct Trajectory(Vec<Vec2>);
+
+        fn record(mut trajectory: ResMut<Trajectory>, hosts: Query<&Transform, With<SteerSeek>>) {
+            trajectory.0.push(hosts.single().translation.xy());
+        }
+
+        let trajectory = |frame_time: Duration| {
+            let mut app = App::new();
+            app.add_plugins((TaskPoolPlugin::default(), TimePlugin))
+                .add_plugins(PhysicsPlugin::default())
+                .insert_resource(TimeUpdateStrategy::ManualDuration(frame_time))
+                .init_resource::<Trajectory>()
+                .add_systems(FixedPostUpdate, record);
+            let host = spawn_host(&mut app, PhysicalParams::default());
+            app.world_mut()
+                .entity_mut(host)
+                .insert((SteerSeek, SteeringTargetVec2::new(Vec2::new(300.0, 100.0))));
+
+            while app.world().resource::<Trajectory>().0.len() < 64 {
+                app.update();
+            }
+            app.world_mut().remove_resource::<Trajectory>().unwrap().0
+        };
+
+        let slow = trajectory(Duration::from_millis(50));
+        let fast = trajectory(Duration::from_millis(4));
+
+        let steps = slow.len().min(fast.len());
+        assert_eq!(slow[..steps], fast[..steps]);
+        assert!(slow[steps - 1].x > 0.0);
+    }
+
+    #[test]
+    fn circle_wedged_into_corner_overlaps_neither_wall() {
+        let mut app = physics_app();
+        let floor = spawn_wall(&mut app, Vec2::new(0.0, -24.0), Vec2::new(200.0, 16.0));
+        let side = spawn_wall(&mut app, Vec2::new(24.0, 0.0), Vec2::new(16.0, 200.0));
+        let mover = spawn_collider(&mut app, circle(8.0), Vec2::ZERO);
+
+        for _ in 0..5 {
+            move_by(&mut app, mover, Vec2::new(12.0, -9.0));
+            tick(&mut app);
+
+            let world = app.world();
+            let collider = world.get::<Collider>(mover).unwrap();
+            for wall in [floor, side] {
+                let wall = world.get::<Collider>(wall).unwrap();
+                assert!(collider.collides_with(wall).is_none());
+            }
+        }
+
+        // resting in the corner
+        let position = position(&app, mover);
+        assert!(position.x > 7.9 && position.x <= 8.0, "{position}");
+        assert!(position.y < -7.9 && position.y >= -8.0, "{position}");
+    }
+
+    #[test]
+    fn fast_mover_invokes_thin_trigger() {
+        let mut app = physics_app();
+        let mut tripwire = rect(2.0, 64.0);
+        tripwire.is_trigger = true;
+        let tripwire = spawn_collider(&mut app, tripwire, Vec2::new(50.0, 0.0));
+        let mover = spawn_collider(&mut app, circle(4.0), Vec2::ZERO);
+
+        // jumping over the tripwire within a single tick
+        move_by(&mut app, mover, Vec2::new(100.0, 0.0));
+        tick(&mut app);
+
+        assert_eq!(position(&app, mover).x, 100.0);
+        assert_eq!(invoked_triggers(&app), vec![(mover, tripwire)]);
+    }
+
+    #[test]
+    fn coincident_spawned_circles_are_separated() {
+        let mut app = physics_app();
+        let depenetration = DepenetrationOnSpawn::default();
+        let a = spawn_collider(&mut app, circle(8.0), Vec2::new(50.0, 50.0));
+        let b = spawn_collider(&mut app, circle(8.0), Vec2::new(50.0, 50.0));
+        app.world_mut().entity_mut(a).insert(depenetration);
+        app.world_mut().entity_mut(b).insert(depenetration);
+
+        tick(&mut app);
+
+        let colliders = [a, b].map(|entity| app.world().get::<Collider>(entity).unwrap());
+        assert!(colliders[0].collides_with(colliders[1]).is_none());
+        assert!(position(&app, a).distance(position(&app, b)) >= 16.0);
+        assert!(app.world().get::<DepenetrationOnSpawn>(a).is_none());
+        assert!(app.world().get::<DepenetrationOnSpawn>(b).is_none());
+    }
+
+    #[test]
+    fn static_collider_observes_hit() {
+        #[derive(Resource, Default)]
+        struct Hits(Vec<(Entity, Entity)>);
+
+        let mut app = physics_app();
+        app.init_resource::<Hits>();
+        let wall = spawn_wall(&mut app, Vec2::new(20.0, 0.0), Vec2::new(16.0, 64.0));
+        let mover = spawn_collider(&mut app, circle(8.0), Vec2::ZERO);
+        app.world_mut().entity_mut(wall).observe(
+            |trigger: Trigger<CollideEvent>, mut hits: ResMut<Hits>| {
+                let event = trigger.event();
+                hits.0.push((event.entity_main, event.collided_with));
+            },
+        );
+
+        move_by(&mut app, mover, Vec2::new(2.0, 0.0));
+        tick(&mut app);
+        assert!(app.world().resource::<Hits>().0.is_empty());
+
+        move_by(&mut app, mover, Vec2::new(10.0, 0.0));
+        tick(&mut app);
+        assert_eq!(app.world().resource::<Hits>().0, vec![(wall, mover)]);
+    }
+
+    #[test]
+    fn disabling_layer_mid_run_lets_mover_pass_through() {
+        let mut app = physics_app();
+        let wall = spawn_wall(&mut app, Vec2::new(20.0, 0.0), Vec2::new(16.0, 64.0));
+        app.world_mut()
+            .get_mut::<Collider>(wall)
+            .unwrap()
+            .physics_layer = 0b10;
+        let mover = spawn_collider(&mut app, circle(8.0), Vec2::ZERO);
+        let skin = PhysicsConfig::default().skin_width;
+
+        // the wall spans from 12 to 28
+        move_by(&mut app, mover, Vec2::new(10.0, 0.0));
+        tick(&mut app);
+        let stopped = position(&app, mover).x;
+        assert!((stopped - (12.0 - 8.0 - skin)).abs() < 1e-4, "{stopped}");
+
+        // the mover ends up fully past the wall
+        app.world_mut().resource_mut::<DisabledLayers>().0 = 0b10;
+        move_by(&mut app, mover, Vec2::new(40.0, 0.0));
+        tick(&mut app);
+        let passed = position(&app, mover).x;
+        assert_eq!(passed, stopped + 40.0);
+        assert!(passed - 8.0 > 28.0, "{passed}");
+
+        // the wall blocks the mover again once the layer is enabled
+        app.world_mut().resource_mut::<DisabledLayers>().0 = 0;
+        move_by(&mut app, mover, Vec2::new(-10.0, 0.0));
+        tick(&mut app);
+        let blocked = position(&app, mover).x;
+        assert!((blocked - (28.0 + 8.0 + skin)).abs() < 1e-4, "{blocked}");
+    }
+
+    #[test]
+    fn identical_runs_end_at_identical_positions() {
+        fn run() -> Vec<Vec2> {
+            let mut app = physics_app();
+            spawn_wall(&mut app, Vec2::new(100.0, 0.0), Vec2::new(16.0, 200.0));
+            spawn_wall(&mut app, Vec2::new(40.0, 100.0), Vec2::new(136.0, 16.0));
+            let hosts: Vec<Entity> = (0..16)
+                .map(|i| {
+                    let host = spawn_host(&mut app, PhysicalParams::default());
+                    let position = Vec2::new((i % 4) as f32 * 12.0, (i / 4) as f32 * 12.0);
+                    app.world_mut().entity_mut(host).insert((
+                        circle(6.0),
+                        Transform::from_xyz(position.x, position.y, 0.0),
+                        SteerSeek,
+                        SteeringTargetVec2::new(Vec2::new(200.0, 100.0)),
+                    ));
+                    host
+                })
+                .collect();
+
+            for _ in 0..120 {
+                tick(&mut app);
+            }
+
+            hosts.iter().map(|host| position(&app, *host)).collect()
+        }
+
+        let first = run();
+        assert!(first.iter().all(|position| position.is_finite()));
+        assert_eq!(first, run());
+    }
+
+    #[test]
+    fn contact_lifecycle_begins_persists_and_ends() {
+        fn read<E: Event + Copy>(app: &App) -> Vec<E> {
+            let events = app.world().resource::<Events<E>>();
+            events.get_reader().read(events).copied().collect()
+        }
+        let counts = |app: &App| {
+            (
+                read::<ContactBegan>(app).len(),
+                read::<ContactPersisted>(app).len(),
+                read::<ContactEnded>(app).len(),
+            )
+        };
+
+        let mut app = physics_app();
+        let wall = spawn_wall(&mut app, Vec2::new(20.0, 0.0), Vec2::new(16.0, 64.0));
+        let mover = spawn_collider(&mut app, circle(8.0), Vec2::ZERO);
+        let id = ContactId::new(mover, wall);
+
+        move_by(&mut app, mover, Vec2::new(10.0, 0.0));
+        tick(&mut app);
+        assert_eq!(counts(&app), (1, 0, 0));
+        let began = read::<ContactBegan>(&app)[0];
+        assert_eq!(began.id, id);
+        // pointing from `id.b` towards `id.a`
+        let towards_mover = if id.a == mover { -Vec2::X } else { Vec2::X };
+        assert!(
+            began.normal.abs_diff_eq(towards_mover, 1e-4),
+            "{}",
+            began.normal
+        );
+        assert_eq!(
+            app.world().resource::<Contacts>().get(id).unwrap().normal,
+            began.normal
+        );
+
+        for persisted in 1..=2 {
+            move_by(&mut app, mover, Vec2::new(5.0, 0.0));
+            tick(&mut app);
+            assert_eq!(counts(&app), (1, persisted, 0));
+        }
+        assert!(read::<ContactPersisted>(&app)
+            .iter()
+            .all(|evt| evt.id == id));
+
+        move_by(&mut app, mover, Vec2::new(-10.0, 0.0));
+        tick(&mut app);
+        assert_eq!(counts(&app), (1, 2, 1));
+        assert_eq!(read::<ContactEnded>(&app)[0].id, id);
+        assert!(app.world().resource::<Contacts>().get(id).is_none());
+
+        tick(&mut app);
+        assert_eq!(counts(&app), (1, 2, 1));
+    }
+
+    #[test]
+    fn world_bounds_clamp_and_remove_outward_velocity() {
+        let mut app = physics_app();
+        app.insert_resource(WorldBounds(FRect::new(0.0, 0.0, 100.0, 100.0)));
+        let host = spawn_host(&mut app, PhysicalParams::default());
+        app.world_mut().entity_mut(host).insert((
+            circle(8.0),
+            ConfineToWorld::new(8.0),
+            Transform::from_xyz(90.0, 50.0, 0.0),
+        ));
+        app.world_mut()
+            .get_mut::<SteeringHost>(host)
+            .unwrap()
+            .apply_impulse(Vec2::new(400.0, 100.0));
+        let body = spawn_collider(&mut app, circle(8.0), Vec2::new(20.0, 20.0));
+        app.world_mut()
+            .entity_mut(body)
+            .insert((ConfineToWorld::new(8.0), Velocity(Vec2::new(-2000.0, 0.0))));
+
+        tick(&mut app);
+
+        let host_position = position(&app, host);
+        assert_eq!(host_position.x, 92.0);
+        assert!(host_position.y > 50.0);
+        let velocity = app.world().get::<SteeringHost>(host).unwrap().velocity;
+        assert_eq!(velocity.x, 0.0);
+        assert!(velocity.y > 0.0);
+        let bounds = with_hash(&mut app, |hash, _| hash.registered_bounds(host).unwrap());
+        assert_eq!(bounds.right(), 100.0);
+
+        assert_eq!(position(&app, body), Vec2::new(8.0, 20.0));
+        assert_eq!(app.world().get::<Velocity>(body).unwrap().0, Vec2::ZERO);
+    }
+}
@@ -1,4 +1,5 @@
 pub use crate::collisions::*;
+pub use crate::diagnostics::*;
 pub use crate::movement::*;
 pub use crate::plugin::*;
 pub use bevy::prelude::*;
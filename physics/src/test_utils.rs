@@ -0,0 +1,101 @@
+//! Helpers shared by the unit tests of the crate.
+
+use std::time::Duration;
+
+use bevy::{ecs::system::SystemState, prelude::*};
+
+use crate::prelude::*;
+use colliders::Collider;
+use shapes::ShapeType;
+use spatial_hash::SpatialHash;
+use steering::{PhysicalParams, SteeringBundle};
+
+/// Fixed timestep `tick` advances the time by, the default one of `Time<Fixed>`.
+pub(crate) const TICK: Duration = Duration::from_micros(15_625);
+
+/// Creates an `App` with `PhysicsPlugin` whose `FixedUpdate` is run manually by `tick`.
+pub(crate) fn physics_app() -> App {
+    let mut app = App::new();
+    app.init_resource::<Time>()
+        .add_plugins(PhysicsPlugin::default());
+    app
+}
+
+/// Runs a single `FixedUpdate` tick.
+pub(crate) fn tick(app: &mut App) {
+    app.world_mut().resource_mut::<Time>().advance_by(TICK);
+    app.world_mut().run_schedule(FixedUpdate);
+}
+
+pub(crate) fn circle(radius: f32) -> Collider {
+    Collider::new(ShapeType::Circle { radius })
+}
+
+pub(crate) fn rect(width: f32, height: f32) -> Collider {
+    Collider::new(ShapeType::Box { width, height })
+}
+
+/// Spawns an entity with the collider at `position`, it's registered in the `SpatialHash` at once.
+pub(crate) fn spawn_collider(app: &mut App, collider: Collider, position: Vec2) -> Entity {
+    app.world_mut()
+        .spawn((
+            SpatialBundle::from_transform(Transform::from_xyz(position.x, position.y, 0.0)),
+            collider,
+        ))
+        .id()
+}
+
+/// Spawns a `RigidBodyStatic` box centered at `position`.
+pub(crate) fn spawn_wall(app: &mut App, position: Vec2, size: Vec2) -> Entity {
+    let wall = spawn_collider(app, rect(size.x, size.y), position);
+    app.world_mut().entity_mut(wall).insert(RigidBodyStatic);
+    wall
+}
+
+/// Runs `f` with the `SpatialHash` and the colliders of the app.
+pub(crate) fn with_hash<R>(
+    app: &mut App,
+    f: impl FnOnce(&SpatialHash, &Query<&Collider>) -> R,
+) -> R {
+    let mut state = SystemState::<(Res<SpatialHash>, Query<&Collider>)>::new(app.world_mut());
+    let (spatial_hash, colliders) = state.get(app.world());
+    f(&spatial_hash, &colliders)
+}
+
+/// Same as `with_hash` but the `SpatialHash` can be modified.
+pub(crate) fn with_hash_mut<R>(
+    app: &mut App,
+    f: impl FnOnce(&mut SpatialHash, &Query<&Collider>) -> R,
+) -> R {
+    let mut state = SystemState::<(ResMut<SpatialHash>, Query<&Collider>)>::new(app.world_mut());
+    let (mut spatial_hash, colliders) = state.get_mut(app.world_mut());
+    f(&mut spatial_hash, &colliders)
+}
+
+/// Gets the translation of the entity.
+pub(crate) fn position(app: &App, entity: Entity) -> Vec2 {
+    app.world()
+        .get::<Transform>(entity)
+        .unwrap()
+        .translation
+        .xy()
+}
+
+/// Sends a `MovementCalculateEvent` for the entity.
+pub(crate) fn move_by(app: &mut App, entity: Entity, movement: Vec2) {
+    app.world_mut()
+        .send_event(crate::MovementCalculateEvent { entity, movement });
+}
+
+/// Spawns a steering host with the parameters at the origin.
+pub(crate) fn spawn_host(app: &mut App, params: PhysicalParams) -> Entity {
+    app.world_mut()
+        .spawn((
+            SpatialBundle::default(),
+            SteeringBundle {
+                physics_params: params,
+                ..default()
+            },
+        ))
+        .id()
+}
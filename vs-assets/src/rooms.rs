@@ -15,23 +15,53 @@ pub struct MapAsset {
 }
 
 impl MapAsset {
+    /// Gets the collision rectangles of the layer in the map's pixel space (y goes down).
+    /// The tiles of a tile layer are merged into as few rectangles as possible,
+    /// the rect objects of an object layer are returned as is and the layers
+    /// of a group layer are gathered recursively.
     pub fn get_collision_rects(&self, collision_layer: &str) -> Vec<FRect> {
+        let layer = self
+            .find_layer_by_name(collision_layer)
+            .expect("No layer found");
+
+        let mut rectangles = vec![];
+        self.collect_collision_rects(layer, &mut rectangles);
+        rectangles
+    }
+
+    fn collect_collision_rects(&self, layer: tiled::Layer, rectangles: &mut Vec<FRect>) {
+        match layer.layer_type() {
+            tiled::LayerType::Tiles(tiles) => {
+                rectangles.extend(self.get_tile_collision_rects(&tiles));
+            }
+            tiled::LayerType::Objects(objects) => {
+                rectangles.extend(objects.objects().filter_map(|obj| match &obj.shape {
+                    tiled::ObjectShape::Rect { width, height } => {
+                        Some(FRect::new(obj.x, obj.y, *width, *height))
+                    }
+                    _ => None,
+                }));
+            }
+            tiled::LayerType::Group(group) => {
+                for group_layer in group.layers() {
+                    self.collect_collision_rects(group_layer, rectangles);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Merges the tiles of the layer into rectangles.
+    fn get_tile_collision_rects(&self, tiles: &tiled::TileLayer) -> Vec<FRect> {
         let mut checked_indexes = vec![false; (self.map.width * self.map.height) as usize];
         let mut rectangles = vec![];
         let mut start_col: i32 = -1;
         let mut index: i32;
 
-        let layer = self
-            .find_layer_by_name(collision_layer)
-            .expect("No layer found");
-
         for y in 0..self.map.height {
             for x in 0..self.map.width {
                 index = (y * self.map.width + x) as i32;
-                let tile = match layer.layer_type() {
-                    tiled::LayerType::Tiles(tile) => tile.get_tile(x as i32, y as i32),
-                    _ => None,
-                };
+                let tile = tiles.get_tile(x as i32, y as i32);
 
                 if tile.is_some() && !checked_indexes[index as usize] {
                     if start_col < 0 {
@@ -40,7 +70,7 @@ impl MapAsset {
                     checked_indexes[index as usize] = true;
                 } else if (tile.is_none() || checked_indexes[index as usize]) && start_col >= 0 {
                     rectangles.push(self.find_bounds_rect(
-                        collision_layer,
+                        tiles,
                         start_col,
                         x as i32,
                         y as i32,
@@ -52,7 +82,7 @@ impl MapAsset {
 
             if start_col >= 0 {
                 rectangles.push(self.find_bounds_rect(
-                    collision_layer,
+                    tiles,
                     start_col,
                     self.map.width as i32,
                     y as i32,
@@ -67,29 +97,22 @@ impl MapAsset {
 
     pub fn find_bounds_rect(
         &self,
-        collision_layer: &str,
+        tiles: &tiled::TileLayer,
         start_x: i32,
         end_x: i32,
         start_y: i32,
         checked_indexes: &mut [bool],
     ) -> FRect {
         let mut index;
-        let layer = self
-            .find_layer_by_name(collision_layer)
-            .expect("No layer found");
 
         for y in (start_y + 1)..self.map.height as i32 {
             for x in start_x..end_x {
                 index = y * self.map.width as i32 + x;
-                let tile = match layer.layer_type() {
-                    tiled::LayerType::Tiles(tile) => tile.get_tile(x, y),
-                    // TODO: Check tiled::LayerType::Group
-                    _ => None,
-                };
+                let tile = tiles.get_tile(x, y);
 
                 if tile.is_none() || checked_indexes[index as usize] {
-                    for _x in start_x..x {
-                        index = y * self.map.width as i32 + x;
+                    for prev_x in start_x..x {
+                        index = y * self.map.width as i32 + prev_x;
                         checked_indexes[index as usize] = false;
                     }
 
@@ -149,8 +172,9 @@ impl RoomStore {
             .push((map_handle, size));
     }
 
+    /// Gets the rooms of the map with their sizes in tiles. Empty if there are no such rooms.
     pub fn get_rooms(&self, map_id: u32) -> &[(Handle<MapAsset>, UVec2)] {
-        &self.rooms[&map_id]
+        self.rooms.get(&map_id).map_or(&[], Vec::as_slice)
     }
 
     pub fn get_room_by_index(&self, map_id: u32, index: usize) -> &(Handle<MapAsset>, UVec2) {
@@ -230,3 +254,75 @@ impl AssetLoader for MapAssetLoader {
         &["tmx"]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    /// Loads a 4x3 map of 32x32 tiles made of `layers`.
+    fn map_asset(layers: &str) -> MapAsset {
+        let tmx = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<map version="1.10" orientation="orthogonal" renderorder="right-down" width="4" height="3" tilewidth="32" tileheight="32" infinite="0">
+ <tileset firstgid="1" name="walls" tilewidth="32" tileheight="32" tilecount="1" columns="1"/>
+{}
+</map>"#,
+            layers
+        );
+
+        let reader = move |_: &std::path::Path| {
+            Ok::<_, std::io::Error>(Cursor::new(tmx.clone().into_bytes()))
+        };
+        let map = tiled::Loader::with_reader(reader)
+            .load_tmx_map("room.tmx")
+            .expect("could not load the map");
+
+        MapAsset {
+            name: "room".to_string(),
+            map_id: 1,
+            map,
+        }
+    }
+
+    #[test]
+    fn object_layer_rects_are_returned() {
+        let map = map_asset(
+            r#" <objectgroup id="1" name="collision">
+  <object id="1" x="10" y="20" width="30" height="40"/>
+  <object id="2" x="50" y="50" width="8" height="8">
+   <ellipse/>
+  </object>
+ </objectgroup>"#,
+        );
+
+        assert_eq!(
+            map.get_collision_rects("collision"),
+            vec![FRect::new(10., 20., 30., 40.)]
+        );
+    }
+
+    #[test]
+    fn group_layer_rects_are_gathered() {
+        let map = map_asset(
+            r#" <group id="1" name="collision">
+  <layer id="2" name="tiles" width="4" height="3">
+   <data encoding="csv">
+0,0,0,0,
+1,1,0,0,
+0,0,0,0
+   </data>
+  </layer>
+  <objectgroup id="3" name="objects">
+   <object id="1" x="96" y="0" width="16" height="96"/>
+  </objectgroup>
+ </group>"#,
+        );
+
+        assert_eq!(
+            map.get_collision_rects("collision"),
+            vec![FRect::new(0., 32., 64., 32.), FRect::new(96., 0., 16., 96.)]
+        );
+    }
+}
@@ -4,6 +4,8 @@ use bevy::prelude::*;
 #[cfg(debug_assertions)]
 use bevy_inspector_egui::quick::WorldInspectorPlugin;
 use colliders::Collider;
+use interpolation::PhysicsInterpolation;
+use raycast_debug::{draw_raycasts, RaycastDebugBuffer};
 use steering::SteeringHost;
 
 use crate::enemy::Enemy;
@@ -30,9 +32,17 @@ impl Plugin for DebugPlugin {
 
         app.add_systems(Startup, (add_enemy_count,));
         app.add_systems(FixedUpdate, (update_enemy_count, update_fps));
-        app.add_systems(Update, (handle_input, debug_draw));
+        app.add_systems(
+            Update,
+            (
+                handle_input,
+                debug_draw.run_if(|settings: Res<DebugSettings>| settings.collider_draw_enabled),
+                draw_raycasts,
+            ),
+        );
 
         app.insert_resource(DebugSettings::default());
+        app.insert_resource(RaycastDebugBuffer::default());
     }
 }
 
@@ -106,6 +116,9 @@ fn update_enemy_count(
 fn handle_input(
     keyboard_input: Res<ButtonInput<KeyCode>>,
     mut debug_settings: ResMut<DebugSettings>,
+    mut raycast_buffer: ResMut<RaycastDebugBuffer>,
+    player: Query<(Entity, Has<PhysicsInterpolation>), With<Player>>,
+    mut commands: Commands,
 ) {
     if keyboard_input.just_pressed(KeyCode::KeyG) {
         debug_settings.collider_draw_enabled = !debug_settings.collider_draw_enabled;
@@ -113,16 +126,20 @@ fn handle_input(
     if keyboard_input.just_pressed(KeyCode::KeyH) {
         debug_settings.disable_enemy_spawns = !debug_settings.disable_enemy_spawns;
     }
-}
-
-#[cfg(debug_assertions)]
-fn debug_draw(
-    debug_settings: Res<DebugSettings>,
-    //collider_store: Res<ColliderStore>,
-    mut gizmos: Gizmos,
-) {
-    if debug_settings.collider_draw_enabled {
-        //collider_store.debug_draw(&mut gizmos);
+    if keyboard_input.just_pressed(KeyCode::KeyK) {
+        raycast_buffer.enabled = !raycast_buffer.enabled;
+        raycast_buffer.clear();
+    }
+    if keyboard_input.just_pressed(KeyCode::KeyJ) {
+        if let Ok((player, interpolated)) = player.get_single() {
+            if interpolated {
+                commands.entity(player).remove::<PhysicsInterpolation>();
+            } else {
+                commands
+                    .entity(player)
+                    .insert(PhysicsInterpolation::default());
+            }
+        }
     }
 }
 
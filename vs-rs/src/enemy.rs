@@ -291,20 +291,24 @@ fn spawn(
                                 //SteerSeek,
                                 SteerArrival {
                                     slowing_radius: 64.0,
+                                    ..default()
                                 },
-                                //SteerCollisionAvoidance::default(),
-                                SteeringTargetEntity(player_entity),
+                                // Route around the walls spawned by worldgen on layer 1
+                                SteerCollisionAvoidance::default().with_layer_mask(0b1),
+                                SteeringTargetEntity::new(player_entity),
                                 EnemyDamageTimer(Timer::new(
                                     Duration::from_secs(1),
                                     TimerMode::Repeating,
                                 )),
                                 ExperienceDrop(spawner.exp_drop),
+                                // The batch may spawn enemies on top of each other
+                                DepenetrationOnSpawn::default(),
                             ));
                             if let Some(true) = spawner.is_elite {
                                 break;
                             }
                         }
-                        commands.spawn_batch(enemy_batch)
+                        commands.spawn_colliders(enemy_batch)
                     }
                 }
             }
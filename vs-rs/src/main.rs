@@ -65,7 +65,7 @@ fn main() {
     .add_plugins(UiPlugin)
     .add_plugins(CameraMovementPlugin)
     .add_plugins(PlayerPlugin)
-    .add_plugins(PhysicsPlugin)
+    .add_plugins(PhysicsPlugin::default())
     .add_plugins(EnemyPlugin)
     .add_plugins(ExperiencePlugin)
     .add_systems(Startup, (spawn_camera, setup_gamepad))
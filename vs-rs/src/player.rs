@@ -3,6 +3,7 @@ use crate::input::PlayerControls;
 use crate::stats::*;
 use crate::AppState;
 use crate::ui::health_bar::spawn_health_bar;
+use animation::{DirectionalFrames, SpriteFacing, VelocityAnimation};
 use behaviors::SteerSeek;
 use bevy::sprite::Anchor;
 use bevy::{input::gamepad::GamepadSettings, prelude::*};
@@ -12,7 +13,7 @@ use physics::CollideEvent;
 use shapes::Shape;
 use std::time::Duration;
 use steering::SteeringBundle;
-use steering::SteeringTargetVec2;
+use steering::{SteeringEnabled, SteeringTargetVec2};
 use vs_assets::plugin::GameAssets;
 use vs_assets::plugin::UiAssets;
 
@@ -27,8 +28,7 @@ impl Plugin for PlayerPlugin {
             )
             .add_systems(
                 Update,
-                (update_timer, handle_input, update_sprite, update_atlas)
-                    .run_if(in_state(AppState::Finished)),
+                (update_timer, handle_input).run_if(in_state(AppState::Finished)),
             );
     }
 }
@@ -94,6 +94,15 @@ fn spawn(mut commands: Commands, assets: Res<GameAssets>, ui_assets: Res<UiAsset
             },
             SteerSeek,
             SteeringTargetVec2::default(),
+            SteeringEnabled(false),
+            VelocityAnimation::default()
+                .with_flip(SpriteFacing::Left)
+                .with_frames(DirectionalFrames {
+                    idle: 0,
+                    up: 1,
+                    down: 0,
+                    side: 2,
+                }),
         ))
         .with_children(|c| {
             c.spawn((
@@ -189,35 +198,25 @@ fn handle_input(
 }
 
 fn movement(
-    mut steering_host: Query<(&mut SteeringTargetVec2, &Transform, &Direction), With<Player>>,
+    mut steering_host: Query<
+        (
+            &mut SteeringTargetVec2,
+            &mut SteeringEnabled,
+            &Transform,
+            &Direction,
+        ),
+        With<Player>,
+    >,
 ) {
-    if let Ok((mut target, pos, dir)) = steering_host.get_single_mut() {
-        target.0 = if dir.0 == Vec2::ZERO {
-            Vec2::ZERO
-        } else {
-            pos.translation.xy() + dir.0 * 10.0
-        };
-    }
-}
-
-fn update_atlas(mut query: Query<(&Direction, &mut TextureAtlas), With<Player>>) {
-    if let Ok((dir, mut atlas)) = query.get_single_mut() {
-        if dir.0.y > 0.0 {
-            atlas.index = 1;
-        } else if dir.0.x != 0.0 {
-            atlas.index = 2;
-        } else {
-            atlas.index = 0;
+    if let Ok((mut target, mut enabled, pos, dir)) = steering_host.get_single_mut() {
+        // Without input the player isn't steered at all and slows down via friction
+        enabled.0 = dir.0 != Vec2::ZERO;
+        if enabled.0 {
+            target.0 = Some(pos.translation.xy() + dir.0 * 10.0);
         }
     }
 }
 
-fn update_sprite(mut query: Query<(&Direction, &mut Sprite), With<Player>>) {
-    if let Ok((dir, mut sprite)) = query.get_single_mut() {
-        sprite.flip_x = dir.0.x > 0.;
-    }
-}
-
 fn on_collision(
     trigger: Trigger<CollideEvent>,
     damage_query: Query<&Damage, (With<Enemy>, Without<Player>)>,
@@ -35,9 +35,14 @@ fn spawn_world(
         ..default()
     };
 
-    let world_comp = WorldComponent {
-        world: world_gen.generate(settings),
+    let world = match world_gen.generate(settings) {
+        Ok(world) => world,
+        Err(err) => {
+            error!("could not generate the world: {}", err);
+            return;
+        }
     };
+    let world_comp = WorldComponent { world };
 
     let lower_tilemap = world_to_tilemap(&world_comp.world, &assets.tilesheet_main, &map_assets);
 
@@ -52,6 +57,18 @@ fn spawn_world(
         Vec2::new(x, y),
     );
 
+    // Tiles are centered at their positions, so the grid starts half a tile earlier
+    commands.insert_resource(
+        world_comp
+            .world
+            .nav_grid(Vec2::new(32.0, 32.0), Vec2::new(x - 16.0, y - 16.0)),
+    );
+    commands.insert_resource(WorldBounds(
+        world_comp
+            .world
+            .bounds(Vec2::new(32.0, 32.0), Vec2::new(x - 16.0, y - 16.0)),
+    ));
+
     let mut upper_tilemap = TileMap::default();
 
     world_comp
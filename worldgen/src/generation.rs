@@ -6,8 +6,8 @@ use bevy::{
     log::info,
     math::{UVec2, Vec2},
 };
-use common::{math::choose_random, prim::PrimEdge, FRect};
-use rand::{thread_rng, Rng};
+use common::{math::choose_random_with, prim::PrimEdge, FRect};
+use rand::{rngs::StdRng, Rng, SeedableRng};
 use room::WorldRoom;
 use settings::WorldGeneratorSettings;
 use stages::*;
@@ -83,6 +83,15 @@ impl GridGraphPos {
         true
     }
 
+    /// Checks whether all the neighbors of the room cell are room cells as well.
+    fn is_room_interior(&self, world: &IntermediateWorld, pos: GridGraphPos) -> bool {
+        [(1, 0), (0, -1), (-1, 0), (0, 1)].iter().all(|dir| {
+            let next = GridGraphPos(pos.0 + dir.0, pos.1 + dir.1);
+            self.is_in_bounds(&next, world.width, world.height)
+                && world.grid[next.1 as usize][next.0 as usize] == CellType::Room
+        })
+    }
+
     fn get_cost(
         &self,
         world: &IntermediateWorld,
@@ -95,6 +104,7 @@ impl GridGraphPos {
 
         match world.grid[pos.1 as usize][pos.0 as usize] {
             CellType::None => setting.cost_empty_space,
+            CellType::Room if self.is_room_interior(world, pos) => setting.cost_room_interior,
             CellType::Room => setting.cost_room,
             CellType::Hallway => setting.cost_hallway,
             CellType::Wall => setting.cost_wall,
@@ -107,22 +117,21 @@ pub struct WorldPoint {
     pub y: u32,
 }
 
-fn gen_point(min_w: u32, min_h: u32, max_w: u32, max_h: u32) -> WorldPoint {
-    let mut rng = thread_rng();
-
+fn gen_point(rng: &mut StdRng, min_w: u32, min_h: u32, max_w: u32, max_h: u32) -> WorldPoint {
     WorldPoint {
         x: rng.gen_range(min_w..max_w),
         y: rng.gen_range(min_h..max_h),
     }
 }
 
-fn gen_room(world: &IntermediateWorld, room_store: &RoomStore) -> WorldRoom {
+fn gen_room(world: &mut IntermediateWorld, room_store: &RoomStore) -> WorldRoom {
     let all_rooms = room_store.get_rooms(world.settings.map_id);
-    let room = choose_random(all_rooms);
+    let room = choose_random_with(&mut world.rng, all_rooms);
     let size = UVec2::new(room.0 .1.x, room.0 .1.y);
 
     // genering a point that will not touch the world's border
     let pos = gen_point(
+        &mut world.rng,
         1,
         1,
         world.settings.world_width - size.x - 1,
@@ -203,10 +212,25 @@ fn get_border_points(rect: &FRect) -> Vec<(i32, i32)> {
     res
 }
 
+/// Gets the border points of the rect split by its edges (top, bottom, left and right),
+/// each edge ordered along its axis and including both corners.
+fn get_border_edges(rect: &FRect) -> [Vec<(i32, i32)>; 4] {
+    let (left, right) = (rect.left() as i32, rect.right() as i32);
+    let (top, bottom) = (rect.top() as i32, rect.bottom() as i32);
+
+    [
+        (left..=right).map(|x| (x, top)).collect(),
+        (left..=right).map(|x| (x, bottom)).collect(),
+        (top..=bottom).map(|y| (left, y)).collect(),
+        (top..=bottom).map(|y| (right, y)).collect(),
+    ]
+}
+
 type GenStages<'a> = Vec<Box<dyn WorldGenStage + 'a>>;
 
 pub struct WorldGenerator<'a> {
     pub stages: GenStages<'a>,
+    room_store: &'a RoomStore,
 }
 
 impl<'a> WorldGenerator<'a> {
@@ -218,13 +242,56 @@ impl<'a> WorldGenerator<'a> {
             Box::new(WorldGenStage4PlaceTiles {}),
             Box::new(WorldGenStageCreateWalls {}),
             Box::new(WorldGenStage5AStar {}),
+            Box::new(WorldGenStagePlaceDoors {}),
             Box::new(WorldGenStageCalcBitmapAndBitmask {}),
         ];
 
-        Self { stages }
+        Self { stages, room_store }
+    }
+
+    /// Appends the stage to the end of the pipeline.
+    pub fn add_stage(&mut self, stage: impl WorldGenStage + 'a) {
+        self.stages.push(Box::new(stage));
+    }
+
+    /// Inserts the stage right after the stage of type `T`.
+    /// Returns `false` and leaves the pipeline unchanged if there is no such stage.
+    pub fn insert_stage_after<T: WorldGenStage>(&mut self, stage: impl WorldGenStage + 'a) -> bool {
+        match self.stage_index::<T>() {
+            Some(index) => {
+                self.stages.insert(index + 1, Box::new(stage));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Removes the first stage of type `T` and returns it.
+    pub fn remove_stage<T: WorldGenStage>(&mut self) -> Option<Box<dyn WorldGenStage + 'a>> {
+        self.stage_index::<T>()
+            .map(|index| self.stages.remove(index))
     }
 
-    pub fn generate(&mut self, settings: WorldGeneratorSettings) -> World {
+    /// Replaces the first stage of type `T` with the provided one, returning the old stage.
+    pub fn replace_stage<T: WorldGenStage>(
+        &mut self,
+        stage: impl WorldGenStage + 'a,
+    ) -> Option<Box<dyn WorldGenStage + 'a>> {
+        self.stage_index::<T>()
+            .map(|index| std::mem::replace(&mut self.stages[index], Box::new(stage)))
+    }
+
+    fn stage_index<T: WorldGenStage>(&self) -> Option<usize> {
+        let name = std::any::type_name::<T>();
+        self.stages.iter().position(|stage| stage.name() == name)
+    }
+
+    /// Generates a new world.
+    ///
+    /// Returns an error if the settings are invalid, see `WorldGeneratorSettings::validate`.
+    pub fn generate(&mut self, settings: WorldGeneratorSettings) -> Result<World, &'static str> {
+        settings.validate(self.room_store)?;
+
         let w = settings.world_width as usize;
         let h = settings.world_height as usize;
 
@@ -233,8 +300,14 @@ impl<'a> WorldGenerator<'a> {
             w, h
         );
 
+        let rng = match settings.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+
         let mut world = IntermediateWorld {
             settings,
+            rng,
             grid: vec![vec![CellType::None; w]; h],
             rooms: vec![],
             width: w,
@@ -257,6 +330,129 @@ impl<'a> WorldGenerator<'a> {
         let elapsed = now.elapsed();
         info!("Finished generating. Elapsed: {:.2?}", elapsed);
 
-        world.into_world()
+        Ok(world.into_world())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use bevy::asset::Handle;
+    use vs_assets::rooms::MapAsset;
+
+    use super::*;
+
+    /// Loads the rooms of the game's first map straight from the `.tmx` files.
+    fn room_store() -> RoomStore {
+        let dir =
+            std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../vs-rs/assets/rooms/map_01");
+        let mut loader = tiled::Loader::new();
+        let mut room_store = RoomStore::default();
+
+        for name in ["room_01", "room_02", "room_03", "room_04"] {
+            let map = loader
+                .load_tmx_map(dir.join(format!("{}.tmx", name)))
+                .expect("could not load the room");
+            let map_asset = MapAsset {
+                name: name.to_string(),
+                map_id: 1,
+                map,
+            };
+            room_store.insert(Handle::default(), &map_asset);
+        }
+
+        room_store
+    }
+
+    fn small_settings(seed: u64) -> WorldGeneratorSettings {
+        WorldGeneratorSettings {
+            max_rooms: 6,
+            min_used_area: 5_000,
+            world_width: 160,
+            world_height: 160,
+            max_room_iterations: 10_000,
+            ..WorldGeneratorSettings::with_seed(seed)
+        }
+    }
+
+    #[test]
+    fn same_seed_generates_same_world() {
+        let room_store = room_store();
+
+        let first = WorldGenerator::new(&room_store)
+            .generate(small_settings(42))
+            .unwrap();
+        let second = WorldGenerator::new(&room_store)
+            .generate(small_settings(42))
+            .unwrap();
+
+        assert!(first.rooms.len() > 1);
+        assert_eq!(first.grid, second.grid);
+        assert_eq!(
+            first.rooms.iter().map(|room| room.rect).collect::<Vec<_>>(),
+            second
+                .rooms
+                .iter()
+                .map(|room| room.rect)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    /// Writes its `ID` to the shared log when executed.
+    struct RecordStage<const ID: usize>(Rc<RefCell<Vec<usize>>>);
+
+    impl<const ID: usize> WorldGenStage for RecordStage<ID> {
+        fn get_description(&self) -> &'static str {
+            "Recording the stage order"
+        }
+
+        fn execute(&mut self, _world: &mut IntermediateWorld) {
+            self.0.borrow_mut().push(ID);
+        }
+    }
+
+    #[test]
+    fn inserted_stage_runs_in_order() {
+        let log = Rc::new(RefCell::new(vec![]));
+        let room_store = room_store();
+        let mut generator = WorldGenerator {
+            stages: vec![],
+            room_store: &room_store,
+        };
+        generator.add_stage(RecordStage::<1>(log.clone()));
+        generator.add_stage(RecordStage::<2>(log.clone()));
+
+        assert!(generator.insert_stage_after::<RecordStage<1>>(RecordStage::<3>(log.clone())));
+        assert!(!generator.insert_stage_after::<RecordStage<4>>(RecordStage::<5>(log.clone())));
+
+        generator.generate(small_settings(0)).unwrap();
+
+        assert_eq!(*log.borrow(), vec![1, 3, 2]);
+    }
+
+    #[test]
+    fn room_larger_than_world_is_invalid() {
+        let room_store = room_store();
+        let settings = WorldGeneratorSettings {
+            world_width: 8,
+            world_height: 8,
+            min_used_area: 0,
+            ..small_settings(0)
+        };
+
+        assert_eq!(
+            settings.validate(&room_store),
+            Err("the largest room doesn't fit the world")
+        );
+        assert!(WorldGenerator::new(&room_store).generate(settings).is_err());
+        assert_eq!(
+            WorldGeneratorSettings {
+                map_id: 2,
+                ..small_settings(0)
+            }
+            .validate(&room_store),
+            Err("there are no rooms with the map_id")
+        );
     }
 }
@@ -1,3 +1,6 @@
+use bevy::math::UVec2;
+use vs_assets::rooms::RoomStore;
+
 use super::WorldPoint;
 
 pub struct WorldGeneratorSettings {
@@ -13,11 +16,69 @@ pub struct WorldGeneratorSettings {
     pub room_spacing: WorldPoint,
 
     pub cost_empty_space: u32,
+    /// Cost of the room's cells next to its borders.
     pub cost_room: u32,
+    /// Cost of the room's interior cells. Should be high so hallways don't cut through rooms.
+    pub cost_room_interior: u32,
     pub cost_hallway: u32,
     pub cost_wall: u32,
 
+    /// Width in tiles of the door opened in a room's wall where a hallway meets it.
+    pub door_width: u32,
+
     pub map_id: u32,
+
+    /// Seed of the world's random number generator. The same seed with the same settings
+    /// always produces the same world. A random seed is used if `None`.
+    pub seed: Option<u64>,
+}
+
+impl WorldGeneratorSettings {
+    /// Creates the default settings with the provided seed.
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            seed: Some(seed),
+            ..Default::default()
+        }
+    }
+
+    /// Checks whether a world can be generated with these settings and the rooms of `map_id`.
+    pub fn validate(&self, room_store: &RoomStore) -> Result<(), &'static str> {
+        if self.world_width < 3 || self.world_height < 3 {
+            return Err("world must be at least 3x3 tiles");
+        }
+        if self.max_rooms == 0 {
+            return Err("max_rooms must be greater than 0");
+        }
+        if self.min_used_area as u64 > self.world_width as u64 * self.world_height as u64 {
+            return Err("min_used_area is larger than the world");
+        }
+        if self.cost_empty_space == 0
+            || self.cost_room == 0
+            || self.cost_room_interior == 0
+            || self.cost_hallway == 0
+            || self.cost_wall == 0
+        {
+            return Err("costs must be greater than 0");
+        }
+        if self.door_width == 0 {
+            return Err("door_width must be greater than 0");
+        }
+
+        let rooms = room_store.get_rooms(self.map_id);
+        if rooms.is_empty() {
+            return Err("there are no rooms with the map_id");
+        }
+        // rooms are placed at least a tile away from the world's borders
+        let largest = rooms
+            .iter()
+            .fold(UVec2::ZERO, |largest, (_, size)| largest.max(*size));
+        if largest.x + 2 >= self.world_width || largest.y + 2 >= self.world_height {
+            return Err("the largest room doesn't fit the world");
+        }
+
+        Ok(())
+    }
 }
 
 impl Default for WorldGeneratorSettings {
@@ -34,10 +95,15 @@ impl Default for WorldGeneratorSettings {
 
             cost_empty_space: 2,
             cost_room: 1,
+            cost_room_interior: 50,
             cost_hallway: 3,
             cost_wall: 20,
 
+            door_width: 2,
+
             map_id: 1,
+
+            seed: None,
         }
     }
 }
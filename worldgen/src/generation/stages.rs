@@ -6,7 +6,7 @@ use common::{
     FRect,
 };
 use pathfinding::directed::astar;
-use rand::{thread_rng, Rng};
+use rand::Rng;
 use vs_assets::rooms::RoomStore;
 
 use crate::{
@@ -14,11 +14,17 @@ use crate::{
     world::{CellType, IntermediateWorld},
 };
 
-use super::get_border_points;
+use super::{get_border_edges, get_border_points};
 
 pub trait WorldGenStage {
     fn get_description(&self) -> &'static str;
     fn execute(&mut self, world: &mut IntermediateWorld);
+
+    /// Name of the stage used by `WorldGenerator` to find stages by type.
+    /// Should not be overridden unless the stage is never looked up.
+    fn name(&self) -> &'static str {
+        std::any::type_name::<Self>()
+    }
 }
 
 pub struct WorldGenStage1GenRects<'a> {
@@ -102,11 +108,10 @@ impl WorldGenStage for WorldGenStage3MinSpanningTree {
 
         world.edges = min_spanning_tree(&prim_edges, start);
 
-        let mut rng = thread_rng();
         let mut extra_edges = 0;
         // add some random edges
         for edge in graph.edges.iter() {
-            if rng.gen_ratio(1, 200) {
+            if world.rng.gen_ratio(1, 200) {
                 world.edges_extra.push(PrimEdge::new(edge.u, edge.v));
                 extra_edges += 1;
             }
@@ -184,10 +189,15 @@ impl WorldGenStage for WorldGenStage5AStar {
                 found_paths += 1;
 
                 for point in &path.0 {
-                    world.grid[point.1 as usize][point.0 as usize] = CellType::Hallway;
-                    world.grid[point.1 as usize + 1][point.0 as usize] = CellType::Hallway;
-                    world.grid[point.1 as usize][point.0 as usize + 1] = CellType::Hallway;
-                    world.grid[point.1 as usize + 1][point.0 as usize + 1] = CellType::Hallway;
+                    // making the hallway 2 tiles wide without overwriting the rooms
+                    for (dx, dy) in [(0, 0), (0, 1), (1, 0), (1, 1)] {
+                        let x = point.0 as usize + dx;
+                        let y = point.1 as usize + dy;
+                        if y < world.height && x < world.width && world.grid[y][x] != CellType::Room
+                        {
+                            world.grid[y][x] = CellType::Hallway;
+                        }
+                    }
                 }
             }
         }
@@ -218,6 +228,75 @@ impl WorldGenStage for WorldGenStageCreateWalls {
     }
 }
 
+/// Replaces the holes the hallways punched through the rooms' walls with clean doors.
+/// Every run of hallway cells on a wall's edge is walled up again, except for
+/// a `door_width` wide opening in the middle of the run. The doors never take
+/// the corners of the room, so they always open into it.
+/// Runs after `WorldGenStage5AStar`.
+pub struct WorldGenStagePlaceDoors {}
+
+impl WorldGenStage for WorldGenStagePlaceDoors {
+    fn get_description(&self) -> &'static str {
+        "Placing doors where hallways meet rooms"
+    }
+
+    fn execute(&mut self, world: &mut IntermediateWorld) {
+        let door_width = world.settings.door_width as usize;
+        let mut doors = 0;
+
+        for room in &world.rooms {
+            for edge in get_border_edges(&room.rect) {
+                doors += place_doors(&mut world.grid, &edge, door_width);
+            }
+        }
+
+        info!("placed {} doors", doors);
+    }
+}
+
+/// Turns every run of hallway cells on the edge into a single door. Returns the number of doors.
+fn place_doors(grid: &mut [Vec<CellType>], edge: &[(i32, i32)], door_width: usize) -> usize {
+    // corners are excluded from the doors
+    if edge.len() < 3 {
+        return 0;
+    }
+    let door_width = door_width.min(edge.len() - 2);
+
+    let is_hallway = |grid: &[Vec<CellType>], (x, y): (i32, i32)| {
+        grid[y as usize][x as usize] == CellType::Hallway
+    };
+
+    let mut doors = 0;
+    let mut i = 0;
+    while i < edge.len() {
+        if !is_hallway(grid, edge[i]) {
+            i += 1;
+            continue;
+        }
+
+        let start = i;
+        while i < edge.len() && is_hallway(grid, edge[i]) {
+            i += 1;
+        }
+
+        for &(x, y) in &edge[start..i] {
+            grid[y as usize][x as usize] = CellType::Wall;
+        }
+
+        let center = (start + i) / 2;
+        let door_start = center
+            .saturating_sub(door_width / 2)
+            .clamp(1, edge.len() - 1 - door_width);
+        for &(x, y) in &edge[door_start..door_start + door_width] {
+            grid[y as usize][x as usize] = CellType::Hallway;
+        }
+
+        doors += 1;
+    }
+
+    doors
+}
+
 pub struct WorldGenStageCalcBitmapAndBitmask {}
 
 impl WorldGenStage for WorldGenStageCalcBitmapAndBitmask {
@@ -233,3 +312,113 @@ impl WorldGenStage for WorldGenStageCalcBitmapAndBitmask {
         world.bitmask = bitmask;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::math::Vec2;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+    use crate::generation::{room::WorldRoom, settings::WorldGeneratorSettings};
+
+    fn test_world(width: usize, height: usize, rooms: &[FRect]) -> IntermediateWorld {
+        IntermediateWorld {
+            width,
+            height,
+            settings: WorldGeneratorSettings {
+                world_width: width as u32,
+                world_height: height as u32,
+                ..WorldGeneratorSettings::with_seed(0)
+            },
+            rng: StdRng::seed_from_u64(0),
+            grid: vec![vec![CellType::None; width]; height],
+            rooms: rooms
+                .iter()
+                .map(|rect| WorldRoom {
+                    map_asset: Handle::default(),
+                    rect: *rect,
+                })
+                .collect(),
+            triangulation_graph: None,
+            edges: vec![],
+            edges_extra: vec![],
+            bitmap: vec![],
+            bitmask: vec![],
+        }
+    }
+
+    #[test]
+    fn hallway_goes_around_room_interior() {
+        let blocking = FRect::new(12., 4., 6., 12.);
+        let mut world = test_world(
+            30,
+            20,
+            &[
+                FRect::new(2., 8., 4., 4.),
+                FRect::new(24., 8., 4., 4.),
+                blocking,
+            ],
+        );
+        // connecting the centers of the side rooms with the blocking room right in between
+        world.edges = vec![PrimEdge::new(Vec2::new(4., 10.), Vec2::new(26., 10.))];
+
+        WorldGenStage4PlaceTiles {}.execute(&mut world);
+        WorldGenStageCreateWalls {}.execute(&mut world);
+        WorldGenStage5AStar {}.execute(&mut world);
+
+        assert!(world
+            .grid
+            .iter()
+            .flatten()
+            .any(|cell| *cell == CellType::Hallway));
+
+        // the walls may get widened hallways next to them, but the interior is left untouched
+        for y in blocking.top() as usize + 1..blocking.bottom() as usize {
+            for x in blocking.left() as usize + 1..blocking.right() as usize {
+                assert_eq!(
+                    world.grid[y][x],
+                    CellType::Room,
+                    "overwritten at ({}, {})",
+                    x,
+                    y
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn carved_connection_is_single_door() {
+        let room = FRect::new(4., 4., 8., 6.);
+        let mut world = test_world(16, 14, &[room]);
+        world.settings.door_width = 2;
+
+        WorldGenStage4PlaceTiles {}.execute(&mut world);
+        WorldGenStageCreateWalls {}.execute(&mut world);
+        // a widened hallway coming from the left punched 4 cells through the left wall
+        for y in 5..=8 {
+            for x in 0..=4 {
+                world.grid[y][x] = CellType::Hallway;
+            }
+        }
+
+        WorldGenStagePlaceDoors {}.execute(&mut world);
+
+        let left_wall: Vec<CellType> = (4..=10).map(|y| world.grid[y][4]).collect();
+        assert_eq!(
+            left_wall,
+            vec![
+                CellType::Wall,
+                CellType::Wall,
+                CellType::Hallway,
+                CellType::Hallway,
+                CellType::Wall,
+                CellType::Wall,
+                CellType::Wall,
+            ]
+        );
+        // the hallway itself and the rest of the room are left intact
+        assert!((5..=8).all(|y| world.grid[y][3] == CellType::Hallway));
+        assert!((4..=12).all(|x| world.grid[4][x] == CellType::Wall));
+        assert!((4..=10).all(|y| world.grid[y][12] == CellType::Wall));
+    }
+}
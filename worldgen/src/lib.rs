@@ -1,2 +1,3 @@
 pub mod generation;
+pub mod navigation;
 pub mod world;
@@ -0,0 +1,153 @@
+use bevy::prelude::*;
+use pathfinding::directed::astar;
+
+use crate::world::CellType;
+
+/// A navigation grid built from the world's `CellType` grid. `Room` and `Hallway` cells
+/// are walkable, `Wall` and `None` cells are impassable.
+///
+/// The cell `(0, 0)` starts at `origin` in world space and y goes up, the same way
+/// the world's tilemap is placed.
+#[derive(Resource, Debug, Clone)]
+pub struct NavGrid {
+    grid: Vec<Vec<CellType>>,
+    /// Size of a single cell in world units.
+    pub cell_size: Vec2,
+    /// World-space position of the (0, 0) cell's corner.
+    pub origin: Vec2,
+}
+
+impl NavGrid {
+    pub fn new(grid: Vec<Vec<CellType>>, cell_size: Vec2, origin: Vec2) -> Self {
+        Self {
+            grid,
+            cell_size,
+            origin,
+        }
+    }
+
+    /// Gets the width of the grid in cells.
+    pub fn width(&self) -> usize {
+        self.grid.first().map_or(0, |row| row.len())
+    }
+
+    /// Gets the height of the grid in cells.
+    pub fn height(&self) -> usize {
+        self.grid.len()
+    }
+
+    /// Gets the type of the cell or `None` if the cell is out of the grid.
+    pub fn get(&self, cell: IVec2) -> Option<CellType> {
+        if cell.x < 0 || cell.y < 0 {
+            return None;
+        }
+
+        self.grid
+            .get(cell.y as usize)
+            .and_then(|row| row.get(cell.x as usize))
+            .copied()
+    }
+
+    /// Checks whether the cell can be walked through.
+    pub fn is_passable(&self, cell: IVec2) -> bool {
+        matches!(self.get(cell), Some(CellType::Room | CellType::Hallway))
+    }
+
+    /// Gets the cell containing the world-space position.
+    pub fn world_to_cell(&self, position: Vec2) -> IVec2 {
+        ((position - self.origin) / self.cell_size)
+            .floor()
+            .as_ivec2()
+    }
+
+    /// Gets the world-space center of the cell.
+    pub fn cell_to_world(&self, cell: IVec2) -> Vec2 {
+        self.origin + (cell.as_vec2() + 0.5) * self.cell_size
+    }
+
+    /// Finds the shortest walkable path between two world-space positions using A*.
+    /// Returns the world-space centers of the cells on the path including the start and
+    /// the goal ones, which can be fed into `SteerPath::from_waypoints`.
+    /// Returns `None` if either position is not walkable or the goal is unreachable.
+    pub fn find_path(&self, start: Vec2, goal: Vec2) -> Option<Vec<Vec2>> {
+        let start = self.world_to_cell(start);
+        let goal = self.world_to_cell(goal);
+
+        if !self.is_passable(start) || !self.is_passable(goal) {
+            return None;
+        }
+
+        let (path, _cost) = astar::astar(
+            &start,
+            |cell| self.successors(*cell),
+            |cell| cell.x.abs_diff(goal.x) + cell.y.abs_diff(goal.y),
+            |cell| *cell == goal,
+        )?;
+
+        Some(
+            path.into_iter()
+                .map(|cell| self.cell_to_world(cell))
+                .collect(),
+        )
+    }
+
+    fn successors(&self, cell: IVec2) -> Vec<(IVec2, u32)> {
+        static DIRS: &[IVec2] = &[IVec2::X, IVec2::NEG_Y, IVec2::NEG_X, IVec2::Y];
+
+        DIRS.iter()
+            .map(|dir| cell + *dir)
+            .filter(|next| self.is_passable(*next))
+            .map(|next| (next, 1))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 5x5 room with a wall column at `x = 2` which has a gap at the top row.
+    fn walled_grid() -> NavGrid {
+        let mut grid = vec![vec![CellType::Room; 5]; 5];
+        for row in grid.iter_mut().take(4) {
+            row[2] = CellType::Wall;
+        }
+
+        NavGrid::new(grid, Vec2::splat(16.0), Vec2::ZERO)
+    }
+
+    #[test]
+    fn path_goes_around_wall() {
+        let nav = walled_grid();
+        let start = nav.cell_to_world(IVec2::new(0, 0));
+        let goal = nav.cell_to_world(IVec2::new(4, 0));
+
+        let path = nav.find_path(start, goal).unwrap();
+        let cells: Vec<IVec2> = path.iter().map(|p| nav.world_to_cell(*p)).collect();
+
+        assert_eq!(cells.first(), Some(&IVec2::new(0, 0)));
+        assert_eq!(cells.last(), Some(&IVec2::new(4, 0)));
+        // up to the gap, through it and down again
+        assert_eq!(cells.len(), 13);
+        assert!(cells.contains(&IVec2::new(2, 4)));
+        assert!(cells.iter().all(|cell| nav.is_passable(*cell)));
+        assert!(cells
+            .windows(2)
+            .all(|step| (step[1] - step[0]).abs().element_sum() == 1));
+    }
+
+    #[test]
+    fn no_path_to_walled_off_or_impassable_cells() {
+        let mut nav = walled_grid();
+        nav.grid[4][2] = CellType::Wall;
+        let start = nav.cell_to_world(IVec2::new(0, 0));
+
+        assert!(nav
+            .find_path(start, nav.cell_to_world(IVec2::new(4, 0)))
+            .is_none());
+        assert!(nav
+            .find_path(start, nav.cell_to_world(IVec2::new(2, 0)))
+            .is_none());
+        assert!(nav.find_path(start, Vec2::new(-10.0, 0.0)).is_none());
+    }
+}
@@ -3,10 +3,14 @@ use bevy_simple_tilemap::{Tile, TileMap};
 use colliders::Collider;
 use common::{delaunay2d::Delaunay2D, prim::PrimEdge, FRect};
 use physics::prelude::*;
+use rand::rngs::StdRng;
 use tiled::{Layer, TileLayer};
 use vs_assets::rooms::MapAsset;
 
-use crate::generation::{room::WorldRoom, settings::WorldGeneratorSettings};
+use crate::{
+    generation::{room::WorldRoom, settings::WorldGeneratorSettings},
+    navigation::NavGrid,
+};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum CellType {
@@ -23,6 +27,8 @@ pub struct World {
     pub height: usize,
     pub rooms: Vec<WorldRoom>,
     pub map_id: u32,
+    /// Cell types of the world's tiles, indexed as `grid[y][x]`.
+    pub grid: Vec<Vec<CellType>>,
 }
 
 impl World {
@@ -30,6 +36,21 @@ impl World {
         iw.into_world()
     }
 
+    /// Gets the world's rectangle in world space, placed at `origin` with tiles of `tile_size`.
+    pub fn bounds(&self, tile_size: Vec2, origin: Vec2) -> FRect {
+        FRect::new(
+            origin.x,
+            origin.y,
+            self.width as f32 * tile_size.x,
+            self.height as f32 * tile_size.y,
+        )
+    }
+
+    /// Creates a navigation grid of the world placed at `origin` with tiles of `tile_size`.
+    pub fn nav_grid(&self, tile_size: Vec2, origin: Vec2) -> NavGrid {
+        NavGrid::new(self.grid.clone(), tile_size, origin)
+    }
+
     pub fn fill_tilemap(&self, tilemap: &mut TileMap, assets: &Assets<MapAsset>, group_name: &str) {
         for room in &self.rooms {
             let offset_x = room.rect.x as i32;
@@ -64,63 +85,27 @@ impl World {
             let offset_y = room.rect.y * th;
             let room_height = room.rect.height * th;
 
-            /* let collision_rects = map_asset.get_collision_rects(collision_layer_name);
+            // Merging the collision tiles into as few rectangles as possible
+            // so the spatial hash doesn't get flooded with tiny colliders
+            let collision_rects = map_asset.get_collision_rects(collision_layer_name);
             for rect in &collision_rects {
-                collider_store.create_and_register(
-                    ColliderShapeType::Box {
-                        width: rect.width,
-                        height: rect.height,
-                    },
-                    Some(Vec2::new(offset_x + rect.x, offset_y + (height - rect.y))),
+                // Tiles are centered at their positions and the map's y goes down
+                let pos = Vec2::new(
+                    offset.x + offset_x + rect.x + (rect.width - tw) / 2.0,
+                    offset.y + offset_y + room_height - rect.y - (rect.height - th) / 2.0,
                 );
-            } */
 
-            let collision_layer = map_asset
-                .find_layer_by_name(collision_layer_name)
-                .unwrap_or_else(|| {
-                    panic!("invalid collision layer name: {}", collision_layer_name)
-                });
-
-            let collision_layer = collision_layer
-                .as_tile_layer()
-                .expect("expected a tile layer");
-            let w = collision_layer.width().expect("no width") as i32;
-            let h = collision_layer.height().expect("no height") as i32;
-
-            for y in 0..h {
-                for x in 0..w {
-                    let tile = collision_layer.get_tile(x, y);
-                    if tile.is_some() {
-                        let rect = FRect::new(
-                            offset.x + offset_x + x as f32 * tw,
-                            offset.y + offset_y + (room_height - y as f32 * th),
-                            tw,
-                            th,
-                        );
-
-                        /* collider_store.create_and_register(
-                            ColliderData {
-                                shape_type: ColliderShapeType::Box {
-                                    width: rect.width,
-                                    height: rect.height,
-                                },
-                                ..default()
-                            },
-                            Some(Vec2::new(rect.x, rect.y)),
-                        ); */
-                        commands.spawn((
-                            SpatialBundle {
-                                transform: Transform::from_xyz(rect.x, rect.y, 0.0),
-                                ..default()
-                            },
-                            Collider::new(shapes::ShapeType::Box {
-                                width: rect.width,
-                                height: rect.height,
-                            }),
-                            RigidBodyStatic,
-                        ));
-                    }
-                }
+                commands.spawn((
+                    SpatialBundle {
+                        transform: Transform::from_xyz(pos.x, pos.y, 0.0),
+                        ..default()
+                    },
+                    Collider::new(shapes::ShapeType::Box {
+                        width: rect.width,
+                        height: rect.height,
+                    }),
+                    RigidBodyStatic,
+                ));
             }
 
             // Add object colliders
@@ -136,42 +121,100 @@ impl World {
             let collision_layer = collision_layer
                 .as_object_layer()
                 .expect("expected an object layer");
+
+            // Converts a point of the map's space (y goes down) into the world's space
+            let to_world = |x: f32, y: f32| {
+                Vec2::new(
+                    offset.x + offset_x + x - 16.0,
+                    offset.y + offset_y + (room_height - y) + 16.0,
+                )
+            };
+
             for obj in collision_layer.objects() {
-                match obj.shape {
-                    tiled::ObjectShape::Rect { width, height } => {
-                        let pos = Vec2::new(
-                            offset.x + offset_x + obj.x + width / 2.0 - 16.0,
-                            offset.y + offset_y + (room_height - (obj.y + height / 2.0)) + 16.0,
-                        );
+                let (shape_type, pos, is_trigger) = match &obj.shape {
+                    tiled::ObjectShape::Rect { width, height } => (
+                        shapes::ShapeType::Box {
+                            width: *width,
+                            height: *height,
+                        },
+                        to_world(obj.x + width / 2.0, obj.y + height / 2.0),
+                        false,
+                    ),
+                    tiled::ObjectShape::Ellipse { width, height } => {
+                        if width != height {
+                            warn!(
+                                "ellipse collider {}x{} is approximated with a circle",
+                                width, height
+                            );
+                        }
 
-                        /* collider_store.create_and_register(
-                            ColliderData {
-                                shape_type: ColliderShapeType::Box { width, height },
-                                ..default()
-                            },
-                            Some(pos),
-                        ); */
-                        commands.spawn((
-                            SpatialBundle {
-                                transform: Transform::from_xyz(pos.x, pos.y, 0.0),
-                                ..default()
+                        (
+                            shapes::ShapeType::Circle {
+                                radius: (width + height) / 4.0,
                             },
-                            Collider::new(shapes::ShapeType::Box { width, height }),
-                            RigidBodyStatic,
-                        ));
-                    }
-                    tiled::ObjectShape::Ellipse { .. } => {
-                        unimplemented!("ellipse is not implemented")
+                            to_world(obj.x + width / 2.0, obj.y + height / 2.0),
+                            false,
+                        )
                     }
                     tiled::ObjectShape::Polyline { .. } => {
-                        unimplemented!("polyline is not implemented")
+                        warn!(
+                            "open polyline object {} can't be a collider, skipping it",
+                            obj.id()
+                        );
+                        continue;
+                    }
+                    // There are no polygon colliders yet, so approximating with the bounding box
+                    tiled::ObjectShape::Polygon { points } => {
+                        if points.is_empty() {
+                            continue;
+                        }
+
+                        warn!(
+                            "polygon collider {} is approximated with its bounding box",
+                            obj.id()
+                        );
+
+                        let (min, max) = points.iter().fold(
+                            (Vec2::splat(f32::MAX), Vec2::splat(f32::MIN)),
+                            |(min, max), (x, y)| {
+                                let p = Vec2::new(*x, *y);
+                                (min.min(p), max.max(p))
+                            },
+                        );
+                        let size = max - min;
+                        let center = Vec2::new(obj.x, obj.y) + (min + max) / 2.0;
+
+                        (
+                            shapes::ShapeType::Box {
+                                width: size.x,
+                                height: size.y,
+                            },
+                            to_world(center.x, center.y),
+                            false,
+                        )
                     }
-                    tiled::ObjectShape::Polygon { .. } => {
-                        unimplemented!("polygon is not implemented")
+                    tiled::ObjectShape::Point(x, y) => (
+                        shapes::ShapeType::Circle { radius: 1.0 },
+                        to_world(*x, *y),
+                        true,
+                    ),
+                    tiled::ObjectShape::Text { .. } => {
+                        warn!("text object {} can't be a collider, skipping it", obj.id());
+                        continue;
                     }
-                    tiled::ObjectShape::Point(_, _) => unimplemented!("point is not implemented"),
-                    tiled::ObjectShape::Text { .. } => unimplemented!("text is not implemented"),
-                }
+                };
+
+                let mut collider = Collider::new(shape_type);
+                collider.is_trigger = is_trigger;
+
+                commands.spawn((
+                    SpatialBundle {
+                        transform: Transform::from_xyz(pos.x, pos.y, 0.0),
+                        ..default()
+                    },
+                    collider,
+                    RigidBodyStatic,
+                ));
             }
         }
     }
@@ -239,6 +282,9 @@ pub struct IntermediateWorld {
     pub width: usize,
     pub height: usize,
     pub settings: WorldGeneratorSettings,
+    /// Seeded from `WorldGeneratorSettings::seed`. All the generation stages should use it
+    /// instead of `thread_rng()` so the same seed produces the same world.
+    pub rng: StdRng,
     pub grid: Vec<Vec<CellType>>,
     pub rooms: Vec<WorldRoom>,
     pub triangulation_graph: Option<Delaunay2D>,
@@ -255,6 +301,143 @@ impl IntermediateWorld {
             height: self.height,
             rooms: self.rooms,
             map_id: self.settings.map_id,
+            grid: self.grid,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use bevy::ecs::world::CommandQueue;
+
+    use super::*;
+
+    /// Creates a 4x3 map of 32x32 tiles with the `collision` tile layer filled from `rows`
+    /// and the `collision_fine` object layer made of `objects`.
+    fn map_asset(rows: [[u32; 4]; 3], objects: &str) -> MapAsset {
+        let csv = rows
+            .iter()
+            .map(|row| row.map(|gid| gid.to_string()).join(","))
+            .collect::<Vec<_>>()
+            .join(",\n");
+        let tmx = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<map version="1.10" orientation="orthogonal" renderorder="right-down" width="4" height="3" tilewidth="32" tileheight="32" infinite="0">
+ <tileset firstgid="1" name="walls" tilewidth="32" tileheight="32" tilecount="1" columns="1"/>
+ <layer id="1" name="collision" width="4" height="3">
+  <data encoding="csv">
+{}
+  </data>
+ </layer>
+ <objectgroup id="2" name="collision_fine">
+{}
+ </objectgroup>
+</map>"#,
+            csv, objects
+        );
+
+        let reader = move |_: &std::path::Path| {
+            Ok::<_, std::io::Error>(Cursor::new(tmx.clone().into_bytes()))
+        };
+        let map = tiled::Loader::with_reader(reader)
+            .load_tmx_map("room.tmx")
+            .expect("could not load the map");
+
+        MapAsset {
+            name: "room".to_string(),
+            map_id: 1,
+            map,
+        }
+    }
+
+    /// Spawns the colliders of a world made of a single room with the map.
+    fn spawn_colliders(map_asset: MapAsset) -> Vec<(Collider, Vec2)> {
+        let mut assets = Assets::<MapAsset>::default();
+        let world = World {
+            width: 4,
+            height: 3,
+            rooms: vec![WorldRoom {
+                map_asset: assets.add(map_asset),
+                rect: FRect::new(0., 0., 4., 3.),
+            }],
+            map_id: 1,
+            grid: vec![vec![CellType::None; 4]; 3],
+        };
+
+        let mut ecs_world = bevy::ecs::world::World::new();
+        let mut queue = CommandQueue::default();
+        let mut commands = Commands::new(&mut queue, &ecs_world);
+        world.add_colliders(
+            &assets,
+            "collision",
+            "collision_fine",
+            &mut commands,
+            Vec2::ZERO,
+        );
+        queue.apply(&mut ecs_world);
+
+        ecs_world
+            .query::<(&Collider, &Transform)>()
+            .iter(&ecs_world)
+            .map(|(collider, transform)| (collider.clone(), transform.translation.xy()))
+            .collect()
+    }
+
+    #[test]
+    fn wall_strip_is_one_collider() {
+        let colliders = spawn_colliders(map_asset([[0, 0, 0, 0], [1, 1, 1, 1], [0, 0, 0, 0]], ""));
+
+        assert_eq!(colliders.len(), 1);
+        let (collider, position) = &colliders[0];
+        assert_eq!(
+            collider.shape.shape_type,
+            shapes::ShapeType::Box {
+                width: 128.,
+                height: 32.
+            }
+        );
+        assert_eq!(*position, Vec2::new(48., 64.));
+    }
+
+    #[test]
+    fn ellipse_object_is_circle_collider() {
+        let colliders = spawn_colliders(map_asset(
+            [[0; 4]; 3],
+            r#"<object id="1" x="32" y="32" width="16" height="16"><ellipse/></object>
+<object id="2" x="0" y="0" width="32" height="16"><text>label</text></object>"#,
+        ));
+
+        // the text object is skipped
+        assert_eq!(colliders.len(), 1);
+        let (collider, position) = &colliders[0];
+        assert_eq!(
+            collider.shape.shape_type,
+            shapes::ShapeType::Circle { radius: 8. }
+        );
+        assert!(!collider.is_trigger);
+        assert_eq!(*position, Vec2::new(24., 72.));
+    }
+
+    #[test]
+    fn polygon_object_is_bounding_box_and_polyline_is_skipped() {
+        let colliders = spawn_colliders(map_asset(
+            [[0; 4]; 3],
+            r#"<object id="1" x="32" y="32"><polygon points="0,0 32,0 0,16"/></object>
+<object id="2" x="0" y="0"><polyline points="0,0 64,0 64,32"/></object>"#,
+        ));
+
+        // the open polyline is skipped
+        assert_eq!(colliders.len(), 1);
+        let (collider, position) = &colliders[0];
+        assert_eq!(
+            collider.shape.shape_type,
+            shapes::ShapeType::Box {
+                width: 32.,
+                height: 16.
+            }
+        );
+        assert_eq!(*position, Vec2::new(32., 72.));
+    }
+}